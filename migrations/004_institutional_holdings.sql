@@ -0,0 +1,55 @@
+-- 13F-HR filings, one row per filing (amendments and restatements included)
+CREATE TABLE institutional_filings (
+    accession_number VARCHAR(20) PRIMARY KEY,
+    filer_cik VARCHAR(10) NOT NULL,
+    filer_name TEXT NOT NULL,
+    period_of_report DATE NOT NULL,
+    filing_date DATE NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+-- Information table rows of a 13F-HR filing, values in USD
+CREATE TABLE institutional_holdings (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    accession_number VARCHAR(20) NOT NULL REFERENCES institutional_filings(accession_number) ON DELETE CASCADE,
+    cusip VARCHAR(9) NOT NULL,
+    issuer_name TEXT NOT NULL,
+    title_of_class TEXT NOT NULL,
+    value BIGINT NOT NULL,
+    shares BIGINT NOT NULL,
+    share_type VARCHAR(3) NOT NULL,
+    put_call VARCHAR(4),
+    investment_discretion VARCHAR(4) NOT NULL,
+    voting_sole BIGINT NOT NULL DEFAULT 0,
+    voting_shared BIGINT NOT NULL DEFAULT 0,
+    voting_none BIGINT NOT NULL DEFAULT 0
+);
+
+CREATE INDEX idx_institutional_filings_filer_period
+    ON institutional_filings(filer_cik, period_of_report);
+CREATE INDEX idx_institutional_holdings_accession_number
+    ON institutional_holdings(accession_number);
+CREATE INDEX idx_institutional_holdings_cusip
+    ON institutional_holdings(cusip);
+
+-- Share positions per filer, quarter and CUSIP, from the latest filing for each quarter.
+-- Options (put/call rows) and principal amounts are left out.
+CREATE VIEW institutional_positions AS
+WITH latest_filings AS (
+    SELECT DISTINCT ON (filer_cik, period_of_report)
+        accession_number, filer_cik, filer_name, period_of_report
+    FROM institutional_filings
+    ORDER BY filer_cik, period_of_report, filing_date DESC, accession_number DESC
+)
+SELECT
+    f.filer_cik,
+    f.filer_name,
+    f.period_of_report,
+    h.cusip,
+    MAX(h.issuer_name) AS issuer_name,
+    SUM(h.shares)::BIGINT AS shares,
+    SUM(h.value)::BIGINT AS value
+FROM latest_filings f
+JOIN institutional_holdings h ON h.accession_number = f.accession_number
+WHERE h.put_call IS NULL AND h.share_type = 'SH'
+GROUP BY f.filer_cik, f.filer_name, f.period_of_report, h.cusip;
@@ -0,0 +1,13 @@
+-- Company information from the EDGAR submissions API, one row per filer.
+-- The full profile (addresses, former names, ...) is kept as JSON.
+CREATE TABLE company_profiles (
+    cik VARCHAR(10) PRIMARY KEY,
+    name TEXT NOT NULL,
+    sic VARCHAR(4),
+    fiscal_year_end VARCHAR(4),
+    state_of_incorporation TEXT,
+    profile JSONB NOT NULL,
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX idx_company_profiles_sic ON company_profiles(sic);
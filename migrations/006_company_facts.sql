@@ -0,0 +1,32 @@
+-- Concepts a company reported XBRL facts for, from the companyfacts API or bulk archive
+CREATE TABLE company_fact_concepts (
+    cik VARCHAR(10) NOT NULL,
+    taxonomy TEXT NOT NULL,
+    concept TEXT NOT NULL,
+    label TEXT,
+    description TEXT,
+    PRIMARY KEY (cik, taxonomy, concept)
+);
+
+-- Reported values of a concept, one row per unit, period and reporting filing.
+-- Instant (balance sheet) facts have no period_start.
+CREATE TABLE company_facts (
+    cik VARCHAR(10) NOT NULL,
+    taxonomy TEXT NOT NULL,
+    concept TEXT NOT NULL,
+    unit TEXT NOT NULL,
+    period_start DATE,
+    period_end DATE NOT NULL,
+    value DOUBLE PRECISION NOT NULL,
+    accession_number VARCHAR(20) NOT NULL,
+    fiscal_year INTEGER,
+    fiscal_period TEXT,
+    form TEXT NOT NULL,
+    filed DATE NOT NULL,
+    frame TEXT
+);
+
+CREATE INDEX idx_company_facts_cik_concept
+    ON company_facts(cik, taxonomy, concept, unit);
+CREATE INDEX idx_company_facts_frame
+    ON company_facts(taxonomy, concept, unit, frame) WHERE frame IS NOT NULL;
@@ -0,0 +1,69 @@
+-- SEC Financial Statement Data Sets: quarterly dumps of the face financials of every XBRL filing.
+-- `dataset` is the quarter the filing was published in, e.g. `2024q1`.
+CREATE TABLE fsds_submissions (
+    adsh VARCHAR(20) PRIMARY KEY,
+    dataset VARCHAR(6) NOT NULL,
+    cik VARCHAR(10) NOT NULL,
+    name TEXT NOT NULL,
+    sic VARCHAR(4),
+    form TEXT NOT NULL,
+    period DATE,
+    fy INTEGER,
+    fp TEXT,
+    fye VARCHAR(4),
+    filed DATE NOT NULL,
+    afs TEXT,
+    prevrpt BOOLEAN NOT NULL DEFAULT FALSE,
+    instance TEXT
+);
+
+-- Tags used by the filings, standard taxonomy tags and company extensions alike
+CREATE TABLE fsds_tags (
+    tag TEXT NOT NULL,
+    version TEXT NOT NULL,
+    custom BOOLEAN NOT NULL,
+    abstract BOOLEAN NOT NULL,
+    datatype TEXT,
+    iord CHAR(1),
+    crdr CHAR(1),
+    tlabel TEXT,
+    doc TEXT,
+    PRIMARY KEY (tag, version)
+);
+
+-- Numeric values, `qtrs` is the duration in quarters (0 for instants) ending on `ddate`.
+-- The CIK is copied from the submission so screens don't need a join.
+CREATE TABLE fsds_numbers (
+    adsh VARCHAR(20) NOT NULL REFERENCES fsds_submissions(adsh) ON DELETE CASCADE,
+    cik VARCHAR(10) NOT NULL,
+    tag TEXT NOT NULL,
+    version TEXT NOT NULL,
+    ddate DATE NOT NULL,
+    qtrs INTEGER NOT NULL,
+    uom TEXT NOT NULL,
+    segments TEXT,
+    coreg TEXT,
+    value DOUBLE PRECISION,
+    footnote TEXT
+);
+
+-- Where each value appears in the statements of a filing
+CREATE TABLE fsds_presentations (
+    adsh VARCHAR(20) NOT NULL REFERENCES fsds_submissions(adsh) ON DELETE CASCADE,
+    report INTEGER NOT NULL,
+    line INTEGER NOT NULL,
+    stmt VARCHAR(2) NOT NULL,
+    inpth BOOLEAN NOT NULL,
+    rfile CHAR(1),
+    tag TEXT NOT NULL,
+    version TEXT NOT NULL,
+    plabel TEXT,
+    negating BOOLEAN NOT NULL,
+    PRIMARY KEY (adsh, report, line)
+);
+
+CREATE INDEX idx_fsds_submissions_cik ON fsds_submissions(cik);
+CREATE INDEX idx_fsds_submissions_dataset ON fsds_submissions(dataset);
+CREATE INDEX idx_fsds_numbers_adsh_tag ON fsds_numbers(adsh, tag);
+CREATE INDEX idx_fsds_numbers_cik_tag ON fsds_numbers(cik, tag, ddate, qtrs);
+CREATE INDEX idx_fsds_numbers_tag_period ON fsds_numbers(tag, ddate, qtrs);
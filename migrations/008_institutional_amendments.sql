@@ -0,0 +1,37 @@
+-- Amendment type from the cover page of a 13F-HR/A: a RESTATEMENT replaces the report of the
+-- quarter, NEW HOLDINGS only adds rows to it. NULL for original reports.
+ALTER TABLE institutional_filings ADD COLUMN amendment_type TEXT;
+
+-- Share positions per filer, quarter and CUSIP, from the latest original report or restatement
+-- of each quarter plus the NEW HOLDINGS amendments filed after it.
+-- Options (put/call rows) and principal amounts are left out.
+CREATE OR REPLACE VIEW institutional_positions AS
+WITH reports AS (
+    SELECT DISTINCT ON (filer_cik, period_of_report)
+        accession_number, filer_cik, filer_name, period_of_report, filing_date
+    FROM institutional_filings
+    WHERE amendment_type IS DISTINCT FROM 'NEW HOLDINGS'
+    ORDER BY filer_cik, period_of_report, filing_date DESC, accession_number DESC
+),
+report_filings AS (
+    SELECT accession_number, filer_cik, filer_name, period_of_report
+    FROM reports
+    UNION ALL
+    SELECT a.accession_number, r.filer_cik, r.filer_name, r.period_of_report
+    FROM institutional_filings a
+    JOIN reports r ON r.filer_cik = a.filer_cik AND r.period_of_report = a.period_of_report
+    WHERE a.amendment_type = 'NEW HOLDINGS'
+        AND (a.filing_date, a.accession_number) > (r.filing_date, r.accession_number)
+)
+SELECT
+    f.filer_cik,
+    f.filer_name,
+    f.period_of_report,
+    h.cusip,
+    MAX(h.issuer_name) AS issuer_name,
+    SUM(h.shares)::BIGINT AS shares,
+    SUM(h.value)::BIGINT AS value
+FROM report_filings f
+JOIN institutional_holdings h ON h.accession_number = f.accession_number
+WHERE h.put_call IS NULL AND h.share_type = 'SH'
+GROUP BY f.filer_cik, f.filer_name, f.period_of_report, h.cusip;
@@ -0,0 +1,11 @@
+-- CUSIPs of a ticker's securities as reported in 13F information tables. When a ticker has
+-- mapped CUSIPs they are used instead of matching 13F issuer names.
+CREATE TABLE issuer_cusips (
+    ticker VARCHAR(10) NOT NULL,
+    cusip VARCHAR(9) NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (ticker, cusip)
+);
+
+CREATE INDEX idx_institutional_holdings_issuer_name
+    ON institutional_holdings(UPPER(issuer_name));
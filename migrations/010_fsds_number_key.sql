@@ -0,0 +1,20 @@
+-- A data set value is identified by its filing, tag, period, unit, dimensions and
+-- co-registrant, the key of num.txt. Values without dimensions or co-registrant have NULLs
+-- there, which must still collide, so the index treats NULLs as equal.
+DELETE FROM fsds_numbers a
+USING fsds_numbers b
+WHERE a.ctid < b.ctid
+  AND a.adsh = b.adsh
+  AND a.tag = b.tag
+  AND a.version = b.version
+  AND a.ddate = b.ddate
+  AND a.qtrs = b.qtrs
+  AND a.uom = b.uom
+  AND a.segments IS NOT DISTINCT FROM b.segments
+  AND a.coreg IS NOT DISTINCT FROM b.coreg;
+
+CREATE UNIQUE INDEX idx_fsds_numbers_key
+    ON fsds_numbers(adsh, tag, version, ddate, qtrs, uom, segments, coreg) NULLS NOT DISTINCT;
+
+-- Covered by the key
+DROP INDEX idx_fsds_numbers_adsh_tag;
@@ -1,10 +1,9 @@
 use advisor::{
     core::{config::AdvisorConfig, init},
-    edgar::filing,
+    edgar::{bulk, client::EdgarClient, filing, holdings, profile},
     eval,
     memory::{ConversationChainManager, ConversationManager, MessageRole},
     repl::{self, EditorWithHistory},
-    utils::dirs,
 };
 use colored::*;
 use crossterm::execute;
@@ -26,6 +25,8 @@ async fn handle_command(
     conversation_manager: &Arc<RwLock<ConversationManager>>,
     chain_manager: &mut ConversationChainManager,
     llm: OpenAI<OpenAIConfig>,
+    pg_pool: &sqlx::Pool<sqlx::Postgres>,
+    edgar_client: &EdgarClient,
 ) -> Result<(), Box<dyn Error>> {
     match cmd {
         "/history" => {
@@ -129,6 +130,105 @@ async fn handle_command(
                 .switch_conversation(&uuid)
                 .await?;
         }
+        _ if cmd.starts_with("/holders") => {
+            // /holders @TICKER [YYYY-MM-DD]
+            let mut args = cmd.split_whitespace().skip(1);
+            let Some(ticker) = args
+                .next()
+                .map(|t| t.trim_start_matches('@').to_uppercase())
+            else {
+                println!("Usage: /holders @TICKER [quarter end, e.g. 2024-09-30]");
+                return Ok(());
+            };
+            let period = args
+                .next()
+                .map(|d| chrono::NaiveDate::parse_from_str(d, "%Y-%m-%d"))
+                .transpose()?;
+
+            match holdings::institutional_activity_for_ticker(
+                edgar_client,
+                pg_pool,
+                &ticker,
+                period,
+            )
+            .await
+            {
+                Ok(summary) => println!("\n{}", summary),
+                Err(e) => eprintln!("Error: {}", e),
+            }
+        }
+        _ if cmd.starts_with("/cusip") => {
+            // /cusip @TICKER CUSIP ... maps 13F CUSIPs to a ticker for /holders
+            let mut args = cmd.split_whitespace().skip(1);
+            let ticker = args
+                .next()
+                .map(|t| t.trim_start_matches('@').to_uppercase());
+            let cusips = args.map(|c| c.to_uppercase()).collect::<Vec<_>>();
+            let Some(ticker) = ticker.filter(|_| !cusips.is_empty()) else {
+                println!("Usage: /cusip @TICKER CUSIP [CUSIP ...], e.g. /cusip @NVDA 67066G104");
+                return Ok(());
+            };
+            if let Some(invalid) = cusips.iter().find(|c| !holdings::is_cusip(c)) {
+                eprintln!("Error: {} is not a CUSIP", invalid);
+                return Ok(());
+            }
+
+            match advisor::db::map_issuer_cusips(pg_pool, &ticker, &cusips).await {
+                Ok(()) => println!("Mapped {} to {}", ticker, cusips.join(", ")),
+                Err(e) => eprintln!("Error: {}", e),
+            }
+        }
+        _ if cmd.starts_with("/profile") => {
+            // /profile @TICKER
+            let Some(ticker) = cmd.split_whitespace().nth(1) else {
+                println!("Usage: /profile @TICKER");
+                return Ok(());
+            };
+
+            match profile::company_profile_for_ticker(edgar_client, pg_pool, ticker).await {
+                Ok(profile) => println!("\n{}", profile.to_markdown()),
+                Err(e) => eprintln!("Error: {}", e),
+            }
+        }
+        _ if cmd.starts_with("/import") => {
+            // /import PATH ... loads downloaded submissions.zip / companyfacts.zip / 2024q1.zip /
+            // 2024q3_form13f.zip archives
+            let paths = cmd.split_whitespace().skip(1).collect::<Vec<_>>();
+            if paths.is_empty() {
+                println!(
+                    "Usage: /import path/to/submissions.zip [path/to/companyfacts.zip] [path/to/2024q1.zip] [path/to/2024q3_form13f.zip]"
+                );
+                return Ok(());
+            }
+
+            for path in paths {
+                match bulk::import_archive(edgar_client, pg_pool, std::path::Path::new(path)).await
+                {
+                    Ok((archive, summary)) => {
+                        println!("Imported {} from {}: {}", archive, path, summary)
+                    }
+                    Err(e) => eprintln!("Error importing {}: {}", path, e),
+                }
+            }
+        }
+        _ if cmd.starts_with("/refresh") => {
+            // /refresh [@TICKER ...] re-downloads the ticker list and the tickers' filing indexes
+            let tickers = cmd
+                .split_whitespace()
+                .skip(1)
+                .map(|t| t.trim_start_matches('@').to_uppercase())
+                .collect::<Vec<_>>();
+
+            match filing::refresh_filings(edgar_client, &tickers).await {
+                Ok(counts) => {
+                    println!("Refreshed ticker list");
+                    for (ticker, count) in counts {
+                        println!("Refreshed {}: {} filings", ticker, count);
+                    }
+                }
+                Err(e) => eprintln!("Error refreshing EDGAR data: {}", e),
+            }
+        }
         _ => {}
     }
     Ok(())
@@ -173,6 +273,7 @@ async fn main() -> Result<(), Box<dyn Error>> {
     log::debug!("Logger initialized");
 
     let config = AdvisorConfig::from_env()?;
+    let edgar_client = EdgarClient::new(&config)?;
 
     let llm = init::initialize_openai(&config).await?;
     let store = init::initialize_vector_store(&config).await?;
@@ -182,8 +283,11 @@ async fn main() -> Result<(), Box<dyn Error>> {
         .connect(&config.database_url)
         .await?;
 
-    log::debug!("Creating data directory at {}", dirs::EDGAR_FILINGS_DIR);
-    fs::create_dir_all(dirs::EDGAR_FILINGS_DIR)?;
+    log::debug!(
+        "Creating data directory at {}",
+        edgar_client.filings_dir().display()
+    );
+    fs::create_dir_all(edgar_client.filings_dir())?;
 
     println!("Enter 'quit' to exit");
     let token_usage = Arc::new(advisor::TokenUsage::default());
@@ -194,15 +298,10 @@ async fn main() -> Result<(), Box<dyn Error>> {
         conversation_manager.clone(),
         Arc::new(chain_manager.clone()),
         llm.clone(),
+        &edgar_client,
     )
     .await?;
 
-    let http_client = reqwest::Client::builder()
-        .user_agent(filing::USER_AGENT)
-        .timeout(std::time::Duration::from_secs(30))
-        .connect_timeout(std::time::Duration::from_secs(10))
-        .build()?;
-
     let recent_conv = conversation_manager.get_most_recent_conversation().await?;
     match recent_conv {
         Some(conv) => {
@@ -283,6 +382,8 @@ async fn main() -> Result<(), Box<dyn Error>> {
                         &conversation_manager,
                         &mut chain_manager,
                         llm.clone(),
+                        &pg_pool,
+                        &edgar_client,
                     )
                     .await?;
                     continue;
@@ -292,7 +393,7 @@ async fn main() -> Result<(), Box<dyn Error>> {
                     match eval::eval(
                         &input,
                         &conv,
-                        &http_client,
+                        &edgar_client,
                         &llm.clone(),
                         store.clone(),
                         conversation_manager_for_eval.clone(),
@@ -1,6 +1,11 @@
-use advisor::{auth::AuthUser, core::config::AdvisorConfig, memory::ConversationManager};
+use advisor::{
+    auth::AuthUser,
+    core::config::AdvisorConfig,
+    edgar::{client::EdgarClient, filing, ticker_index},
+    memory::ConversationManager,
+};
 use axum::{
-    extract::State,
+    extract::{Query, State},
     http::StatusCode,
     routing::{delete, get, post},
     Json, Router,
@@ -21,6 +26,31 @@ struct CreateConversationResponse {
     id: Uuid,
 }
 
+#[derive(Deserialize)]
+struct RefreshRequest {
+    #[serde(default)]
+    tickers: Vec<String>,
+}
+
+#[derive(Serialize)]
+struct RefreshResponse {
+    ticker: String,
+    filings: usize,
+}
+
+#[derive(Deserialize)]
+struct CompanySearchParams {
+    /// Ticker, company name or CIK
+    q: String,
+    exchange: Option<String>,
+    #[serde(default = "default_search_limit")]
+    limit: usize,
+}
+
+fn default_search_limit() -> usize {
+    10
+}
+
 #[derive(Serialize)]
 struct ConversationResponse {
     id: Uuid,
@@ -34,6 +64,7 @@ struct ConversationResponse {
 #[derive(Debug, Clone)]
 struct AppState {
     pool: PgPool,
+    edgar_client: EdgarClient,
 }
 
 // Health check endpoint
@@ -138,6 +169,43 @@ async fn switch_conversation(
     Ok(StatusCode::OK)
 }
 
+// Re-download the ticker list and the filing indexes of the requested tickers
+#[axum::debug_handler]
+async fn refresh_edgar(
+    State(state): State<AppState>,
+    _auth_user: AuthUser,
+    Json(req): Json<RefreshRequest>,
+) -> Result<Json<Vec<RefreshResponse>>, (StatusCode, String)> {
+    let counts = filing::refresh_filings(&state.edgar_client, &req.tickers)
+        .await
+        .map_err(|e| (StatusCode::BAD_GATEWAY, e.to_string()))?;
+
+    Ok(Json(
+        counts
+            .into_iter()
+            .map(|(ticker, filings)| RefreshResponse { ticker, filings })
+            .collect(),
+    ))
+}
+
+// Search companies by ticker, name or CIK, e.g. `/edgar/companies?q=apple&exchange=Nasdaq`
+#[axum::debug_handler]
+async fn search_companies(
+    State(state): State<AppState>,
+    _auth_user: AuthUser,
+    Query(params): Query<CompanySearchParams>,
+) -> Result<Json<Vec<ticker_index::SearchHit>>, (StatusCode, String)> {
+    let index = ticker_index::fetch_ticker_index(&state.edgar_client)
+        .await
+        .map_err(|e| (StatusCode::BAD_GATEWAY, e.to_string()))?;
+
+    Ok(Json(index.search(
+        &params.q,
+        params.exchange.as_deref(),
+        params.limit,
+    )))
+}
+
 #[tokio::main]
 async fn main() -> anyhow::Result<()> {
     dotenv::dotenv().ok();
@@ -151,8 +219,10 @@ async fn main() -> anyhow::Result<()> {
         .connect(&config.database_url)
         .await?;
 
+    let edgar_client = EdgarClient::new(&config)?;
+
     // Store the database pool in the app state
-    let app_state = AppState { pool };
+    let app_state = AppState { pool, edgar_client };
 
     // Build router with all routes
     let app = Router::new()
@@ -161,6 +231,8 @@ async fn main() -> anyhow::Result<()> {
         .route("/conversations", get(list_conversations))
         .route("/conversations/:id", delete(delete_conversation))
         .route("/conversations/:id/switch", post(switch_conversation))
+        .route("/edgar/refresh", post(refresh_edgar))
+        .route("/edgar/companies", get(search_companies))
         .with_state(app_state);
 
     // Run server
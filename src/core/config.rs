@@ -1,5 +1,6 @@
 use anyhow::{anyhow, Result};
 use std::path::PathBuf;
+use std::time::Duration;
 
 #[derive(Clone, Debug)]
 pub struct AdvisorConfig {
@@ -7,6 +8,8 @@ pub struct AdvisorConfig {
     pub database_url: String,
     pub user_agent: String,
     pub data_dir: PathBuf,
+    pub edgar_requests_per_second: u32,
+    pub edgar_cache_ttl: Duration,
 }
 
 impl AdvisorConfig {
@@ -24,11 +27,28 @@ impl AdvisorConfig {
             std::env::var("ADVISOR_DATA_DIR").unwrap_or_else(|_| "data".to_string())
         );
 
+        let edgar_requests_per_second = match std::env::var("EDGAR_REQUESTS_PER_SECOND") {
+            Ok(rate) => rate
+                .parse()
+                .map_err(|_| anyhow!("Invalid EDGAR_REQUESTS_PER_SECOND: {}", rate))?,
+            Err(_) => crate::edgar::client::DEFAULT_REQUESTS_PER_SECOND,
+        };
+
+        let edgar_cache_ttl = match std::env::var("EDGAR_CACHE_TTL_SECS") {
+            Ok(secs) => Duration::from_secs(
+                secs.parse()
+                    .map_err(|_| anyhow!("Invalid EDGAR_CACHE_TTL_SECS: {}", secs))?,
+            ),
+            Err(_) => crate::edgar::client::DEFAULT_CACHE_TTL,
+        };
+
         Ok(Self {
             openai_key,
             database_url,
             user_agent,
             data_dir,
+            edgar_requests_per_second,
+            edgar_cache_ttl,
         })
     }
 }
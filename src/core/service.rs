@@ -1,12 +1,11 @@
 use super::types::{AdvisorBackend, ConversationInfo};
-use crate::{eval, memory::ConversationManager};
+use crate::{edgar::client::EdgarClient, eval, memory::ConversationManager};
 use anyhow::Result;
 use futures::stream::BoxStream;
 use langchain_rust::{
     llm::{OpenAI, OpenAIConfig},
     vectorstore::pgvector::Store,
 };
-use reqwest::Client;
 use sqlx::{Pool, Postgres};
 use std::{error::Error, sync::Arc};
 use tokio::sync::RwLock;
@@ -15,7 +14,7 @@ use uuid::Uuid;
 pub struct AdvisorService {
     conversation_manager: Arc<RwLock<ConversationManager>>,
     store: Arc<Store>,
-    http_client: Client,
+    edgar_client: EdgarClient,
     llm: OpenAI<OpenAIConfig>,
     pg_pool: Pool<Postgres>,
 }
@@ -24,14 +23,14 @@ impl AdvisorService {
     pub fn new(
         conversation_manager: ConversationManager,
         store: Arc<Store>,
-        http_client: Client,
+        edgar_client: EdgarClient,
         llm: OpenAI<OpenAIConfig>,
         pg_pool: Pool<Postgres>,
     ) -> Self {
         Self {
             conversation_manager: Arc::new(RwLock::new(conversation_manager)),
             store,
-            http_client,
+            edgar_client,
             llm,
             pg_pool,
         }
@@ -56,7 +55,7 @@ impl AdvisorBackend for AdvisorService {
         let (stream, summary) = eval::eval(
             input,
             &conversation,
-            &self.http_client,
+            &self.edgar_client,
             &self.llm,
             Arc::clone(&self.store),
             self.conversation_manager.clone(),
@@ -1,7 +1,12 @@
+use crate::edgar::datasets::{DatasetNumber, DatasetPresentation, DatasetSubmission, DatasetTag};
+use crate::edgar::facts::CompanyFacts;
+use crate::edgar::holdings::{HolderPosition, Holding, HoldingsReport, PositionChange};
+use crate::edgar::profile::CompanyProfile;
 use crate::memory::{Conversation, Message, MessageRole};
 use anyhow::Result;
+use chrono::NaiveDate;
 use serde_json::Value;
-use sqlx::{query, query_as, Pool, Postgres};
+use sqlx::{query, query_as, Pool, Postgres, Transaction};
 use uuid::Uuid;
 
 pub const COLLECTIONS_TABLE: &str = "vs_collections";
@@ -180,6 +185,640 @@ pub async fn count_vectors_with_filepath(pool: &Pool<Postgres>, filepath: &str)
     Ok(row.0)
 }
 
+// Institutional Holdings Database Operations
+
+/// Store a parsed 13F-HR filing, replacing any earlier copy of the same accession number
+pub async fn insert_holdings_report(pool: &Pool<Postgres>, report: &HoldingsReport) -> Result<()> {
+    let mut tx = pool.begin().await?;
+    replace_institutional_filings(&mut tx, std::slice::from_ref(report)).await?;
+    let holdings = report
+        .holdings
+        .iter()
+        .map(|holding| (report.accession_number.as_str(), holding))
+        .collect::<Vec<_>>();
+    insert_institutional_holdings(&mut tx, &holdings).await?;
+    tx.commit().await?;
+    Ok(())
+}
+
+/// Store the cover pages of 13F-HR filings, dropping the holdings of earlier copies of the same
+/// accession numbers; the holdings of `reports` are not stored
+pub async fn replace_institutional_filings(
+    tx: &mut Transaction<'_, Postgres>,
+    reports: &[HoldingsReport],
+) -> Result<()> {
+    let accession_numbers = reports
+        .iter()
+        .map(|r| r.accession_number.clone())
+        .collect::<Vec<_>>();
+    sqlx::query!(
+        "DELETE FROM institutional_filings WHERE accession_number = ANY($1)",
+        &accession_numbers
+    )
+    .execute(&mut **tx)
+    .await?;
+
+    sqlx::query!(
+        r#"
+        INSERT INTO institutional_filings (accession_number, filer_cik, filer_name, period_of_report, filing_date, amendment_type)
+        SELECT * FROM UNNEST($1::text[], $2::text[], $3::text[], $4::date[], $5::date[], $6::text[])
+        "#,
+        &accession_numbers,
+        &reports.iter().map(|r| r.filer_cik.clone()).collect::<Vec<_>>(),
+        &reports.iter().map(|r| r.filer_name.clone()).collect::<Vec<_>>(),
+        &reports.iter().map(|r| r.period_of_report).collect::<Vec<_>>() as _,
+        &reports.iter().map(|r| r.filing_date).collect::<Vec<_>>() as _,
+        &reports
+            .iter()
+            .map(|r| r.amendment_type.clone())
+            .collect::<Vec<_>>() as &[Option<String>],
+    )
+    .execute(&mut **tx)
+    .await?;
+    Ok(())
+}
+
+/// Store information table rows, each with the accession number of its filing
+pub async fn insert_institutional_holdings(
+    tx: &mut Transaction<'_, Postgres>,
+    holdings: &[(&str, &Holding)],
+) -> Result<()> {
+    sqlx::query!(
+        r#"
+        INSERT INTO institutional_holdings (
+            accession_number, cusip, issuer_name, title_of_class, value, shares, share_type,
+            put_call, investment_discretion, voting_sole, voting_shared, voting_none
+        )
+        SELECT * FROM UNNEST(
+            $1::text[], $2::text[], $3::text[], $4::text[], $5::bigint[], $6::bigint[], $7::text[],
+            $8::text[], $9::text[], $10::bigint[], $11::bigint[], $12::bigint[]
+        )
+        "#,
+        &holdings
+            .iter()
+            .map(|(accession_number, _)| accession_number.to_string())
+            .collect::<Vec<_>>(),
+        &holdings
+            .iter()
+            .map(|(_, h)| h.cusip.clone())
+            .collect::<Vec<_>>(),
+        &holdings
+            .iter()
+            .map(|(_, h)| h.name_of_issuer.clone())
+            .collect::<Vec<_>>(),
+        &holdings
+            .iter()
+            .map(|(_, h)| h.title_of_class.clone())
+            .collect::<Vec<_>>(),
+        &holdings.iter().map(|(_, h)| h.value).collect::<Vec<_>>(),
+        &holdings.iter().map(|(_, h)| h.shares).collect::<Vec<_>>(),
+        &holdings
+            .iter()
+            .map(|(_, h)| h.share_type.clone())
+            .collect::<Vec<_>>(),
+        &holdings
+            .iter()
+            .map(|(_, h)| h.put_call.clone())
+            .collect::<Vec<_>>() as &[Option<String>],
+        &holdings
+            .iter()
+            .map(|(_, h)| h.investment_discretion.clone())
+            .collect::<Vec<_>>(),
+        &holdings
+            .iter()
+            .map(|(_, h)| h.voting_sole)
+            .collect::<Vec<_>>(),
+        &holdings
+            .iter()
+            .map(|(_, h)| h.voting_shared)
+            .collect::<Vec<_>>(),
+        &holdings
+            .iter()
+            .map(|(_, h)| h.voting_none)
+            .collect::<Vec<_>>(),
+    )
+    .execute(&mut **tx)
+    .await?;
+    Ok(())
+}
+
+/// CUSIPs and issuer names reported in 13F filings for issuers whose name starts with `word`,
+/// the candidates for an exact match of the normalized name
+pub async fn find_holdings_issuers(
+    pool: &Pool<Postgres>,
+    word: &str,
+) -> Result<Vec<(String, String)>> {
+    let rows = sqlx::query!(
+        "SELECT DISTINCT cusip, issuer_name FROM institutional_holdings WHERE UPPER(issuer_name) LIKE $1",
+        format!("{}%", word.to_uppercase())
+    )
+    .fetch_all(pool)
+    .await?;
+    Ok(rows
+        .into_iter()
+        .map(|row| (row.cusip, row.issuer_name))
+        .collect())
+}
+
+/// CUSIPs mapped to a ticker with [`map_issuer_cusips`]
+pub async fn mapped_issuer_cusips(pool: &Pool<Postgres>, ticker: &str) -> Result<Vec<String>> {
+    let rows = sqlx::query!(
+        "SELECT cusip FROM issuer_cusips WHERE ticker = $1 ORDER BY cusip",
+        ticker
+    )
+    .fetch_all(pool)
+    .await?;
+    Ok(rows.into_iter().map(|row| row.cusip).collect())
+}
+
+/// Record the CUSIPs of a ticker's securities, replacing the ones mapped before
+pub async fn map_issuer_cusips(
+    pool: &Pool<Postgres>,
+    ticker: &str,
+    cusips: &[String],
+) -> Result<()> {
+    let mut tx = pool.begin().await?;
+    sqlx::query!("DELETE FROM issuer_cusips WHERE ticker = $1", ticker)
+        .execute(&mut *tx)
+        .await?;
+    sqlx::query!(
+        "INSERT INTO issuer_cusips (ticker, cusip) SELECT $1, * FROM UNNEST($2::text[])",
+        ticker,
+        cusips
+    )
+    .execute(&mut *tx)
+    .await?;
+    tx.commit().await?;
+    Ok(())
+}
+
+/// Most recent quarter with stored 13F positions in any of the CUSIPs
+pub async fn latest_holdings_period(
+    pool: &Pool<Postgres>,
+    cusips: &[String],
+) -> Result<Option<NaiveDate>> {
+    let row = sqlx::query!(
+        r#"SELECT MAX(period_of_report) AS "period: NaiveDate" FROM institutional_positions WHERE cusip = ANY($1)"#,
+        cusips
+    )
+    .fetch_one(pool)
+    .await?;
+    Ok(row.period)
+}
+
+/// Largest holders of an issuer's shares for a quarter
+pub async fn top_holders(
+    pool: &Pool<Postgres>,
+    cusips: &[String],
+    period: NaiveDate,
+    limit: i64,
+) -> Result<Vec<HolderPosition>> {
+    sqlx::query_as!(
+        HolderPosition,
+        r#"
+        SELECT
+            filer_cik AS "filer_cik!",
+            filer_name AS "filer_name!",
+            cusip AS "cusip!",
+            issuer_name AS "issuer_name!",
+            shares AS "shares!",
+            value AS "value!"
+        FROM institutional_positions
+        WHERE cusip = ANY($1) AND period_of_report = $2
+        ORDER BY value DESC
+        LIMIT $3
+        "#,
+        cusips,
+        period as _,
+        limit
+    )
+    .fetch_all(pool)
+    .await
+    .map_err(Into::into)
+}
+
+/// Changes in every filer's position in an issuer between two quarters. Only filers with
+/// filings for both quarters are compared, so late filers don't show up as exits.
+pub async fn issuer_position_changes(
+    pool: &Pool<Postgres>,
+    cusips: &[String],
+    period: NaiveDate,
+    previous_period: NaiveDate,
+) -> Result<Vec<PositionChange>> {
+    sqlx::query_as!(
+        PositionChange,
+        r#"
+        WITH filers AS (
+            SELECT filer_cik FROM institutional_filings WHERE period_of_report = $2
+            INTERSECT
+            SELECT filer_cik FROM institutional_filings WHERE period_of_report = $3
+        ),
+        current_positions AS (
+            SELECT * FROM institutional_positions
+            WHERE cusip = ANY($1) AND period_of_report = $2 AND filer_cik IN (SELECT filer_cik FROM filers)
+        ),
+        previous_positions AS (
+            SELECT * FROM institutional_positions
+            WHERE cusip = ANY($1) AND period_of_report = $3 AND filer_cik IN (SELECT filer_cik FROM filers)
+        )
+        SELECT
+            COALESCE(c.filer_cik, p.filer_cik) AS "filer_cik!",
+            COALESCE(c.filer_name, p.filer_name) AS "filer_name!",
+            COALESCE(c.cusip, p.cusip) AS "cusip!",
+            COALESCE(c.issuer_name, p.issuer_name) AS "issuer_name!",
+            p.shares AS "previous_shares?",
+            c.shares AS "current_shares?",
+            p.value AS "previous_value?",
+            c.value AS "current_value?"
+        FROM current_positions c
+        FULL OUTER JOIN previous_positions p ON c.filer_cik = p.filer_cik AND c.cusip = p.cusip
+        "#,
+        cusips,
+        period as _,
+        previous_period as _
+    )
+    .fetch_all(pool)
+    .await
+    .map_err(Into::into)
+}
+
+/// Quarter over quarter changes of a filer's portfolio, against the filer's previous report
+pub async fn filer_position_changes(
+    pool: &Pool<Postgres>,
+    filer_cik: &str,
+    period: NaiveDate,
+) -> Result<Vec<PositionChange>> {
+    sqlx::query_as!(
+        PositionChange,
+        r#"
+        WITH previous_period AS (
+            SELECT MAX(period_of_report) AS period_of_report
+            FROM institutional_filings
+            WHERE filer_cik = $1 AND period_of_report < $2
+        ),
+        current_positions AS (
+            SELECT * FROM institutional_positions WHERE filer_cik = $1 AND period_of_report = $2
+        ),
+        previous_positions AS (
+            SELECT * FROM institutional_positions
+            WHERE filer_cik = $1 AND period_of_report = (SELECT period_of_report FROM previous_period)
+        )
+        SELECT
+            COALESCE(c.filer_cik, p.filer_cik) AS "filer_cik!",
+            COALESCE(c.filer_name, p.filer_name) AS "filer_name!",
+            COALESCE(c.cusip, p.cusip) AS "cusip!",
+            COALESCE(c.issuer_name, p.issuer_name) AS "issuer_name!",
+            p.shares AS "previous_shares?",
+            c.shares AS "current_shares?",
+            p.value AS "previous_value?",
+            c.value AS "current_value?"
+        FROM current_positions c
+        FULL OUTER JOIN previous_positions p ON c.cusip = p.cusip
+        "#,
+        filer_cik,
+        period as _
+    )
+    .fetch_all(pool)
+    .await
+    .map_err(Into::into)
+}
+
+// Company Profile Database Operations
+
+/// Store the profile of a company, replacing the stored one
+pub async fn upsert_company_profile(pool: &Pool<Postgres>, profile: &CompanyProfile) -> Result<()> {
+    sqlx::query!(
+        "INSERT INTO company_profiles (cik, name, sic, fiscal_year_end, state_of_incorporation, profile)
+         VALUES ($1, $2, $3, $4, $5, $6)
+         ON CONFLICT (cik) DO UPDATE SET
+            name = EXCLUDED.name,
+            sic = EXCLUDED.sic,
+            fiscal_year_end = EXCLUDED.fiscal_year_end,
+            state_of_incorporation = EXCLUDED.state_of_incorporation,
+            profile = EXCLUDED.profile,
+            updated_at = NOW()",
+        profile.cik,
+        profile.name,
+        profile.sic,
+        profile.fiscal_year_end,
+        profile.state_of_incorporation,
+        serde_json::to_value(profile)?
+    )
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+pub async fn get_company_profile(
+    pool: &Pool<Postgres>,
+    cik: &str,
+) -> Result<Option<CompanyProfile>> {
+    let row = sqlx::query!("SELECT profile FROM company_profiles WHERE cik = $1", cik)
+        .fetch_optional(pool)
+        .await?;
+    row.map(|row| serde_json::from_value(row.profile).map_err(Into::into))
+        .transpose()
+}
+
+// Company Facts Database Operations
+
+/// Store every XBRL fact of a company, replacing the facts stored for it before
+pub async fn replace_company_facts(pool: &Pool<Postgres>, facts: &CompanyFacts) -> Result<usize> {
+    let cik = format!("{:010}", facts.cik);
+    let mut tx = pool.begin().await?;
+
+    sqlx::query!("DELETE FROM company_fact_concepts WHERE cik = $1", cik)
+        .execute(&mut *tx)
+        .await?;
+    sqlx::query!("DELETE FROM company_facts WHERE cik = $1", cik)
+        .execute(&mut *tx)
+        .await?;
+
+    let concepts = facts
+        .facts
+        .iter()
+        .flat_map(|(taxonomy, concepts)| {
+            concepts
+                .iter()
+                .map(move |(concept, facts)| (taxonomy, concept, facts))
+        })
+        .collect::<Vec<_>>();
+    sqlx::query!(
+        r#"
+        INSERT INTO company_fact_concepts (cik, taxonomy, concept, label, description)
+        SELECT $1, * FROM UNNEST($2::text[], $3::text[], $4::text[], $5::text[])
+        "#,
+        cik,
+        &concepts
+            .iter()
+            .map(|(taxonomy, _, _)| taxonomy.to_string())
+            .collect::<Vec<_>>(),
+        &concepts
+            .iter()
+            .map(|(_, concept, _)| concept.to_string())
+            .collect::<Vec<_>>(),
+        &concepts
+            .iter()
+            .map(|(_, _, facts)| facts.label.clone())
+            .collect::<Vec<_>>() as &[Option<String>],
+        &concepts
+            .iter()
+            .map(|(_, _, facts)| facts.description.clone())
+            .collect::<Vec<_>>() as &[Option<String>],
+    )
+    .execute(&mut *tx)
+    .await?;
+
+    let values = concepts
+        .iter()
+        .flat_map(|(taxonomy, concept, facts)| {
+            facts.units.iter().flat_map(move |(unit, values)| {
+                values
+                    .iter()
+                    .map(move |value| (*taxonomy, *concept, unit, value))
+            })
+        })
+        .collect::<Vec<_>>();
+    sqlx::query!(
+        r#"
+        INSERT INTO company_facts (
+            cik, taxonomy, concept, unit, period_start, period_end, value, accession_number,
+            fiscal_year, fiscal_period, form, filed, frame
+        )
+        SELECT $1, * FROM UNNEST(
+            $2::text[], $3::text[], $4::text[], $5::date[], $6::date[], $7::float8[], $8::text[],
+            $9::int4[], $10::text[], $11::text[], $12::date[], $13::text[]
+        )
+        "#,
+        cik,
+        &values
+            .iter()
+            .map(|(taxonomy, _, _, _)| taxonomy.to_string())
+            .collect::<Vec<_>>(),
+        &values
+            .iter()
+            .map(|(_, concept, _, _)| concept.to_string())
+            .collect::<Vec<_>>(),
+        &values
+            .iter()
+            .map(|(_, _, unit, _)| unit.to_string())
+            .collect::<Vec<_>>(),
+        &values
+            .iter()
+            .map(|(_, _, _, v)| v.start)
+            .collect::<Vec<_>>() as _,
+        &values.iter().map(|(_, _, _, v)| v.end).collect::<Vec<_>>() as _,
+        &values.iter().map(|(_, _, _, v)| v.val).collect::<Vec<_>>(),
+        &values
+            .iter()
+            .map(|(_, _, _, v)| v.accn.clone())
+            .collect::<Vec<_>>(),
+        &values.iter().map(|(_, _, _, v)| v.fy).collect::<Vec<_>>() as &[Option<i32>],
+        &values
+            .iter()
+            .map(|(_, _, _, v)| v.fp.clone())
+            .collect::<Vec<_>>() as &[Option<String>],
+        &values
+            .iter()
+            .map(|(_, _, _, v)| v.form.clone())
+            .collect::<Vec<_>>(),
+        &values
+            .iter()
+            .map(|(_, _, _, v)| v.filed)
+            .collect::<Vec<_>>() as _,
+        &values
+            .iter()
+            .map(|(_, _, _, v)| v.frame.clone())
+            .collect::<Vec<_>>() as &[Option<String>],
+    )
+    .execute(&mut *tx)
+    .await?;
+
+    tx.commit().await?;
+    Ok(values.len())
+}
+
+// Financial Statement Data Sets Database Operations
+
+/// Store the filings of a quarterly data set, deleting an earlier import of the quarter
+/// together with its values and presentation lines
+pub async fn replace_fsds_submissions(
+    tx: &mut Transaction<'_, Postgres>,
+    dataset: &str,
+    submissions: &[DatasetSubmission],
+) -> Result<()> {
+    let adshs = submissions
+        .iter()
+        .map(|s| s.adsh.clone())
+        .collect::<Vec<_>>();
+
+    sqlx::query!(
+        "DELETE FROM fsds_submissions WHERE dataset = $1 OR adsh = ANY($2)",
+        dataset,
+        &adshs
+    )
+    .execute(&mut **tx)
+    .await?;
+
+    sqlx::query!(
+        r#"
+        INSERT INTO fsds_submissions (
+            dataset, adsh, cik, name, sic, form, period, fy, fp, fye, filed, afs, prevrpt, instance
+        )
+        SELECT $1, * FROM UNNEST(
+            $2::text[], $3::text[], $4::text[], $5::text[], $6::text[], $7::date[], $8::int4[],
+            $9::text[], $10::text[], $11::date[], $12::text[], $13::bool[], $14::text[]
+        )
+        "#,
+        dataset,
+        &adshs,
+        &submissions
+            .iter()
+            .map(|s| s.cik.clone())
+            .collect::<Vec<_>>(),
+        &submissions
+            .iter()
+            .map(|s| s.name.clone())
+            .collect::<Vec<_>>(),
+        &submissions
+            .iter()
+            .map(|s| s.sic.clone())
+            .collect::<Vec<_>>() as &[Option<String>],
+        &submissions
+            .iter()
+            .map(|s| s.form.clone())
+            .collect::<Vec<_>>(),
+        &submissions.iter().map(|s| s.period).collect::<Vec<_>>() as _,
+        &submissions.iter().map(|s| s.fy).collect::<Vec<_>>() as &[Option<i32>],
+        &submissions.iter().map(|s| s.fp.clone()).collect::<Vec<_>>() as &[Option<String>],
+        &submissions
+            .iter()
+            .map(|s| s.fye.clone())
+            .collect::<Vec<_>>() as &[Option<String>],
+        &submissions.iter().map(|s| s.filed).collect::<Vec<_>>() as _,
+        &submissions
+            .iter()
+            .map(|s| s.afs.clone())
+            .collect::<Vec<_>>() as &[Option<String>],
+        &submissions.iter().map(|s| s.prevrpt).collect::<Vec<_>>(),
+        &submissions
+            .iter()
+            .map(|s| s.instance.clone())
+            .collect::<Vec<_>>() as &[Option<String>],
+    )
+    .execute(&mut **tx)
+    .await?;
+    Ok(())
+}
+
+/// Store tag definitions; tags of the standard taxonomies recur in every data set
+pub async fn upsert_fsds_tags(
+    tx: &mut Transaction<'_, Postgres>,
+    tags: &[DatasetTag],
+) -> Result<()> {
+    sqlx::query!(
+        r#"
+        INSERT INTO fsds_tags (tag, version, custom, abstract, datatype, iord, crdr, tlabel, doc)
+        SELECT * FROM UNNEST(
+            $1::text[], $2::text[], $3::bool[], $4::bool[], $5::text[], $6::text[], $7::text[],
+            $8::text[], $9::text[]
+        )
+        ON CONFLICT (tag, version) DO UPDATE SET
+            custom = EXCLUDED.custom,
+            abstract = EXCLUDED.abstract,
+            datatype = EXCLUDED.datatype,
+            iord = EXCLUDED.iord,
+            crdr = EXCLUDED.crdr,
+            tlabel = EXCLUDED.tlabel,
+            doc = EXCLUDED.doc
+        "#,
+        &tags.iter().map(|t| t.tag.clone()).collect::<Vec<_>>(),
+        &tags.iter().map(|t| t.version.clone()).collect::<Vec<_>>(),
+        &tags.iter().map(|t| t.custom).collect::<Vec<_>>(),
+        &tags.iter().map(|t| t.is_abstract).collect::<Vec<_>>(),
+        &tags.iter().map(|t| t.datatype.clone()).collect::<Vec<_>>() as &[Option<String>],
+        &tags.iter().map(|t| t.iord.clone()).collect::<Vec<_>>() as &[Option<String>],
+        &tags.iter().map(|t| t.crdr.clone()).collect::<Vec<_>>() as &[Option<String>],
+        &tags.iter().map(|t| t.tlabel.clone()).collect::<Vec<_>>() as &[Option<String>],
+        &tags.iter().map(|t| t.doc.clone()).collect::<Vec<_>>() as &[Option<String>],
+    )
+    .execute(&mut **tx)
+    .await?;
+    Ok(())
+}
+
+/// Store data set values; a value repeated in `num.txt` is stored once
+pub async fn insert_fsds_numbers(
+    tx: &mut Transaction<'_, Postgres>,
+    numbers: &[DatasetNumber],
+) -> Result<()> {
+    sqlx::query!(
+        r#"
+        INSERT INTO fsds_numbers (
+            adsh, cik, tag, version, ddate, qtrs, uom, segments, coreg, value, footnote
+        )
+        SELECT * FROM UNNEST(
+            $1::text[], $2::text[], $3::text[], $4::text[], $5::date[], $6::int4[], $7::text[],
+            $8::text[], $9::text[], $10::float8[], $11::text[]
+        )
+        ON CONFLICT DO NOTHING
+        "#,
+        &numbers.iter().map(|n| n.adsh.clone()).collect::<Vec<_>>(),
+        &numbers.iter().map(|n| n.cik.clone()).collect::<Vec<_>>(),
+        &numbers.iter().map(|n| n.tag.clone()).collect::<Vec<_>>(),
+        &numbers
+            .iter()
+            .map(|n| n.version.clone())
+            .collect::<Vec<_>>(),
+        &numbers.iter().map(|n| n.ddate).collect::<Vec<_>>() as _,
+        &numbers.iter().map(|n| n.qtrs).collect::<Vec<_>>(),
+        &numbers.iter().map(|n| n.uom.clone()).collect::<Vec<_>>(),
+        &numbers
+            .iter()
+            .map(|n| n.segments.clone())
+            .collect::<Vec<_>>() as &[Option<String>],
+        &numbers.iter().map(|n| n.coreg.clone()).collect::<Vec<_>>() as &[Option<String>],
+        &numbers.iter().map(|n| n.value).collect::<Vec<_>>() as &[Option<f64>],
+        &numbers
+            .iter()
+            .map(|n| n.footnote.clone())
+            .collect::<Vec<_>>() as &[Option<String>],
+    )
+    .execute(&mut **tx)
+    .await?;
+    Ok(())
+}
+
+pub async fn insert_fsds_presentations(
+    tx: &mut Transaction<'_, Postgres>,
+    lines: &[DatasetPresentation],
+) -> Result<()> {
+    sqlx::query!(
+        r#"
+        INSERT INTO fsds_presentations (
+            adsh, report, line, stmt, inpth, rfile, tag, version, plabel, negating
+        )
+        SELECT * FROM UNNEST(
+            $1::text[], $2::int4[], $3::int4[], $4::text[], $5::bool[], $6::text[], $7::text[],
+            $8::text[], $9::text[], $10::bool[]
+        )
+        "#,
+        &lines.iter().map(|l| l.adsh.clone()).collect::<Vec<_>>(),
+        &lines.iter().map(|l| l.report).collect::<Vec<_>>(),
+        &lines.iter().map(|l| l.line).collect::<Vec<_>>(),
+        &lines.iter().map(|l| l.stmt.clone()).collect::<Vec<_>>(),
+        &lines.iter().map(|l| l.inpth).collect::<Vec<_>>(),
+        &lines.iter().map(|l| l.rfile.clone()).collect::<Vec<_>>() as &[Option<String>],
+        &lines.iter().map(|l| l.tag.clone()).collect::<Vec<_>>(),
+        &lines.iter().map(|l| l.version.clone()).collect::<Vec<_>>(),
+        &lines.iter().map(|l| l.plabel.clone()).collect::<Vec<_>>() as &[Option<String>],
+        &lines.iter().map(|l| l.negating).collect::<Vec<_>>(),
+    )
+    .execute(&mut **tx)
+    .await?;
+    Ok(())
+}
+
 pub async fn insert_memory(pool: &Pool<Postgres>, file_path: &str, messages: &Value) -> Result<()> {
     query("INSERT INTO memory (file_path, messages) VALUES ($1, $2)")
         .bind(file_path)
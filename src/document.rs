@@ -13,6 +13,7 @@ use std::sync::Arc;
 
 pub const COLLECTION_NAME: &str = "advisor";
 
+use crate::edgar::items::Form8KItem;
 use crate::edgar::report::ReportType;
 use crate::ProgressTracker;
 use std::str::FromStr;
@@ -20,6 +21,7 @@ use std::str::FromStr;
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub enum DocType {
     EdgarFiling,
+    EdgarFilingText,
     EarningTranscript,
 }
 
@@ -27,6 +29,7 @@ impl fmt::Display for DocType {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         match self {
             DocType::EdgarFiling => write!(f, "EdgarFiling"),
+            DocType::EdgarFilingText => write!(f, "EdgarFilingText"),
             DocType::EarningTranscript => write!(f, "EarningTranscript"),
         }
     }
@@ -39,6 +42,7 @@ impl FromStr for DocType {
         log::info!("!: {}", s);
         match s {
             "edgar_filing" => Ok(DocType::EdgarFiling),
+            "edgar_filing_text" => Ok(DocType::EdgarFilingText),
             "earnings_transcript" => Ok(DocType::EarningTranscript),
             _ => Err(anyhow!("Unknown document type: {}", s)),
         }
@@ -59,10 +63,16 @@ pub enum Metadata {
     MetaEdgarFiling {
         doc_type: DocType,
         filepath: PathBuf,
-        symbol: String,
+        /// Missing for filers without a ticker, which are known by `cik` and `entity_name`
+        symbol: Option<String>,
+        entity_name: Option<String>,
         filing_type: ReportType,
         cik: String,
         accession_number: String,
+        items: Vec<Form8KItem>,
+        item: Option<String>,
+        section_title: Option<String>,
+        exhibit_type: Option<String>,
         chunk_index: usize,
         total_chunks: usize,
     },
@@ -91,10 +101,10 @@ impl Metadata {
         self
     }
 
-    pub fn symbol(&self) -> &String {
+    pub fn symbol(&self) -> Option<&str> {
         match self {
-            Metadata::MetaEarningsTranscript { symbol, .. } => symbol,
-            Metadata::MetaEdgarFiling { symbol, .. } => symbol,
+            Metadata::MetaEarningsTranscript { symbol, .. } => Some(symbol),
+            Metadata::MetaEdgarFiling { symbol, .. } => symbol.as_deref(),
         }
     }
 
@@ -137,6 +147,11 @@ impl From<Metadata> for HashMap<String, Value> {
                 cik,
                 accession_number,
                 symbol,
+                entity_name,
+                items,
+                item,
+                section_title,
+                exhibit_type,
                 chunk_index,
                 total_chunks,
                 doc_type,
@@ -155,7 +170,33 @@ impl From<Metadata> for HashMap<String, Value> {
                     "accession_number".to_string(),
                     Value::String(accession_number),
                 );
-                map.insert("symbol".to_string(), Value::String(symbol));
+                if let Some(symbol) = symbol {
+                    map.insert("symbol".to_string(), Value::String(symbol));
+                }
+                if let Some(entity_name) = entity_name {
+                    map.insert("entity_name".to_string(), Value::String(entity_name));
+                }
+                if !items.is_empty() {
+                    map.insert(
+                        "items".to_string(),
+                        Value::String(
+                            items
+                                .iter()
+                                .map(|item| item.code())
+                                .collect::<Vec<_>>()
+                                .join(","),
+                        ),
+                    );
+                }
+                if let Some(item) = item {
+                    map.insert("item".to_string(), Value::String(item));
+                }
+                if let Some(section_title) = section_title {
+                    map.insert("section_title".to_string(), Value::String(section_title));
+                }
+                if let Some(exhibit_type) = exhibit_type {
+                    map.insert("exhibit_type".to_string(), Value::String(exhibit_type));
+                }
                 map.insert(
                     "chunk_index".to_string(),
                     Value::Number(serde_json::Number::from(chunk_index)),
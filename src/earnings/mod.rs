@@ -2,24 +2,19 @@ mod query;
 pub use query::Query;
 
 use anyhow::{anyhow, Result};
-use chrono::{Datelike, NaiveDate};
-use once_cell::sync::OnceCell;
-use reqwest::Client;
+use chrono::NaiveDate;
 use serde::{Deserialize, Serialize};
 use std::fs;
 use std::path::PathBuf;
 
-use crate::utils::rate_limit::RateLimiter;
-
-static RATE_LIMITER: OnceCell<RateLimiter> = OnceCell::new();
-
-fn rate_limiter() -> &'static RateLimiter {
-    RATE_LIMITER.get_or_init(|| RateLimiter::new(10))
-}
+use crate::edgar::client::EdgarClient;
+use crate::edgar::fiscal::{FiscalCalendar, FiscalQuarter};
+use crate::edgar::tickers::{normalize_ticker, Ticker};
 
 use crate::utils::dirs::EARNINGS_DIR;
-const USER_AGENT: &str = "software@example.com";
 const API_BASE_URL: &str = "https://discountingcashflows.com/api/transcript";
+/// The transcript API spells share classes with a dot (`BRK.B`) where SEC uses `BRK-B`
+const SHARE_CLASS_SEPARATOR: char = '.';
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct Transcript {
@@ -32,48 +27,30 @@ pub struct Transcript {
 
 type TranscriptResponse = Vec<Transcript>;
 
+/// Transcript of the earnings call for a fiscal quarter, which the API numbers the way the
+/// company does (Apple's Q1 FY2025 ended in December 2024). Requests go through the client's
+/// HTTP client and rate limiter, so they count against the configured requests per second.
 pub async fn fetch_transcript(
-    client: &Client,
+    client: &EdgarClient,
     ticker: &str,
-    date: NaiveDate,
+    fiscal_quarter: FiscalQuarter,
 ) -> Result<(Transcript, PathBuf)> {
     crate::utils::dirs::ensure_earnings_dirs()?;
+    let ticker = Ticker::new(ticker.to_string())?;
+    let symbol = ticker.with_separator(SHARE_CLASS_SEPARATOR);
+    let (quarter, year) = (fiscal_quarter.quarter as i32, fiscal_quarter.year);
 
-    // Calculate the most recently completed quarter
-    // Note: Earnings for Q4 are typically reported in Q1 of next year
-    let (quarter, year) = match date.month() {
-        1..=2 => (4, date.year() - 1), // Jan-Feb: Q4 of previous year
-        3..=5 => (1, date.year()),     // Mar-May: Q1 of current year
-        6..=8 => (2, date.year()),     // Jun-Aug: Q2 of current year
-        9..=11 => (3, date.year()),    // Sep-Nov: Q3 of current year
-        12 => (3, date.year()),        // December: still Q3, Q4 not reported yet
-        _ => unreachable!(),
-    };
-
-    let url = format!("{}/{}/{}/{}/", API_BASE_URL, ticker, quarter, year);
+    let url = format!("{}/{}/{}/{}/", API_BASE_URL, symbol, quarter, year);
 
     log::debug!("Earnings API Request URL: {}", url);
-    log::debug!("Earnings API Headers: User-Agent: {}", USER_AGENT);
+    log::debug!("Earnings API Headers: User-Agent: {}", client.user_agent());
 
     let filepath = PathBuf::from(EARNINGS_DIR)
-        .join(ticker)
+        .join(ticker.as_str())
         .join(format!("{}_{}_Q{}.json", ticker, year, quarter));
 
-    // Create ticker directory if it doesn't exist
-    if let Some(parent) = filepath.parent() {
-        fs::create_dir_all(parent)?;
-    }
-
-    // Fetch and save transcript
-    let _ = crate::utils::http::fetch_and_save(
-        client,
-        &url::Url::parse(&url)?,
-        &filepath,
-        USER_AGENT,
-        mime::APPLICATION_JSON,
-        rate_limiter(),
-    )
-    .await;
+    // Fetch and save transcript, creating the ticker directory if it doesn't exist
+    let _ = client.fetch(&url, &filepath, mime::APPLICATION_JSON).await;
 
     // Read and parse the saved transcript
     let content = fs::read_to_string(&filepath)?;
@@ -111,7 +88,9 @@ pub async fn fetch_transcript(
     })?;
 
     // Validate the response
-    if transcript.symbol != ticker || transcript.year != year || transcript.quarter != quarter {
+    // Compared in canonical form, the API may echo the symbol in any share class spelling
+    let same_symbol = normalize_ticker(&transcript.symbol).is_ok_and(|s| s == ticker.as_str());
+    if !same_symbol || transcript.year != year || transcript.quarter != quarter {
         return Err(anyhow!(
             "Mismatched transcript data: expected {}/{}/Q{}, got {}/{}/{}",
             ticker,
@@ -126,9 +105,12 @@ pub async fn fetch_transcript(
     Ok((transcript, filepath))
 }
 
+/// Transcripts of the fiscal quarters ending between the dates, or of the last quarter ended by
+/// `end_date` for a range shorter than a quarter
 pub async fn fetch_transcripts(
-    client: &Client,
+    client: &EdgarClient,
     ticker: &str,
+    calendar: &FiscalCalendar,
     start_date: NaiveDate,
     end_date: NaiveDate,
 ) -> Result<Vec<(Transcript, PathBuf)>> {
@@ -139,15 +121,13 @@ pub async fn fetch_transcripts(
 
     // Spawn tasks to fetch each transcript
     let mut handles = Vec::new();
-    let mut current_date = start_date;
-    while current_date <= end_date {
+    for quarter in calendar.quarters_between(start_date, end_date) {
         let tx = tx.clone();
         let client = client.clone();
         let ticker = ticker.to_string();
-        let date = current_date;
 
         let handle = tokio::spawn(async move {
-            match fetch_transcript(&client, &ticker, date).await {
+            match fetch_transcript(&client, &ticker, quarter).await {
                 Ok(transcript) => {
                     let _ = tx.send(Some(transcript)).await;
                 }
@@ -158,10 +138,6 @@ pub async fn fetch_transcripts(
             }
         });
         handles.push(handle);
-
-        current_date = current_date
-            .checked_add_signed(chrono::Duration::days(90))
-            .unwrap_or(end_date);
     }
 
     // Drop the original sender
@@ -187,26 +163,44 @@ pub async fn fetch_transcripts(
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::core::config::AdvisorConfig;
+    use crate::edgar::client::{DEFAULT_CACHE_TTL, DEFAULT_REQUESTS_PER_SECOND};
     use tokio;
 
+    const USER_AGENT: &str = "software@example.com";
+
+    fn client() -> EdgarClient {
+        let config = AdvisorConfig {
+            openai_key: String::new(),
+            database_url: String::new(),
+            user_agent: USER_AGENT.to_string(),
+            data_dir: PathBuf::from("data"),
+            edgar_requests_per_second: DEFAULT_REQUESTS_PER_SECOND,
+            edgar_cache_ttl: DEFAULT_CACHE_TTL,
+        };
+        EdgarClient::new(&config).unwrap()
+    }
+
     #[tokio::test]
     async fn test_fetch_transcript() {
-        let client = Client::new();
+        let client = client();
         let ticker = "AAPL";
-        let date = NaiveDate::from_ymd_opt(2024, 1, 1).unwrap();
+        let quarter = FiscalQuarter::new(2023, 4);
 
-        let result = fetch_transcript(&client, ticker, date).await;
+        let result = fetch_transcript(&client, ticker, quarter).await;
         assert!(result.is_ok());
     }
 
     #[tokio::test]
     async fn test_fetch_transcripts() {
-        let client = Client::new();
+        let client = client();
         let ticker = "AAPL";
         let start_date = NaiveDate::from_ymd_opt(2024, 1, 1).unwrap();
         let end_date = NaiveDate::from_ymd_opt(2024, 3, 31).unwrap();
 
-        let result = fetch_transcripts(&client, ticker, start_date, end_date).await;
+        let calendar = FiscalCalendar::new(9, 28);
+
+        let result = fetch_transcripts(&client, ticker, &calendar, start_date, end_date).await;
         assert!(result.is_ok());
     }
 }
@@ -0,0 +1,291 @@
+// Offline ingestion of the nightly EDGAR bulk archives: `submissions.zip` (the submissions JSON
+// of every filer) and `companyfacts.zip` (the companyfacts JSON of every filer). Submissions are
+// unpacked into the filing cache that per-company lookups read, company facts are loaded into
+// Postgres, so a whole universe of companies can be ingested without per-company requests.
+// Quarterly Financial Statement Data Sets are imported by [`super::datasets`], Form 13F data
+// sets by [`super::form13f`].
+use anyhow::{anyhow, Result};
+use chrono::{DateTime, Utc};
+use indicatif::{ProgressBar, ProgressStyle};
+use sqlx::{Pool, Postgres};
+use std::fmt;
+use std::fs::{self, File};
+use std::io::Read;
+use std::path::Path;
+use zip::ZipArchive;
+
+use super::client::EdgarClient;
+use super::datasets;
+use super::facts::CompanyFacts;
+use super::form13f;
+use crate::db;
+use crate::utils::cache::{self, CacheEntry};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BulkArchive {
+    Submissions,
+    CompanyFacts,
+    /// Financial Statement Data Sets of a quarter, `2024q1.zip`
+    FinancialStatements,
+    /// Form 13F data set of a quarter, `2024q3_form13f.zip`
+    InstitutionalHoldings,
+}
+
+impl BulkArchive {
+    /// Archive kind from its file name, as published by SEC
+    pub fn detect(path: &Path) -> Result<Self> {
+        let name = path
+            .file_name()
+            .and_then(|name| name.to_str())
+            .unwrap_or_default()
+            .to_lowercase();
+        if name.contains("companyfacts") {
+            Ok(BulkArchive::CompanyFacts)
+        } else if name.contains("submissions") {
+            Ok(BulkArchive::Submissions)
+        } else if form13f::is_form13f_dataset(path) {
+            Ok(BulkArchive::InstitutionalHoldings)
+        } else if datasets::dataset_name(path).is_some() {
+            Ok(BulkArchive::FinancialStatements)
+        } else {
+            Err(anyhow!(
+                "Unknown bulk archive {}, expected submissions.zip, companyfacts.zip or a quarterly data set like 2024q1.zip or 2024q3_form13f.zip",
+                path.display()
+            ))
+        }
+    }
+}
+
+impl fmt::Display for BulkArchive {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            BulkArchive::Submissions => write!(f, "submissions"),
+            BulkArchive::CompanyFacts => write!(f, "companyfacts"),
+            BulkArchive::FinancialStatements => write!(f, "financial statements"),
+            BulkArchive::InstitutionalHoldings => write!(f, "13F holdings"),
+        }
+    }
+}
+
+/// What an import did with the entries of an archive
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct BulkSummary {
+    /// Entries written to the cache or loaded into Postgres
+    pub imported: usize,
+    /// Entries already cached from a newer download
+    pub up_to_date: usize,
+    /// Entries that are not company JSON or failed to parse
+    pub skipped: usize,
+    /// Fact values (or data set values) loaded into Postgres
+    pub facts: usize,
+}
+
+impl fmt::Display for BulkSummary {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{} imported, {} up to date, {} skipped",
+            self.imported, self.up_to_date, self.skipped
+        )?;
+        if self.facts > 0 {
+            write!(f, ", {} facts", self.facts)?;
+        }
+        Ok(())
+    }
+}
+
+/// Cache file of a submissions archive entry: `CIK##########.json` is the first page of the
+/// filing list (`CIK##########_0.json`), `CIK##########-submissions-NNN.json` the NNNth
+/// additional page (`CIK##########_NNN.json`), matching `get_company_filings_internal`
+pub fn submissions_cache_name(entry: &str) -> Option<String> {
+    let stem = entry.strip_suffix(".json")?;
+    let (cik, page) = match stem.split_once("-submissions-") {
+        Some((cik, page)) => (cik, page.parse::<usize>().ok()?),
+        None => (stem, 0),
+    };
+    let digits = cik.strip_prefix("CIK")?;
+    if digits.len() != 10 || !digits.chars().all(|c| c.is_ascii_digit()) {
+        return None;
+    }
+    Some(format!("{}_{}.json", cik, page))
+}
+
+fn open_archive(path: &Path) -> Result<ZipArchive<File>> {
+    let file = File::open(path).map_err(|e| anyhow!("Failed to open {}: {}", path.display(), e))?;
+    ZipArchive::new(file).map_err(|e| anyhow!("Failed to read {}: {}", path.display(), e))
+}
+
+fn progress_bar(len: usize, archive: BulkArchive) -> ProgressBar {
+    let pb = ProgressBar::new(len as u64);
+    pb.set_style(
+        ProgressStyle::default_bar()
+            .template("{spinner:.green} [{elapsed_precise}] [{bar:40.cyan/blue}] {pos}/{len} {msg}")
+            .unwrap()
+            .progress_chars("#>-"),
+    );
+    pb.set_message(archive.to_string());
+    pb
+}
+
+/// Unpack a `submissions.zip` into the filing cache. Pages count as fetched when the archive
+/// was downloaded, so they are used until they outlive the cache TTL; pages cached from a
+/// later download are kept.
+pub fn import_submissions(client: &EdgarClient, path: &Path) -> Result<BulkSummary> {
+    let fetched_at: DateTime<Utc> = fs::metadata(path)?.modified()?.into();
+    let mut archive = open_archive(path)?;
+    let filings_dir = client.filings_dir();
+    fs::create_dir_all(&filings_dir)?;
+
+    let pb = progress_bar(archive.len(), BulkArchive::Submissions);
+    let mut summary = BulkSummary::default();
+    for i in 0..archive.len() {
+        pb.inc(1);
+        let mut entry = archive.by_index(i)?;
+        let Some(cache_name) = submissions_cache_name(entry.name()) else {
+            log::debug!("Skipping submissions archive entry {}", entry.name());
+            summary.skipped += 1;
+            continue;
+        };
+
+        let filepath = filings_dir.join(&cache_name);
+        if cache::load_entry(&filepath).is_some_and(|cached| cached.fetched_at >= fetched_at) {
+            summary.up_to_date += 1;
+            continue;
+        }
+
+        let mut content = Vec::with_capacity(entry.size() as usize);
+        entry.read_to_end(&mut content)?;
+        fs::write(&filepath, content)?;
+        cache::save_entry(
+            &filepath,
+            &CacheEntry {
+                url: format!("{}/submissions/{}", client.data_url(), entry.name()),
+                fetched_at,
+                etag: None,
+                last_modified: None,
+            },
+        )?;
+        summary.imported += 1;
+    }
+    pb.finish_and_clear();
+
+    log::info!("Imported {}: {}", path.display(), summary);
+    Ok(summary)
+}
+
+/// Load every company of a `companyfacts.zip` into Postgres, replacing the stored facts of
+/// each company in the archive
+pub async fn import_company_facts(pool: &Pool<Postgres>, path: &Path) -> Result<BulkSummary> {
+    let mut archive = open_archive(path)?;
+
+    let pb = progress_bar(archive.len(), BulkArchive::CompanyFacts);
+    let mut summary = BulkSummary::default();
+    for i in 0..archive.len() {
+        pb.inc(1);
+        // Entries are decompressed one at a time; the largest filers are tens of megabytes
+        let (name, content) = {
+            let mut entry = archive.by_index(i)?;
+            let mut content = String::with_capacity(entry.size() as usize);
+            entry.read_to_string(&mut content)?;
+            (entry.name().to_string(), content)
+        };
+
+        let facts: CompanyFacts = match serde_json::from_str(&content) {
+            Ok(facts) => facts,
+            Err(e) => {
+                log::warn!("Skipping companyfacts archive entry {}: {}", name, e);
+                summary.skipped += 1;
+                continue;
+            }
+        };
+        summary.facts += db::replace_company_facts(pool, &facts).await?;
+        summary.imported += 1;
+    }
+    pb.finish_and_clear();
+
+    log::info!("Imported {}: {}", path.display(), summary);
+    Ok(summary)
+}
+
+/// Import a bulk archive of either kind, see [`BulkArchive::detect`]
+pub async fn import_archive(
+    client: &EdgarClient,
+    pool: &Pool<Postgres>,
+    path: &Path,
+) -> Result<(BulkArchive, BulkSummary)> {
+    let kind = BulkArchive::detect(path)?;
+    let summary = match kind {
+        BulkArchive::Submissions => import_submissions(client, path)?,
+        BulkArchive::CompanyFacts => import_company_facts(pool, path).await?,
+        BulkArchive::FinancialStatements => {
+            datasets::import_financial_statements(pool, path).await?
+        }
+        BulkArchive::InstitutionalHoldings => form13f::import_holdings(pool, path).await?,
+    };
+    Ok((kind, summary))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::config::AdvisorConfig;
+    use crate::edgar::client::{DEFAULT_CACHE_TTL, DEFAULT_REQUESTS_PER_SECOND};
+    use std::io::Write;
+    use std::path::PathBuf;
+    use zip::write::SimpleFileOptions;
+
+    #[test]
+    fn test_import_submissions() {
+        assert_eq!(
+            submissions_cache_name("CIK0000320193-submissions-002.json").as_deref(),
+            Some("CIK0000320193_2.json")
+        );
+        assert_eq!(submissions_cache_name("CIK320193.json"), None);
+
+        let dir = tempfile::tempdir().unwrap();
+        let archive_path = dir.path().join("submissions.zip");
+        let mut zip = zip::ZipWriter::new(File::create(&archive_path).unwrap());
+        for (name, content) in [
+            ("CIK0000320193.json", r#"{"cik": "320193"}"#),
+            (
+                "CIK0000320193-submissions-001.json",
+                r#"{"accessionNumber": []}"#,
+            ),
+            ("README.txt", "not a filer"),
+        ] {
+            zip.start_file(name, SimpleFileOptions::default()).unwrap();
+            zip.write_all(content.as_bytes()).unwrap();
+        }
+        zip.finish().unwrap();
+
+        let config = AdvisorConfig {
+            openai_key: String::new(),
+            database_url: String::new(),
+            user_agent: "Advisor Tests tests@example.com".to_string(),
+            data_dir: PathBuf::from("data"),
+            edgar_requests_per_second: DEFAULT_REQUESTS_PER_SECOND,
+            edgar_cache_ttl: DEFAULT_CACHE_TTL,
+        };
+        let client = EdgarClient::new(&config)
+            .unwrap()
+            .with_cache_dir(dir.path().join("edgar"));
+        assert_eq!(
+            BulkArchive::detect(&archive_path).unwrap(),
+            BulkArchive::Submissions
+        );
+
+        let summary = import_submissions(&client, &archive_path).unwrap();
+        assert_eq!((summary.imported, summary.skipped), (2, 1));
+        let first_page = client.filings_dir().join("CIK0000320193_0.json");
+        assert_eq!(
+            fs::read_to_string(&first_page).unwrap(),
+            r#"{"cik": "320193"}"#
+        );
+        assert!(client.filings_dir().join("CIK0000320193_1.json").exists());
+        assert!(cache::is_fresh(&first_page, DEFAULT_CACHE_TTL));
+
+        // Nothing newer in the archive the second time around
+        let summary = import_submissions(&client, &archive_path).unwrap();
+        assert_eq!((summary.imported, summary.up_to_date), (0, 2));
+    }
+}
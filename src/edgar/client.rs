@@ -0,0 +1,203 @@
+use anyhow::Result;
+use mime::Mime;
+use reqwest::Client;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use url::Url;
+
+use crate::core::config::AdvisorConfig;
+use crate::utils::http::{fetch_and_save, fetch_if_stale};
+use crate::utils::rate_limit::RateLimiter;
+
+pub const DEFAULT_DATA_URL: &str = "https://data.sec.gov";
+pub const DEFAULT_SEC_URL: &str = "https://www.sec.gov";
+
+/// SEC fair access policy allows at most 10 requests per second
+pub const DEFAULT_REQUESTS_PER_SECOND: u32 = 10;
+
+/// How long downloaded submissions and ticker lists are used before revalidating them
+pub const DEFAULT_CACHE_TTL: Duration = Duration::from_secs(6 * 60 * 60);
+
+/// Everything needed to talk to EDGAR: the HTTP client, the user agent SEC asks every client
+/// to declare, the shared rate limiter, where downloads are cached and the base URLs, which
+/// can point at a local mock server in tests. Clones share the HTTP client and rate limiter.
+#[derive(Clone, Debug)]
+pub struct EdgarClient {
+    http: Client,
+    user_agent: String,
+    rate_limiter: RateLimiter,
+    cache_dir: PathBuf,
+    cache_ttl: Duration,
+    data_url: String,
+    sec_url: String,
+}
+
+impl EdgarClient {
+    pub fn new(config: &AdvisorConfig) -> Result<Self> {
+        let http = Client::builder()
+            .user_agent(&config.user_agent)
+            .timeout(Duration::from_secs(30))
+            .connect_timeout(Duration::from_secs(10))
+            .build()?;
+
+        Ok(EdgarClient {
+            http,
+            user_agent: config.user_agent.clone(),
+            rate_limiter: RateLimiter::new(config.edgar_requests_per_second),
+            cache_dir: config.data_dir.join("edgar"),
+            cache_ttl: config.edgar_cache_ttl,
+            data_url: DEFAULT_DATA_URL.to_string(),
+            sec_url: DEFAULT_SEC_URL.to_string(),
+        })
+    }
+
+    /// Base URL of the JSON APIs (submissions, XBRL facts), `https://data.sec.gov` by default
+    pub fn with_data_url(mut self, url: &str) -> Self {
+        self.data_url = url.trim_end_matches('/').to_string();
+        self
+    }
+
+    /// Base URL of the archives and static files, `https://www.sec.gov` by default
+    pub fn with_sec_url(mut self, url: &str) -> Self {
+        self.sec_url = url.trim_end_matches('/').to_string();
+        self
+    }
+
+    pub fn with_cache_dir(mut self, dir: impl Into<PathBuf>) -> Self {
+        self.cache_dir = dir.into();
+        self
+    }
+
+    pub fn http(&self) -> &Client {
+        &self.http
+    }
+
+    pub fn user_agent(&self) -> &str {
+        &self.user_agent
+    }
+
+    pub fn data_url(&self) -> &str {
+        &self.data_url
+    }
+
+    /// Base URL of filing documents: `{archives_url}/{cik}/{accession}/{document}`
+    pub fn archives_url(&self) -> String {
+        format!("{}/Archives/edgar/data", self.sec_url)
+    }
+
+    pub fn tickers_url(&self) -> String {
+        format!("{}/files/company_tickers.json", self.sec_url)
+    }
+
+    /// Tickers with company names, CIKs and exchanges, the source of the ticker index
+    pub fn tickers_exchange_url(&self) -> String {
+        format!("{}/files/company_tickers_exchange.json", self.sec_url)
+    }
+
+    pub fn cache_dir(&self) -> &Path {
+        &self.cache_dir
+    }
+
+    /// Submission pages and filing documents
+    pub fn filings_dir(&self) -> PathBuf {
+        self.cache_dir.join("filings")
+    }
+
+    /// Responses of the XBRL JSON APIs
+    pub fn facts_dir(&self) -> PathBuf {
+        self.cache_dir.join("facts")
+    }
+
+    /// Markdown, facts and other artifacts parsed from filing documents
+    pub fn parsed_dir(&self) -> PathBuf {
+        self.cache_dir.join("parsed")
+    }
+
+    pub fn tickers_path(&self) -> PathBuf {
+        self.cache_dir.join("tickers.json")
+    }
+
+    pub fn tickers_exchange_path(&self) -> PathBuf {
+        self.cache_dir.join("tickers_exchange.json")
+    }
+
+    /// Download `url` into `filepath`, creating its directory if needed
+    pub async fn fetch(&self, url: &str, filepath: &Path, content_type: Mime) -> Result<()> {
+        if let Some(parent) = filepath.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fetch_and_save(
+            &self.http,
+            &Url::parse(url)?,
+            filepath,
+            &self.user_agent,
+            content_type,
+            &self.rate_limiter,
+        )
+        .await
+    }
+
+    /// Download `url` into `filepath` unless the cached copy is younger than the cache TTL.
+    /// Returns whether new content was saved.
+    pub async fn fetch_if_stale(
+        &self,
+        url: &str,
+        filepath: &Path,
+        content_type: Mime,
+    ) -> Result<bool> {
+        if let Some(parent) = filepath.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fetch_if_stale(
+            &self.http,
+            &Url::parse(url)?,
+            filepath,
+            &self.user_agent,
+            content_type,
+            &self.rate_limiter,
+            self.cache_ttl,
+        )
+        .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::edgar::filing;
+    use axum::{routing::get, Router};
+
+    #[tokio::test]
+    async fn test_fetch_from_mock_server() {
+        let submissions = fs::read_to_string("src/edgar/tests/AAPL/filing.json").unwrap();
+        let app = Router::new().route(
+            "/submissions/CIK0000320193.json",
+            get(move || async move { submissions }),
+        );
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let base_url = format!("http://{}", listener.local_addr().unwrap());
+        tokio::spawn(async move { axum::serve(listener, app).await });
+
+        let cache_dir = tempfile::tempdir().unwrap();
+        let config = AdvisorConfig {
+            openai_key: String::new(),
+            database_url: String::new(),
+            user_agent: "Advisor Tests tests@example.com".to_string(),
+            data_dir: PathBuf::from("data"),
+            edgar_requests_per_second: DEFAULT_REQUESTS_PER_SECOND,
+            edgar_cache_ttl: DEFAULT_CACHE_TTL,
+        };
+        let client = EdgarClient::new(&config)
+            .unwrap()
+            .with_data_url(&base_url)
+            .with_sec_url(&base_url)
+            .with_cache_dir(cache_dir.path());
+
+        let filings = filing::get_company_filings(&client, "320193", Some(1), false)
+            .await
+            .unwrap();
+        assert_eq!(filings.name, "Apple Inc");
+        assert!(client.filings_dir().join("CIK0000320193_0.json").exists());
+    }
+}
@@ -0,0 +1,329 @@
+// Importer for the SEC Financial Statement Data Sets (https://www.sec.gov/dera/data/financial-statement-data-sets):
+// quarterly zip files with the face financials of every XBRL filing as tab-separated tables.
+// `sub.txt` lists the filings, `tag.txt` the tags, `num.txt` the numeric values and `pre.txt`
+// where each value is presented. Loaded into Postgres, they allow SQL screens across all filers.
+use anyhow::{anyhow, Result};
+use chrono::NaiveDate;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Deserializer, Serialize};
+use sqlx::{Pool, Postgres};
+use std::collections::HashMap;
+use std::fs::File;
+use std::path::Path;
+use tokio::sync::mpsc;
+use zip::ZipArchive;
+
+use super::bulk::BulkSummary;
+use crate::db;
+
+/// Rows sent to Postgres per statement
+const BATCH_SIZE: usize = 50_000;
+
+/// A filing, from `sub.txt`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DatasetSubmission {
+    /// Accession number, `0000320193-24-000006`
+    pub adsh: String,
+    #[serde(deserialize_with = "padded_cik")]
+    pub cik: String,
+    pub name: String,
+    pub sic: Option<String>,
+    pub form: String,
+    /// Balance sheet date, rounded to the nearest month end
+    #[serde(default, deserialize_with = "optional_yyyymmdd")]
+    pub period: Option<NaiveDate>,
+    pub fy: Option<i32>,
+    pub fp: Option<String>,
+    /// Fiscal year end, `MMDD`
+    pub fye: Option<String>,
+    #[serde(deserialize_with = "yyyymmdd")]
+    pub filed: NaiveDate,
+    /// Filer status, `1-LAF` for large accelerated filers, ...
+    pub afs: Option<String>,
+    /// Whether a later filing amended the values of this one
+    #[serde(deserialize_with = "flag")]
+    pub prevrpt: bool,
+    pub instance: Option<String>,
+}
+
+/// A standard or custom tag, from `tag.txt`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DatasetTag {
+    pub tag: String,
+    /// Taxonomy (`us-gaap/2023`) or, for custom tags, the accession number
+    pub version: String,
+    #[serde(deserialize_with = "flag")]
+    pub custom: bool,
+    #[serde(rename = "abstract", deserialize_with = "flag")]
+    pub is_abstract: bool,
+    pub datatype: Option<String>,
+    /// `I` for instants, `D` for durations
+    pub iord: Option<String>,
+    /// `C` for credit, `D` for debit balances
+    pub crdr: Option<String>,
+    pub tlabel: Option<String>,
+    pub doc: Option<String>,
+}
+
+/// A numeric value, from `num.txt`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DatasetNumber {
+    pub adsh: String,
+    /// Filer of the submission, not part of `num.txt`
+    #[serde(skip)]
+    pub cik: String,
+    pub tag: String,
+    pub version: String,
+    /// End of the period, rounded to the nearest month end
+    #[serde(deserialize_with = "yyyymmdd")]
+    pub ddate: NaiveDate,
+    /// Duration in quarters, 0 for instants
+    pub qtrs: i32,
+    pub uom: String,
+    /// Axis and member of dimensional values, only in data sets from 2024 on
+    #[serde(default)]
+    pub segments: Option<String>,
+    /// Co-registrant the value is reported for
+    #[serde(default)]
+    pub coreg: Option<String>,
+    pub value: Option<f64>,
+    #[serde(default)]
+    pub footnote: Option<String>,
+}
+
+/// A line of a financial statement, from `pre.txt`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DatasetPresentation {
+    pub adsh: String,
+    pub report: i32,
+    pub line: i32,
+    /// `BS`, `IS`, `CF`, `EQ`, `CI`, ...
+    pub stmt: String,
+    /// Whether the statement is parenthetical
+    #[serde(deserialize_with = "flag")]
+    pub inpth: bool,
+    pub rfile: Option<String>,
+    pub tag: String,
+    pub version: String,
+    pub plabel: Option<String>,
+    #[serde(deserialize_with = "flag")]
+    pub negating: bool,
+}
+
+pub(crate) fn padded_cik<'de, D: Deserializer<'de>>(deserializer: D) -> Result<String, D::Error> {
+    let cik = u64::deserialize(deserializer)?;
+    Ok(format!("{:010}", cik))
+}
+
+fn parse_yyyymmdd<E: serde::de::Error>(date: &str) -> Result<NaiveDate, E> {
+    NaiveDate::parse_from_str(date, "%Y%m%d").map_err(E::custom)
+}
+
+fn yyyymmdd<'de, D: Deserializer<'de>>(deserializer: D) -> Result<NaiveDate, D::Error> {
+    parse_yyyymmdd(&String::deserialize(deserializer)?)
+}
+
+fn optional_yyyymmdd<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> Result<Option<NaiveDate>, D::Error> {
+    Option::<String>::deserialize(deserializer)?
+        .map(|date| parse_yyyymmdd(&date))
+        .transpose()
+}
+
+/// Booleans are `0` and `1`
+fn flag<'de, D: Deserializer<'de>>(deserializer: D) -> Result<bool, D::Error> {
+    Ok(String::deserialize(deserializer)? == "1")
+}
+
+/// Quarter of a data set file, `2024q1` for `2024q1.zip`
+pub fn dataset_name(path: &Path) -> Option<String> {
+    let stem = path.file_stem()?.to_str()?.to_lowercase();
+    let (year, quarter) = stem.split_once('q')?;
+    let valid = year.len() == 4
+        && year.chars().all(|c| c.is_ascii_digit())
+        && matches!(quarter, "1" | "2" | "3" | "4");
+    valid.then_some(stem)
+}
+
+/// Parsed rows of a table, with the number of rows that could not be parsed
+pub(crate) struct Batch<T> {
+    pub(crate) rows: Vec<T>,
+    pub(crate) skipped: usize,
+}
+
+impl<T> Batch<T> {
+    fn new() -> Self {
+        Batch {
+            rows: Vec::with_capacity(BATCH_SIZE),
+            skipped: 0,
+        }
+    }
+}
+
+/// Parse one table of a data set on a blocking thread, in batches of [`BATCH_SIZE`] rows
+pub(crate) fn read_table<T: DeserializeOwned + Send + 'static>(
+    path: &Path,
+    table: &'static str,
+) -> mpsc::Receiver<Result<Batch<T>>> {
+    let (tx, rx) = mpsc::channel(2);
+    let path = path.to_path_buf();
+    tokio::task::spawn_blocking(move || {
+        if let Err(e) = send_batches(&path, table, &tx) {
+            let _ = tx.blocking_send(Err(e));
+        }
+    });
+    rx
+}
+
+fn send_batches<T: DeserializeOwned>(
+    path: &Path,
+    table: &str,
+    tx: &mpsc::Sender<Result<Batch<T>>>,
+) -> Result<()> {
+    let mut archive = ZipArchive::new(File::open(path)?)?;
+    let entry = archive
+        .by_name(table)
+        .map_err(|e| anyhow!("No {} in {}: {}", table, path.display(), e))?;
+    // Text fields contain stray quotes, the tables are not quoted
+    let mut reader = csv::ReaderBuilder::new()
+        .delimiter(b'\t')
+        .quoting(false)
+        .from_reader(entry);
+
+    let mut batch = Batch::new();
+    for row in reader.deserialize() {
+        match row {
+            Ok(row) => batch.rows.push(row),
+            Err(e) => {
+                log::debug!("Skipping {} row: {}", table, e);
+                batch.skipped += 1;
+            }
+        }
+        if batch.rows.len() == BATCH_SIZE
+            && tx
+                .blocking_send(Ok(std::mem::replace(&mut batch, Batch::new())))
+                .is_err()
+        {
+            // The import was abandoned
+            return Ok(());
+        }
+    }
+    let _ = tx.blocking_send(Ok(batch));
+    Ok(())
+}
+
+/// Load a quarterly data set into Postgres, replacing an earlier import of the same quarter.
+/// The import is a single transaction, so a failed import leaves the earlier one in place.
+/// Values and presentation lines of filings missing from `sub.txt` are skipped.
+pub async fn import_financial_statements(
+    pool: &Pool<Postgres>,
+    path: &Path,
+) -> Result<BulkSummary> {
+    let dataset = dataset_name(path).ok_or_else(|| {
+        anyhow!(
+            "Unknown data set {}, expected a quarterly file like 2024q1.zip",
+            path.display()
+        )
+    })?;
+    let mut summary = BulkSummary::default();
+    let mut tx = pool.begin().await?;
+
+    let mut submissions = Vec::new();
+    let mut batches = read_table::<DatasetSubmission>(path, "sub.txt");
+    while let Some(batch) = batches.recv().await {
+        let batch = batch?;
+        summary.skipped += batch.skipped;
+        submissions.extend(batch.rows);
+    }
+    db::replace_fsds_submissions(&mut tx, &dataset, &submissions).await?;
+    summary.imported = submissions.len();
+    let ciks: HashMap<String, String> = submissions
+        .into_iter()
+        .map(|submission| (submission.adsh, submission.cik))
+        .collect();
+
+    let mut batches = read_table::<DatasetTag>(path, "tag.txt");
+    while let Some(batch) = batches.recv().await {
+        let batch = batch?;
+        summary.skipped += batch.skipped;
+        db::upsert_fsds_tags(&mut tx, &batch.rows).await?;
+    }
+
+    let mut batches = read_table::<DatasetNumber>(path, "num.txt");
+    while let Some(batch) = batches.recv().await {
+        let batch = batch?;
+        let total = batch.rows.len();
+        let numbers = batch
+            .rows
+            .into_iter()
+            .filter_map(|mut number| {
+                number.cik = ciks.get(&number.adsh)?.clone();
+                Some(number)
+            })
+            .collect::<Vec<_>>();
+        summary.skipped += batch.skipped + total - numbers.len();
+        summary.facts += numbers.len();
+        db::insert_fsds_numbers(&mut tx, &numbers).await?;
+    }
+
+    let mut batches = read_table::<DatasetPresentation>(path, "pre.txt");
+    while let Some(batch) = batches.recv().await {
+        let batch = batch?;
+        let total = batch.rows.len();
+        let lines = batch
+            .rows
+            .into_iter()
+            .filter(|line| ciks.contains_key(&line.adsh))
+            .collect::<Vec<_>>();
+        summary.skipped += batch.skipped + total - lines.len();
+        db::insert_fsds_presentations(&mut tx, &lines).await?;
+    }
+    tx.commit().await?;
+
+    log::info!("Imported {} ({}): {}", path.display(), dataset, summary);
+    Ok(summary)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_dataset_tables() {
+        assert_eq!(
+            dataset_name(Path::new("/tmp/2024q1.zip")).as_deref(),
+            Some("2024q1")
+        );
+        assert_eq!(dataset_name(Path::new("companyfacts.zip")), None);
+
+        let sub = "adsh\tcik\tname\tsic\tcountryba\tform\tperiod\tfy\tfp\tfiled\tafs\tprevrpt\tfye\tinstance\n\
+            0000320193-24-000006\t320193\tAPPLE INC\t3571\tUS\t10-Q\t20231231\t2024\tQ1\t20240202\t1-LAF\t0\t0928\taapl-20231230_htm.xml\n";
+        let submissions = csv::ReaderBuilder::new()
+            .delimiter(b'\t')
+            .quoting(false)
+            .from_reader(sub.as_bytes())
+            .deserialize::<DatasetSubmission>()
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        assert_eq!(submissions[0].cik, "0000320193");
+        assert_eq!(submissions[0].period, NaiveDate::from_ymd_opt(2023, 12, 31));
+        assert!(!submissions[0].prevrpt);
+
+        // Older data sets have no segments column; a value can be missing
+        let num = "adsh\ttag\tversion\tcoreg\tddate\tqtrs\tuom\tvalue\tfootnote\n\
+            0000320193-24-000006\tRevenues\tus-gaap/2023\t\t20231231\t1\tUSD\t119575000000.0000\t\n\
+            0000320193-24-000006\tAssets\tus-gaap/2023\t\t20231231\t0\tUSD\t\tNot \"reported\"\n";
+        let numbers = csv::ReaderBuilder::new()
+            .delimiter(b'\t')
+            .quoting(false)
+            .from_reader(num.as_bytes())
+            .deserialize::<DatasetNumber>()
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        assert_eq!(numbers[0].value, Some(119575000000.0));
+        assert_eq!((numbers[0].qtrs, numbers[0].segments.as_deref()), (1, None));
+        assert_eq!(numbers[1].value, None);
+        assert_eq!(numbers[1].footnote.as_deref(), Some("Not \"reported\""));
+    }
+}
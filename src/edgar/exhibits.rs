@@ -0,0 +1,241 @@
+use anyhow::Result;
+use scraper::{Html, Selector};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+
+use super::filing::FilingIndex;
+use super::report::ReportType;
+
+/// Name of the typed document list saved next to the downloaded documents of a filing
+pub const DOCUMENTS_FILE: &str = "documents.json";
+
+/// A document of a filing, as listed by the `index.json` of its accession directory, with the
+/// form type (`10-K`, `EX-99.1`, `GRAPHIC`, ...) and description from the filing index page
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FilingDocument {
+    pub name: String,
+    pub sequence: Option<u32>,
+    pub document_type: Option<String>,
+    pub description: Option<String>,
+    pub size: Option<u64>,
+}
+
+impl FilingDocument {
+    pub fn is_exhibit(&self) -> bool {
+        self.document_type
+            .as_deref()
+            .is_some_and(|t| t.starts_with("EX-"))
+    }
+
+    fn is_text(&self) -> bool {
+        let name = self.name.to_ascii_lowercase();
+        name.ends_with(".htm") || name.ends_with(".html") || name.ends_with(".txt")
+    }
+}
+
+/// Parse the document tables of a filing index page (`{accession}-index.htm`).
+/// Rows are `Seq | Description | Document | Type | Size`; the data files table
+/// (XBRL instance, schemas) uses the same layout.
+pub fn parse_index_page(html: &str) -> Vec<FilingDocument> {
+    let document = Html::parse_document(html);
+    let rows = Selector::parse("table.tableFile tr").unwrap();
+    let cells = Selector::parse("td").unwrap();
+    let link = Selector::parse("a").unwrap();
+
+    document
+        .select(&rows)
+        .filter_map(|row| {
+            let columns = row.select(&cells).collect::<Vec<_>>();
+            if columns.len() < 5 {
+                return None;
+            }
+            let text = |i: usize| {
+                let value = columns[i].text().collect::<String>().trim().to_string();
+                (!value.is_empty()).then_some(value)
+            };
+            // The document cell links to the file and may carry an `iXBRL` marker after it
+            let name = columns[2]
+                .select(&link)
+                .next()
+                .map(|a| a.text().collect::<String>())
+                .or_else(|| text(2))?
+                .trim()
+                .to_string();
+
+            Some(FilingDocument {
+                name,
+                sequence: text(0).and_then(|s| s.parse().ok()),
+                description: text(1),
+                document_type: text(3),
+                size: text(4).and_then(|s| s.parse().ok()),
+            })
+        })
+        .collect()
+}
+
+/// Combine the directory listing with the typed documents of the index page. Every file of
+/// the listing is kept; the ones missing from the index page have no type or description.
+pub fn merge_documents(index: &FilingIndex, described: Vec<FilingDocument>) -> Vec<FilingDocument> {
+    let mut described = described
+        .into_iter()
+        .map(|document| (document.name.clone(), document))
+        .collect::<HashMap<_, _>>();
+
+    let mut documents = index
+        .directory
+        .item
+        .iter()
+        .filter(|item| !item.item_type.starts_with("folder"))
+        .map(|item| {
+            described
+                .remove(&item.name)
+                .unwrap_or_else(|| FilingDocument {
+                    name: item.name.clone(),
+                    sequence: None,
+                    document_type: None,
+                    description: None,
+                    size: item.size.parse().ok(),
+                })
+        })
+        .collect::<Vec<_>>();
+    documents.sort_by_key(|document| (document.sequence.is_none(), document.sequence));
+    documents
+}
+
+pub fn save_documents(dir: &Path, documents: &[FilingDocument]) -> Result<()> {
+    fs::write(
+        dir.join(DOCUMENTS_FILE),
+        serde_json::to_string_pretty(documents)?,
+    )?;
+    Ok(())
+}
+
+/// The document entry of a downloaded file, looked up in the `documents.json` next to it
+pub fn document_for_path(filepath: &Path) -> Option<FilingDocument> {
+    let name = filepath.file_name()?.to_str()?;
+    let content = fs::read_to_string(filepath.parent()?.join(DOCUMENTS_FILE)).ok()?;
+    serde_json::from_str::<Vec<FilingDocument>>(&content)
+        .ok()?
+        .into_iter()
+        .find(|document| document.name == name)
+}
+
+/// Exhibit types to download and ingest, keyed by report type (`"8-K": ["EX-99.1"]`).
+/// A type matches its sub-numbered exhibits, so `EX-10` selects `EX-10.1` and `EX-10.2`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct ExhibitSelection(HashMap<String, Vec<String>>);
+
+impl Default for ExhibitSelection {
+    /// Earnings press releases of 8-Ks, subsidiaries and material contracts of 10-Ks and
+    /// material contracts of 10-Qs
+    fn default() -> Self {
+        ExhibitSelection::none()
+            .with(ReportType::Form8K, &["EX-99.1"])
+            .with(ReportType::Form10K, &["EX-21", "EX-10"])
+            .with(ReportType::Form10Q, &["EX-10"])
+    }
+}
+
+impl ExhibitSelection {
+    pub fn none() -> Self {
+        ExhibitSelection(HashMap::new())
+    }
+
+    pub fn with(mut self, report_type: ReportType, exhibit_types: &[&str]) -> Self {
+        self.0.insert(
+            report_type.to_string(),
+            exhibit_types.iter().map(|t| t.to_uppercase()).collect(),
+        );
+        self
+    }
+
+    /// Amendments use the selection of their own form if present, otherwise of the original
+    pub fn exhibit_types(&self, report_type: &ReportType) -> &[String] {
+        self.0
+            .get(&report_type.to_string())
+            .or_else(|| self.0.get(&report_type.original().to_string()))
+            .map(Vec::as_slice)
+            .unwrap_or_default()
+    }
+
+    pub fn matches(&self, report_type: &ReportType, document_type: &str) -> bool {
+        let document_type = document_type.to_uppercase();
+        self.exhibit_types(report_type).iter().any(|selected| {
+            document_type
+                .strip_prefix(selected.as_str())
+                .is_some_and(|rest| rest.is_empty() || rest.starts_with('.'))
+        })
+    }
+
+    /// Exhibits of a filing selected for download; only text documents can be ingested
+    pub fn select<'a>(
+        &self,
+        report_type: &ReportType,
+        documents: &'a [FilingDocument],
+    ) -> Vec<&'a FilingDocument> {
+        documents
+            .iter()
+            .filter(|document| document.is_exhibit() && document.is_text())
+            .filter(|document| {
+                document
+                    .document_type
+                    .as_deref()
+                    .is_some_and(|t| self.matches(report_type, t))
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::edgar::filing::{FilingIndexDirectory, FilingIndexItem};
+
+    const INDEX_PAGE: &str = r#"
+        <table class="tableFile" summary="Document Format Files">
+          <tr><th scope="col">Seq</th><th scope="col">Description</th><th scope="col">Document</th><th scope="col">Type</th><th scope="col">Size</th></tr>
+          <tr><td scope="row">1</td><td scope="row">8-K</td><td scope="row"><a href="/Archives/edgar/data/320193/000032019324000120/aapl-20241031.htm">aapl-20241031.htm</a> &nbsp;&nbsp;<span>iXBRL</span></td><td scope="row">8-K</td><td scope="row">34813</td></tr>
+          <tr><td scope="row">2</td><td scope="row">EX-99.1</td><td scope="row"><a href="/Archives/edgar/data/320193/000032019324000120/a8-kex991q4202409282024.htm">a8-kex991q4202409282024.htm</a></td><td scope="row">EX-99.1</td><td scope="row">158012</td></tr>
+          <tr><td scope="row">3</td><td scope="row">EX-99.10</td><td scope="row"><a href="/Archives/edgar/data/320193/000032019324000120/ex9910.htm">ex9910.htm</a></td><td scope="row">EX-99.10</td><td scope="row">1200</td></tr>
+          <tr><td scope="row">&nbsp;</td><td scope="row">Complete submission text file</td><td scope="row"><a href="/Archives/edgar/data/320193/000032019324000120/0000320193-24-000120.txt">0000320193-24-000120.txt</a></td><td scope="row">&nbsp;</td><td scope="row">379549</td></tr>
+        </table>
+    "#;
+
+    #[test]
+    fn test_select_exhibits_from_index() {
+        let index = FilingIndex {
+            directory: FilingIndexDirectory {
+                name: "/Archives/edgar/data/320193/000032019324000120".to_string(),
+                item: [
+                    "ex9910.htm",
+                    "a8-kex991q4202409282024.htm",
+                    "aapl-20241031.htm",
+                ]
+                .iter()
+                .map(|name| FilingIndexItem {
+                    name: name.to_string(),
+                    item_type: "text.gif".to_string(),
+                    size: String::new(),
+                })
+                .collect(),
+            },
+        };
+        let documents = merge_documents(&index, parse_index_page(INDEX_PAGE));
+
+        assert_eq!(documents.len(), 3);
+        assert_eq!(documents[0].name, "aapl-20241031.htm");
+        assert_eq!(documents[1].document_type.as_deref(), Some("EX-99.1"));
+        assert_eq!(documents[1].size, Some(158012));
+
+        let selection = ExhibitSelection::default();
+        let selected = selection.select(&ReportType::Form8K, &documents);
+        assert_eq!(selected.len(), 1);
+        assert_eq!(selected[0].name, "a8-kex991q4202409282024.htm");
+        assert!(selection.matches(&ReportType::Form10K, "EX-10.1"));
+        assert!(!selection.matches(&ReportType::Form10K, "EX-101.INS"));
+        assert!(selection.select(&ReportType::Form4, &documents).is_empty());
+    }
+}
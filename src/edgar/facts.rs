@@ -0,0 +1,414 @@
+// Client for the XBRL JSON APIs on data.sec.gov: companyfacts (every fact a company reported),
+// companyconcept (one concept for one company) and frames (one concept across all companies
+// for a calendar period). These give multi-year series without parsing each filing's instance.
+use anyhow::{anyhow, Result};
+use chrono::NaiveDate;
+use mime::APPLICATION_JSON;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
+use std::fs;
+
+use super::client::EdgarClient;
+use super::tickers;
+
+/// One reported value of a concept, as it appears in companyfacts and companyconcept responses
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FactValue {
+    /// Start of the reporting period, absent for instant (balance sheet) facts
+    pub start: Option<NaiveDate>,
+    pub end: NaiveDate,
+    pub val: f64,
+    /// Accession number of the filing that reported the value
+    pub accn: String,
+    /// Fiscal year and period ("FY", "Q1".."Q4") of the reporting filing
+    pub fy: Option<i32>,
+    pub fp: Option<String>,
+    pub form: String,
+    pub filed: NaiveDate,
+    /// Calendar frame (e.g. "CY2023Q4I") when the value was picked for the frames API
+    pub frame: Option<String>,
+}
+
+/// Period a fact value covers
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum FactPeriod {
+    Instant(NaiveDate),
+    Duration { start: NaiveDate, end: NaiveDate },
+}
+
+impl FactPeriod {
+    pub fn end(&self) -> NaiveDate {
+        match self {
+            FactPeriod::Instant(end) | FactPeriod::Duration { end, .. } => *end,
+        }
+    }
+
+    /// Length of a duration period in days, zero for instants
+    pub fn days(&self) -> i64 {
+        match self {
+            FactPeriod::Instant(_) => 0,
+            FactPeriod::Duration { start, end } => (*end - *start).num_days(),
+        }
+    }
+
+    pub fn is_annual(&self) -> bool {
+        (350..=380).contains(&self.days())
+    }
+
+    pub fn is_quarterly(&self) -> bool {
+        (80..=100).contains(&self.days())
+    }
+}
+
+impl fmt::Display for FactPeriod {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            FactPeriod::Instant(end) => write!(f, "{}", end),
+            FactPeriod::Duration { start, end } => write!(f, "{} to {}", start, end),
+        }
+    }
+}
+
+impl FactValue {
+    pub fn period(&self) -> FactPeriod {
+        match self.start {
+            Some(start) => FactPeriod::Duration {
+                start,
+                end: self.end,
+            },
+            None => FactPeriod::Instant(self.end),
+        }
+    }
+}
+
+/// All values of one concept, keyed by unit of measure (e.g. "USD", "shares", "USD/shares")
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ConceptFacts {
+    pub label: Option<String>,
+    pub description: Option<String>,
+    pub units: HashMap<String, Vec<FactValue>>,
+}
+
+/// Response of `api/xbrl/companyfacts/CIK##########.json`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CompanyFacts {
+    pub cik: u64,
+    #[serde(rename = "entityName")]
+    pub entity_name: String,
+    /// Concepts keyed by taxonomy ("us-gaap", "dei", "ifrs-full", ...) and then by concept name
+    #[serde(default)]
+    pub facts: HashMap<String, HashMap<String, ConceptFacts>>,
+}
+
+/// Response of `api/xbrl/companyconcept/CIK##########/{taxonomy}/{tag}.json`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CompanyConcept {
+    pub cik: u64,
+    pub taxonomy: String,
+    pub tag: String,
+    pub label: Option<String>,
+    pub description: Option<String>,
+    #[serde(rename = "entityName")]
+    pub entity_name: String,
+    pub units: HashMap<String, Vec<FactValue>>,
+}
+
+/// One company's value in a frame
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FrameValue {
+    pub accn: String,
+    pub cik: u64,
+    #[serde(rename = "entityName")]
+    pub entity_name: String,
+    pub loc: Option<String>,
+    pub start: Option<NaiveDate>,
+    pub end: NaiveDate,
+    pub val: f64,
+}
+
+/// Response of `api/xbrl/frames/{taxonomy}/{tag}/{unit}/{period}.json`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Frame {
+    pub taxonomy: String,
+    pub tag: String,
+    /// Calendar period of the frame, e.g. "CY2023", "CY2023Q4" or "CY2023Q4I"
+    pub ccp: String,
+    pub uom: String,
+    pub label: Option<String>,
+    pub description: Option<String>,
+    pub pts: usize,
+    pub data: Vec<FrameValue>,
+}
+
+/// Values of one concept in one unit, ordered by period
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FactSeries {
+    pub taxonomy: String,
+    pub concept: String,
+    pub label: Option<String>,
+    pub unit: String,
+    pub values: Vec<FactValue>,
+}
+
+impl FactSeries {
+    fn new(
+        taxonomy: &str,
+        concept: &str,
+        label: Option<String>,
+        unit: &str,
+        values: &[FactValue],
+    ) -> Self {
+        let mut values = values.to_vec();
+        values.sort_by(|a, b| a.period().cmp(&b.period()).then(a.filed.cmp(&b.filed)));
+        FactSeries {
+            taxonomy: taxonomy.to_string(),
+            concept: concept.to_string(),
+            label,
+            unit: unit.to_string(),
+            values,
+        }
+    }
+
+    /// One value per period: the same period is repeated in later filings as a comparative
+    /// figure, so keep the most recently filed value, which includes any restatement
+    pub fn latest_per_period(&self) -> BTreeMap<FactPeriod, &FactValue> {
+        let mut periods: BTreeMap<FactPeriod, &FactValue> = BTreeMap::new();
+        for value in &self.values {
+            let entry = periods.entry(value.period()).or_insert(value);
+            if value.filed >= entry.filed {
+                *entry = value;
+            }
+        }
+        periods
+    }
+
+    /// Values covering a full fiscal year (or instants at fiscal year end from annual reports)
+    pub fn annual(&self) -> Vec<(FactPeriod, &FactValue)> {
+        self.latest_per_period()
+            .into_iter()
+            .filter(|(period, value)| match period {
+                FactPeriod::Instant(_) => value.fp.as_deref() == Some("FY"),
+                FactPeriod::Duration { .. } => period.is_annual(),
+            })
+            .collect()
+    }
+
+    /// Values covering a single fiscal quarter
+    pub fn quarterly(&self) -> Vec<(FactPeriod, &FactValue)> {
+        self.latest_per_period()
+            .into_iter()
+            .filter(|(period, _)| period.is_quarterly())
+            .collect()
+    }
+
+    pub fn to_markdown(&self) -> String {
+        let mut md = format!(
+            "### {} ({}:{}, {})\n\n| Period | Value | Form | Filed |\n| --- | --- | --- | --- |\n",
+            self.label.as_deref().unwrap_or(&self.concept),
+            self.taxonomy,
+            self.concept,
+            self.unit
+        );
+        for (period, value) in self.latest_per_period() {
+            md.push_str(&format!(
+                "| {} | {} | {} | {} |\n",
+                period, value.val, value.form, value.filed
+            ));
+        }
+        md
+    }
+}
+
+impl CompanyFacts {
+    pub fn concept(&self, taxonomy: &str, concept: &str) -> Option<&ConceptFacts> {
+        self.facts.get(taxonomy)?.get(concept)
+    }
+
+    /// Series of a concept in the given unit
+    pub fn series(&self, taxonomy: &str, concept: &str, unit: &str) -> Option<FactSeries> {
+        let facts = self.concept(taxonomy, concept)?;
+        let values = facts.units.get(unit)?;
+        Some(FactSeries::new(
+            taxonomy,
+            concept,
+            facts.label.clone(),
+            unit,
+            values,
+        ))
+    }
+
+    /// Series of a concept in every unit it was reported in
+    pub fn all_series(&self, taxonomy: &str, concept: &str) -> Vec<FactSeries> {
+        let Some(facts) = self.concept(taxonomy, concept) else {
+            return Vec::new();
+        };
+        let mut series = facts
+            .units
+            .iter()
+            .map(|(unit, values)| {
+                FactSeries::new(taxonomy, concept, facts.label.clone(), unit, values)
+            })
+            .collect::<Vec<_>>();
+        series.sort_by(|a, b| a.unit.cmp(&b.unit));
+        series
+    }
+}
+
+impl CompanyConcept {
+    pub fn series(&self, unit: &str) -> Option<FactSeries> {
+        let values = self.units.get(unit)?;
+        Some(FactSeries::new(
+            &self.taxonomy,
+            &self.tag,
+            self.label.clone(),
+            unit,
+            values,
+        ))
+    }
+}
+
+/// Calendar period identifier used by the frames API: "CY2023" for annual durations,
+/// "CY2023Q4" for quarterly durations and "CY2023Q4I" for instants at quarter end
+pub fn frame_period(year: i32, quarter: Option<u32>, instant: bool) -> String {
+    match (quarter, instant) {
+        (Some(q), true) => format!("CY{}Q{}I", year, q),
+        (Some(q), false) => format!("CY{}Q{}", year, q),
+        // Annual instants are reported as of the fourth quarter
+        (None, true) => format!("CY{}Q4I", year),
+        (None, false) => format!("CY{}", year),
+    }
+}
+
+/// Fetch a data.sec.gov XBRL API resource into the facts cache and parse it. The cached
+/// copy is used when the request fails.
+async fn fetch_xbrl_api<T: DeserializeOwned>(client: &EdgarClient, resource: &str) -> Result<T> {
+    let url = format!("{}/api/xbrl/{}", client.data_url(), resource);
+    let filepath = client.facts_dir().join(resource);
+
+    if let Err(e) = client.fetch(&url, &filepath, APPLICATION_JSON).await {
+        if !filepath.exists() {
+            return Err(anyhow!("Failed to fetch {}: {}", url, e));
+        }
+        log::warn!("Error fetching {}, using cached version: {}", url, e);
+    }
+
+    let content = fs::read_to_string(&filepath)?;
+    serde_json::from_str(&content).map_err(|e| anyhow!("Failed to parse {}: {}", url, e))
+}
+
+fn cik_path(cik: &str) -> String {
+    format!("CIK{:0>10}", cik.trim_start_matches("CIK"))
+}
+
+/// Every XBRL fact the company has reported, across all of its filings
+pub async fn fetch_company_facts(client: &EdgarClient, cik: &str) -> Result<CompanyFacts> {
+    fetch_xbrl_api(client, &format!("companyfacts/{}.json", cik_path(cik))).await
+}
+
+pub async fn fetch_company_facts_for_ticker(
+    client: &EdgarClient,
+    ticker: &str,
+) -> Result<CompanyFacts> {
+    let cik = tickers::resolve_cik(client, ticker).await?;
+    fetch_company_facts(client, &cik).await
+}
+
+/// Every value of a single concept reported by the company
+pub async fn fetch_company_concept(
+    client: &EdgarClient,
+    cik: &str,
+    taxonomy: &str,
+    tag: &str,
+) -> Result<CompanyConcept> {
+    fetch_xbrl_api(
+        client,
+        &format!("companyconcept/{}/{}/{}.json", cik_path(cik), taxonomy, tag),
+    )
+    .await
+}
+
+/// One value per reporting company for a concept, unit and calendar period (see [`frame_period`])
+pub async fn fetch_frame(
+    client: &EdgarClient,
+    taxonomy: &str,
+    tag: &str,
+    unit: &str,
+    period: &str,
+) -> Result<Frame> {
+    fetch_xbrl_api(
+        client,
+        &format!("frames/{}/{}/{}/{}.json", taxonomy, tag, unit, period),
+    )
+    .await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const COMPANY_FACTS: &str = r#"{
+        "cik": 320193,
+        "entityName": "Apple Inc.",
+        "facts": {
+            "us-gaap": {
+                "Revenues": {
+                    "label": "Revenues",
+                    "description": "Amount of revenue recognized.",
+                    "units": {
+                        "USD": [
+                            {"start": "2021-09-26", "end": "2022-09-24", "val": 394328000000, "accn": "0000320193-22-000108", "fy": 2022, "fp": "FY", "form": "10-K", "filed": "2022-10-28", "frame": "CY2022"},
+                            {"start": "2022-06-26", "end": "2022-09-24", "val": 90146000000, "accn": "0000320193-22-000108", "fy": 2022, "fp": "FY", "form": "10-K", "filed": "2022-10-28"},
+                            {"start": "2021-09-26", "end": "2022-09-24", "val": 394000000000, "accn": "0000320193-23-000106", "fy": 2023, "fp": "FY", "form": "10-K", "filed": "2023-11-03"},
+                            {"start": "2022-09-25", "end": "2023-09-30", "val": 383285000000, "accn": "0000320193-23-000106", "fy": 2023, "fp": "FY", "form": "10-K", "filed": "2023-11-03", "frame": "CY2023"}
+                        ]
+                    }
+                },
+                "EarningsPerShareDiluted": {
+                    "label": null,
+                    "description": null,
+                    "units": {
+                        "USD/shares": [
+                            {"start": "2022-09-25", "end": "2023-09-30", "val": 6.13, "accn": "0000320193-23-000106", "fy": null, "fp": null, "form": "10-K", "filed": "2023-11-03"}
+                        ]
+                    }
+                }
+            }
+        }
+    }"#;
+
+    #[test]
+    fn test_company_facts_series() {
+        let facts: CompanyFacts = serde_json::from_str(COMPANY_FACTS).unwrap();
+        assert_eq!(facts.entity_name, "Apple Inc.");
+        assert!(facts.series("us-gaap", "Revenues", "shares").is_none());
+
+        let revenues = facts.series("us-gaap", "Revenues", "USD").unwrap();
+        assert_eq!(revenues.values.len(), 4);
+
+        // The FY2022 figure restated in the FY2023 report replaces the original one
+        let annual = revenues.annual();
+        assert_eq!(annual.len(), 2);
+        assert_eq!(annual[0].1.val, 394000000000.0);
+        assert_eq!(annual[1].1.val, 383285000000.0);
+
+        let quarterly = revenues.quarterly();
+        assert_eq!(quarterly.len(), 1);
+        assert_eq!(quarterly[0].1.val, 90146000000.0);
+
+        let eps = facts.all_series("us-gaap", "EarningsPerShareDiluted");
+        assert_eq!(eps.len(), 1);
+        assert_eq!(eps[0].unit, "USD/shares");
+        assert_eq!(eps[0].values[0].val, 6.13);
+    }
+
+    #[test]
+    fn test_frame_period() {
+        assert_eq!(frame_period(2023, None, false), "CY2023");
+        assert_eq!(frame_period(2023, Some(2), false), "CY2023Q2");
+        assert_eq!(frame_period(2023, Some(4), true), "CY2023Q4I");
+        assert_eq!(cik_path("320193"), "CIK0000320193");
+        assert_eq!(cik_path("0000320193"), "CIK0000320193");
+    }
+}
@@ -7,25 +7,34 @@ use encoding_rs_io::DecodeReaderBytesBuilder;
 use indicatif::MultiProgress;
 use indicatif::ProgressBar;
 use itertools::Itertools;
+use langchain_rust::schemas::Document;
 use langchain_rust::vectorstore::pgvector::{PgFilter::*, PgLit::*, Store};
 use langchain_rust::vectorstore::VectorStore as _;
 use log::{error, info};
-use mime::{APPLICATION_JSON, TEXT_XML};
-use reqwest::Client;
+use mime::{Mime, APPLICATION_JSON, TEXT_HTML, TEXT_XML};
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
+use sqlx::{Pool, Postgres};
 use std::collections::HashMap;
 use std::fs::{self, File};
 use std::io::{BufReader, Read};
-use std::path::{Path, PathBuf};
+use std::path::Path;
 use std::str::FromStr;
 use std::sync::Arc;
-use url::Url;
 
-use crate::utils::http::fetch_and_save;
+use crate::utils::cache;
 
+use super::client::EdgarClient;
+use super::exhibits::{self, ExhibitSelection, FilingDocument};
+use super::holdings;
+use super::items::Form8KItem;
+use super::linkbase::{
+    Calculation, Labels, Presentation, CALCULATION_LINKBASE_SUFFIX, LABEL_LINKBASE_SUFFIX,
+    LINKBASE_SUFFIXES, PRESENTATION_LINKBASE_SUFFIX,
+};
 use super::query::Query;
-use super::report::ReportType;
+use super::report::{FormParser, ReportType};
+use super::sections;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Filing {
@@ -43,14 +52,95 @@ pub struct Filing {
     pub is_inline_xbrl: bool,
     pub primary_document: String,
     pub primary_doc_description: String,
+    /// Accession number of the filing this amendment amends, if it could be found
+    #[serde(default)]
+    pub amends: Option<String>,
+    /// Name of the filer from its submissions, the only label of entities without a ticker
+    #[serde(default)]
+    pub entity_name: Option<String>,
 }
 
 impl Filing {
-    fn matches_report_type(&self, report_types: &[ReportType]) -> bool {
-        report_types.iter().any(|rt| &self.report_type == rt)
+    /// Amendments match the form they amend only when `include_amendments` is set
+    fn matches_report_type(&self, report_types: &[ReportType], include_amendments: bool) -> bool {
+        report_types.iter().any(|rt| {
+            &self.report_type == rt
+                || (include_amendments
+                    && self.report_type.is_amendment()
+                    && &self.report_type.original() == rt)
+        })
+    }
+
+    /// Insider ownership reports, filed as ownership XML rather than XBRL
+    fn is_ownership_report(&self) -> bool {
+        self.report_type.parser() == FormParser::Ownership
+    }
+
+    /// Institutional holdings reports, whose positions live in a separate information table
+    fn is_holdings_report(&self) -> bool {
+        self.report_type.parser() == FormParser::Holdings
+    }
+
+    /// Filings ingested as the text of their primary document only (proxies, prospectuses)
+    fn is_text_report(&self) -> bool {
+        self.report_type.parser() == FormParser::Text
+    }
+
+    /// Name of the primary document without the XSL rendering directory, if any
+    fn raw_primary_document(&self) -> &str {
+        self.primary_document
+            .rsplit('/')
+            .next()
+            .unwrap_or(&self.primary_document)
+    }
+
+    /// Item codes reported by an 8-K, empty for every other form
+    pub fn form_8k_items(&self) -> Vec<Form8KItem> {
+        match self.report_type.original() {
+            ReportType::Form8K => Form8KItem::parse_list(&self.items),
+            _ => Vec::new(),
+        }
+    }
+
+    /// Item filters only narrow down 8-Ks, other report types always pass
+    fn matches_items(&self, items: &[Form8KItem]) -> bool {
+        if items.is_empty() || self.report_type.original() != ReportType::Form8K {
+            return true;
+        }
+        self.form_8k_items().iter().any(|item| items.contains(item))
+    }
+
+    /// Whether `self` is an amendment of `original`: same form family, same file number and
+    /// same period of report, filed no earlier
+    fn amends_filing(&self, original: &Filing) -> bool {
+        self.report_type.is_amendment()
+            && !original.report_type.is_amendment()
+            && self.report_type.original() == original.report_type
+            && !self.file_number.is_empty()
+            && self.file_number == original.file_number
+            && self.report_date.is_some()
+            && self.report_date == original.report_date
+            && self.filing_date >= original.filing_date
     }
 }
 
+/// Point every amendment at the latest filing it amends
+fn link_amendments(filings: &mut [Filing]) {
+    for i in 0..filings.len() {
+        if !filings[i].report_type.is_amendment() {
+            continue;
+        }
+        filings[i].amends = filings
+            .iter()
+            .filter(|original| filings[i].amends_filing(original))
+            .max_by_key(|original| original.filing_date)
+            .map(|original| original.accession_number.clone());
+    }
+}
+
+/// Filings fetched for a query, grouped by company CIK and then keyed by local document path
+pub type FilingsByCik = HashMap<String, HashMap<String, Filing>>;
+
 fn process_filing_entries(entry: &FilingEntry, query: &Query) -> Result<Vec<Filing>> {
     let mut filings = Vec::new();
 
@@ -76,34 +166,28 @@ fn process_filing_entries(entry: &FilingEntry, query: &Query) -> Result<Vec<Fili
             is_inline_xbrl: entry.is_inline_xbrl[i] == 1,
             primary_document: entry.primary_document[i].clone(),
             primary_doc_description: entry.primary_doc_description[i].clone(),
+            amends: None,
+            entity_name: None,
         };
-        // Construct document URL
-        let base = "https://www.sec.gov/Archives/edgar/data";
-        let cik = format!("{:0>10}", query.tickers[0]); // Assuming the first ticker's CIK is used
-        let accession_number = filing.accession_number.replace("-", "");
-        let document_url = format!(
-            "{}/{}/{}/{}",
-            base, cik, accession_number, filing.primary_document
-        );
-
-        log::debug!("Constructed document URL: {}", document_url);
+        filings.push(filing);
+    }
+    // Amendments are linked before filtering, the original may fall outside the query
+    link_amendments(&mut filings);
 
-        // Check if filing matches query criteria
-        if filing.matches_report_type(&query.report_types)
+    // Check if filing matches query criteria
+    filings.retain(|filing| {
+        filing.matches_report_type(&query.report_types, query.include_amendments)
+            && filing.matches_items(&query.items)
             && filing.filing_date >= query.start_date
             && filing.filing_date <= query.end_date
-        {
-            filings.push(filing);
-        }
-    }
+    });
 
     Ok(filings)
 }
 
 // Hardcoded values
-use crate::utils::dirs::EDGAR_FILINGS_DIR;
-pub const EDGAR_DATA_URL: &str = "https://data.sec.gov";
-pub const USER_AGENT: &str = "software@example.com";
+/// Number of largest 13F positions included in the indexed holdings summary
+const HOLDINGS_SUMMARY_POSITIONS: usize = 50;
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct CompanyInfo {
@@ -165,6 +249,96 @@ mod tests {
             );
         }
     }
+
+    #[test]
+    fn test_filter_8k_items() {
+        let entry: FilingEntry = serde_json::from_value(serde_json::json!({
+            "accessionNumber": ["0000320193-24-000120", "0000320193-24-000110", "0000320193-24-000100"],
+            "filingDate": ["2024-10-31", "2024-09-10", "2024-08-02"],
+            "reportDate": ["2024-10-31", "2024-09-10", "2024-06-29"],
+            "acceptanceDateTime": ["", "", ""],
+            "act": ["34", "34", "34"],
+            "form": ["8-K", "8-K", "10-Q"],
+            "fileNumber": ["001-36743", "001-36743", "001-36743"],
+            "filmNumber": ["", "", ""],
+            "items": ["2.02,9.01", "5.02", ""],
+            "size": [0, 0, 0],
+            "isXBRL": [1, 1, 1],
+            "isInlineXBRL": [1, 1, 1],
+            "primaryDocument": ["a.htm", "b.htm", "c.htm"],
+            "primaryDocDescription": ["8-K", "8-K", "10-Q"]
+        }))
+        .unwrap();
+
+        let query = Query::builder()
+            .tickers(vec!["AAPL".to_string()])
+            .start_date(NaiveDate::from_ymd_opt(2024, 1, 1).unwrap())
+            .end_date(NaiveDate::from_ymd_opt(2024, 12, 31).unwrap())
+            .report_types(vec![ReportType::Form8K, ReportType::Form10Q])
+            .items(vec![Form8KItem::ResultsOfOperations])
+            .build()
+            .unwrap();
+
+        let filings = process_filing_entries(&entry, &query).unwrap();
+        let accessions: Vec<&str> = filings
+            .iter()
+            .map(|f| f.accession_number.as_str())
+            .collect();
+
+        assert_eq!(
+            accessions,
+            vec!["0000320193-24-000120", "0000320193-24-000100"]
+        );
+        assert_eq!(
+            filings[0].form_8k_items(),
+            vec![
+                Form8KItem::ResultsOfOperations,
+                Form8KItem::FinancialStatementsAndExhibits
+            ]
+        );
+    }
+
+    #[test]
+    fn test_link_amendments() {
+        let entry: FilingEntry = serde_json::from_value(serde_json::json!({
+            "accessionNumber": ["0000950170-24-000200", "0000950170-24-000100", "0000950170-23-000100"],
+            "filingDate": ["2024-04-29", "2024-02-28", "2023-02-27"],
+            "reportDate": ["2023-12-31", "2023-12-31", "2022-12-31"],
+            "acceptanceDateTime": ["", "", ""],
+            "act": ["34", "34", "34"],
+            "form": ["10-K/A", "10-K", "10-K"],
+            "fileNumber": ["001-12345", "001-12345", "001-12345"],
+            "filmNumber": ["", "", ""],
+            "items": ["", "", ""],
+            "size": [0, 0, 0],
+            "isXBRL": [1, 1, 1],
+            "isInlineXBRL": [1, 1, 1],
+            "primaryDocument": ["a.htm", "b.htm", "c.htm"],
+            "primaryDocDescription": ["10-K/A", "10-K", "10-K"]
+        }))
+        .unwrap();
+
+        let query = Query::builder()
+            .tickers(vec!["TEST".to_string()])
+            .start_date(NaiveDate::from_ymd_opt(2024, 1, 1).unwrap())
+            .end_date(NaiveDate::from_ymd_opt(2024, 12, 31).unwrap())
+            .report_types(vec![ReportType::Form10K])
+            .build()
+            .unwrap();
+        let filings = process_filing_entries(&entry, &query).unwrap();
+        assert_eq!(filings.len(), 1);
+        assert_eq!(filings[0].report_type, ReportType::Form10K);
+
+        let query = Query {
+            include_amendments: true,
+            ..query
+        };
+        let filings = process_filing_entries(&entry, &query).unwrap();
+        assert_eq!(filings.len(), 2);
+        assert_eq!(filings[0].report_type, ReportType::Form10KA);
+        assert_eq!(filings[0].amends.as_deref(), Some("0000950170-24-000100"));
+        assert_eq!(filings[1].amends, None);
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -222,23 +396,17 @@ pub struct CompanyFilings {
     #[serde(rename = "sicDescription")]
     pub sic_description: String,
     pub name: String,
+    /// Month and day the fiscal year ends, `MMDD`
+    #[serde(rename = "fiscalYearEnd", default)]
+    pub fiscal_year_end: Option<String>,
     pub tickers: Vec<String>,
     pub exchanges: Vec<String>,
     pub filings: FilingsData,
 }
 
-async fn fetch_filing_page(client: &Client, url: &str, filepath: &Path) -> Result<()> {
-    match fetch_and_save(
-        client,
-        &Url::parse(url)?,
-        filepath,
-        USER_AGENT,
-        APPLICATION_JSON,
-        crate::edgar::rate_limiter(),
-    )
-    .await
-    {
-        Ok(()) => {
+async fn fetch_filing_page(client: &EdgarClient, url: &str, filepath: &Path) -> Result<()> {
+    match client.fetch_if_stale(url, filepath, APPLICATION_JSON).await {
+        Ok(true) => {
             log::debug!(
                 "Successfully fetched and saved {} filing to {}",
                 url,
@@ -246,6 +414,10 @@ async fn fetch_filing_page(client: &Client, url: &str, filepath: &Path) -> Resul
             );
             Ok(())
         }
+        Ok(false) => {
+            log::debug!("Cached {} is up to date", filepath.to_str().unwrap());
+            Ok(())
+        }
         Err(e) => {
             // If the file exists despite an error, try to use it anyway
             if !filepath.exists() {
@@ -318,7 +490,7 @@ async fn process_filing_page(
 }
 
 async fn process_adr_company_filings(
-    client: &Client,
+    client: &EdgarClient,
     cik: &str,
     limit: Option<usize>,
 ) -> Result<CompanyFilings> {
@@ -332,19 +504,17 @@ async fn process_adr_company_filings(
 }
 
 async fn get_company_filings_internal(
-    client: &Client,
+    client: &EdgarClient,
     cik: &str,
     limit: Option<usize>,
 ) -> Result<CompanyFilings> {
     // Ensure CIK is 10 digits with leading zeros
     let padded_cik = format!("{:0>10}", cik);
-    let initial_url = format!("{}/submissions/CIK{}.json", EDGAR_DATA_URL, padded_cik);
+    let initial_url = format!("{}/submissions/CIK{}.json", client.data_url(), padded_cik);
 
     info!("Fetching company filings from EDGAR API");
     log::debug!("EDGAR API Request URL: {}", initial_url);
-    log::debug!("EDGAR API Headers: User-Agent: {}", USER_AGENT);
-
-    crate::utils::dirs::ensure_edgar_dirs()?;
+    log::debug!("EDGAR API Headers: User-Agent: {}", client.user_agent());
 
     let mut all_filings = Vec::new();
     let mut fetched_count = 0;
@@ -352,12 +522,11 @@ async fn get_company_filings_internal(
     let mut additional_files = Vec::new();
 
     loop {
-        let filepath = PathBuf::from(EDGAR_FILINGS_DIR)
+        let filepath = client
+            .filings_dir()
             .join(format!("CIK{}_{}.json", padded_cik, fetched_count));
 
-        if !filepath.exists() {
-            fetch_filing_page(client, &current_url, &filepath).await?;
-        }
+        fetch_filing_page(client, &current_url, &filepath).await?;
 
         process_filing_page(
             &filepath,
@@ -384,12 +553,14 @@ async fn get_company_filings_internal(
 
         // Get next page URL
         let next_page = additional_files.remove(0);
-        current_url = format!("{}/submissions/{}", EDGAR_DATA_URL, next_page.name);
+        current_url = format!("{}/submissions/{}", client.data_url(), next_page.name);
     }
 
     // Get the initial response which contains company info
     let content = fs::read_to_string(
-        PathBuf::from(EDGAR_FILINGS_DIR).join(format!("CIK{}_{}.json", padded_cik, 0)),
+        client
+            .filings_dir()
+            .join(format!("CIK{}_{}.json", padded_cik, 0)),
     )?;
 
     let mut initial_response: CompanyFilings = serde_json::from_str(&content)
@@ -462,7 +633,7 @@ fn log_filing_summary(merged: &FilingEntry, padded_cik: &str) {
 }
 
 pub async fn get_company_filings(
-    client: &Client,
+    client: &EdgarClient,
     cik: &str,
     limit: Option<usize>,
     is_adr: bool,
@@ -474,111 +645,413 @@ pub async fn get_company_filings(
     }
 }
 
-async fn get_cik_for_query(query: &Query) -> Result<String> {
-    let tickers = super::tickers::fetch_tickers().await?;
-    tickers
-        .iter()
-        .find(|(ticker, _, _)| ticker.as_str() == query.tickers[0])
-        .map(|(_, _, cik)| cik.to_string())
-        .ok_or_else(|| anyhow!("CIK not found for ticker: {}", query.tickers[0]))
+/// Mark the cached submission pages of a company as stale, so the next lookup revalidates them
+pub fn invalidate_company_filings(client: &EdgarClient, cik: &str) -> Result<()> {
+    let prefix = format!("CIK{:0>10}_", cik);
+    let Ok(entries) = fs::read_dir(client.filings_dir()) else {
+        return Ok(());
+    };
+    for entry in entries {
+        let path = entry?.path();
+        let is_page = path
+            .file_name()
+            .and_then(|name| name.to_str())
+            .is_some_and(|name| {
+                name.starts_with(&prefix)
+                    && name.ends_with(".json")
+                    && !name.ends_with(".meta.json")
+            });
+        if is_page {
+            cache::invalidate(&path)?;
+        }
+    }
+    Ok(())
+}
+
+/// Re-download the ticker list and the submissions of the given tickers regardless of cache age.
+/// Returns the number of filings known for each ticker afterwards.
+pub async fn refresh_filings(
+    client: &EdgarClient,
+    tickers: &[String],
+) -> Result<Vec<(String, usize)>> {
+    super::tickers::refresh_tickers(client).await?;
+    super::ticker_index::refresh_ticker_index(client).await?;
+
+    let mut counts = Vec::new();
+    for ticker in tickers {
+        let cik = super::tickers::resolve_cik(client, ticker).await?;
+        invalidate_company_filings(client, &cik)?;
+        let filings = get_company_filings(client, &cik, None, false).await?;
+        counts.push((
+            ticker.clone(),
+            filings.filings.recent.accession_number.len(),
+        ));
+    }
+    Ok(counts)
 }
 
+/// Resolve every ticker in the query to its CIK, returning (ticker, CIK) pairs in query order.
+/// Tickers are normalized (`brk/b` is `BRK-B`) and CIK references (`CIK0000320193`) resolve
+/// to themselves. Tickers that do not resolve are logged and skipped so the rest of the query
+/// still runs.
+async fn get_ciks_for_query(client: &EdgarClient, query: &Query) -> Vec<(String, String)> {
+    let mut ciks = Vec::new();
+    for ticker in query.tickers.iter().unique() {
+        match super::tickers::resolve_cik(client, ticker).await {
+            Ok(cik) => ciks.push((ticker.clone(), cik)),
+            Err(e) => log::warn!("Skipping {}: {}", ticker, e),
+        }
+    }
+    ciks
+}
+
+async fn fetch_filing_document(
+    client: &EdgarClient,
+    cik: &str,
+    filing: &Filing,
+    document: &str,
+    content_type: Mime,
+) -> Result<String> {
+    let accession_number = filing.accession_number.replace("-", "");
+
+    let document_path = client
+        .filings_dir()
+        .join(cik)
+        .join(&accession_number)
+        .join(document);
+    let document_url = format!(
+        "{}/{}/{}/{}",
+        client.archives_url(),
+        cik,
+        accession_number,
+        document
+    );
+
+    log::debug!("EDGAR Document Request URL: {}", document_url);
+    log::info!("Fetching: {}", document_url);
+
+    client
+        .fetch(&document_url, &document_path, content_type)
+        .await?;
+
+    let document_path = document_path.to_string_lossy().into_owned();
+    log::info!("Saved filing document to {}", document_path);
+    Ok(document_path)
+}
+
+/// Directory listing of a filing, served as `index.json` next to its documents
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FilingIndex {
+    pub directory: FilingIndexDirectory,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FilingIndexDirectory {
+    pub name: String,
+    #[serde(default)]
+    pub item: Vec<FilingIndexItem>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FilingIndexItem {
+    pub name: String,
+    #[serde(rename = "type", default)]
+    pub item_type: String,
+    #[serde(default)]
+    pub size: String,
+}
+
+/// Fetch (or read from cache) the `index.json` listing of a filing's documents
+async fn fetch_filing_index(
+    client: &EdgarClient,
+    cik: &str,
+    filing: &Filing,
+) -> Result<FilingIndex> {
+    let path = fetch_filing_document(client, cik, filing, "index.json", APPLICATION_JSON).await?;
+    let content = fs::read_to_string(&path)?;
+    serde_json::from_str(&content)
+        .map_err(|e| anyhow!("Failed to parse filing index {}: {}", path, e))
+}
+
+/// Fetch the typed document list of a filing: the `index.json` listing merged with the types
+/// and descriptions of the filing index page. Saved as `documents.json` next to the documents.
+async fn fetch_filing_documents(
+    client: &EdgarClient,
+    cik: &str,
+    filing: &Filing,
+) -> Result<Vec<FilingDocument>> {
+    let index = fetch_filing_index(client, cik, filing).await?;
+    let index_page = format!("{}-index.htm", filing.accession_number);
+    let path = fetch_filing_document(client, cik, filing, &index_page, TEXT_HTML).await?;
+    let documents =
+        exhibits::merge_documents(&index, exhibits::parse_index_page(&read_decoded(&path)?));
+
+    let dir = Path::new(&path)
+        .parent()
+        .ok_or_else(|| anyhow!("Invalid filing path: {}", path))?;
+    exhibits::save_documents(dir, &documents)?;
+    Ok(documents)
+}
+
+/// Fetch the label, presentation and calculation linkbases of an XBRL filing, as listed in
+/// the filing index. Linkbases are saved next to the instance, where extraction picks them up;
+/// the ones that fail to download are skipped.
+async fn fetch_linkbases(client: &EdgarClient, cik: &str, filing: &Filing) -> Result<Vec<String>> {
+    let index = fetch_filing_index(client, cik, filing).await?;
+    let mut paths = Vec::new();
+    for suffix in LINKBASE_SUFFIXES {
+        let Some(linkbase) = index
+            .directory
+            .item
+            .iter()
+            .map(|item| item.name.as_str())
+            .find(|name| name.ends_with(suffix))
+        else {
+            log::info!(
+                "No {} linkbase in filing {}",
+                suffix,
+                filing.accession_number
+            );
+            continue;
+        };
+        match fetch_filing_document(client, cik, filing, linkbase, TEXT_XML).await {
+            Ok(path) => paths.push(path),
+            Err(e) => log::warn!(
+                "Failed to fetch linkbase {} of {}: {}",
+                linkbase,
+                filing.accession_number,
+                e
+            ),
+        }
+    }
+    Ok(paths)
+}
+
+/// Download the exhibits of a filing selected for its report type. Exhibits that fail to
+/// download are skipped.
+async fn fetch_selected_exhibits(
+    client: &EdgarClient,
+    cik: &str,
+    filing: &Filing,
+    selection: &ExhibitSelection,
+) -> Result<Vec<String>> {
+    let documents = fetch_filing_documents(client, cik, filing).await?;
+    let mut paths = Vec::new();
+    for document in selection.select(&filing.report_type, &documents) {
+        match fetch_filing_document(client, cik, filing, &document.name, TEXT_HTML).await {
+            Ok(path) => paths.push(path),
+            Err(e) => log::warn!(
+                "Failed to fetch exhibit {} of {}: {}",
+                document.name,
+                filing.accession_number,
+                e
+            ),
+        }
+    }
+    Ok(paths)
+}
+
+/// Download the documents of a filing as its form's parser needs them. For financial reports
+/// that is the XBRL instance, the primary HTML document and the exhibits selected for the
+/// report type, and it succeeds if at least one of them was fetched.
 async fn fetch_and_process_filing(
-    client: &Client,
+    client: &EdgarClient,
     cik: &str,
     filing: &Filing,
+    exhibit_selection: &ExhibitSelection,
     progress: Option<Arc<ProgressBar>>,
-) -> Result<(String, Filing)> {
+) -> Result<Vec<(String, Filing)>> {
     if let Some(pb) = progress {
         pb.set_message(format!("Fetching filing {}", filing.accession_number));
         pb.set_position(25);
     }
-    let base = "https://www.sec.gov/Archives/edgar/data";
-    let accession_number = filing.accession_number.replace("-", "");
-    let xbrl_document = filing.primary_document.replace(".htm", "_htm.xml");
+    let mut documents = Vec::new();
+
+    if filing.is_ownership_report() {
+        // The primary document points at the XSL rendering (e.g. `xslF345X05/form4.xml`),
+        // the raw ownership XML has the same name at the root of the filing
+        let ownership_document = filing.raw_primary_document();
+        let path = fetch_filing_document(client, cik, filing, ownership_document, TEXT_XML).await?;
+        return Ok(vec![(path, filing.clone())]);
+    }
 
-    let filing_dir = format!("{}/{}/{}", EDGAR_FILINGS_DIR, cik, accession_number);
-    fs::create_dir_all(&filing_dir)?;
+    if filing.is_holdings_report() {
+        // The cover page (`primary_doc.xml`) carries the manager name and report period, the
+        // positions are in an information table whose name varies between filers, so it is picked
+        // by its document type in the filing index
+        let cover_page = filing.raw_primary_document();
+        fetch_filing_document(client, cik, filing, cover_page, TEXT_XML).await?;
+
+        let documents = fetch_filing_documents(client, cik, filing).await?;
+        let information_table = documents
+            .iter()
+            .find(|document| {
+                document
+                    .document_type
+                    .as_deref()
+                    .is_some_and(|t| t.eq_ignore_ascii_case("INFORMATION TABLE"))
+                    && document.name.to_lowercase().ends_with(".xml")
+            })
+            .map(|document| document.name.as_str())
+            .ok_or_else(|| {
+                anyhow!(
+                    "No information table in 13F filing {}",
+                    filing.accession_number
+                )
+            })?;
+        let path = fetch_filing_document(client, cik, filing, information_table, TEXT_XML).await?;
+        return Ok(vec![(path, filing.clone())]);
+    }
 
-    let document_path = format!("{}/{}", filing_dir, xbrl_document);
-    let document_url = format!("{}/{}/{}/{}", base, cik, accession_number, xbrl_document);
+    if filing.report_type.parser() == FormParser::Unsupported
+        || (filing.is_text_report() && filing.primary_document.contains('/'))
+    {
+        // Structured XML forms, their primary document is an XSL rendering
+        log::info!(
+            "Skipping {} {}: no parser for this form",
+            filing.report_type,
+            filing.accession_number
+        );
+        return Ok(Vec::new());
+    }
 
-    log::debug!("EDGAR Document Request URL: {}", document_url);
-    log::info!("Fetching: {}", document_url);
+    if filing.is_text_report() {
+        let path =
+            fetch_filing_document(client, cik, filing, &filing.primary_document, TEXT_HTML).await?;
+        return Ok(vec![(path, filing.clone())]);
+    }
 
-    let document_url_obj = Url::parse(&document_url)?;
-    let local_path = Path::new(&document_path);
+    let xbrl_document = filing.primary_document.replace(".htm", "_htm.xml");
+    let xbrl_result = fetch_filing_document(client, cik, filing, &xbrl_document, TEXT_XML).await;
+
+    match xbrl_result {
+        Ok(path) => documents.push((path, filing.clone())),
+        Err(e) if filing.is_inline_xbrl => log::info!(
+            "No XBRL instance for {} {}, reading the inline XBRL of the primary document: {}",
+            filing.report_type,
+            filing.accession_number,
+            e
+        ),
+        Err(e) => log::warn!(
+            "No XBRL instance for {} {}, continuing with the primary document: {}",
+            filing.report_type,
+            filing.accession_number,
+            e
+        ),
+    }
 
-    fetch_and_save(
-        client,
-        &document_url_obj,
-        local_path,
-        USER_AGENT,
-        TEXT_XML,
-        crate::edgar::rate_limiter(),
-    )
-    .await?;
+    if filing.is_xbrl || filing.is_inline_xbrl {
+        if let Err(e) = fetch_linkbases(client, cik, filing).await {
+            log::warn!(
+                "No linkbases for {} {}, facts are rendered without labels or statements: {}",
+                filing.report_type,
+                filing.accession_number,
+                e
+            );
+        }
+    }
 
-    log::info!("Saved filing document to {}", document_path);
-    Ok((document_path, filing.clone()))
+    let primary_path =
+        fetch_filing_document(client, cik, filing, &filing.primary_document, TEXT_HTML).await;
+    match primary_path {
+        Ok(path) => documents.push((path, filing.clone())),
+        Err(e) if !documents.is_empty() => log::warn!(
+            "Failed to fetch primary document {} for {}: {}",
+            filing.primary_document,
+            filing.accession_number,
+            e
+        ),
+        Err(e) => return Err(e),
+    }
+
+    if !exhibit_selection
+        .exhibit_types(&filing.report_type)
+        .is_empty()
+    {
+        match fetch_selected_exhibits(client, cik, filing, exhibit_selection).await {
+            Ok(paths) => documents.extend(paths.into_iter().map(|path| (path, filing.clone()))),
+            Err(e) => log::warn!(
+                "Failed to fetch the document index of {}: {}",
+                filing.accession_number,
+                e
+            ),
+        }
+    }
+
+    Ok(documents)
 }
 
 pub async fn fetch_matching_filings(
-    client: &Client,
+    client: &EdgarClient,
     query: &Query,
     multi_progress: Option<&Arc<MultiProgress>>,
-) -> Result<HashMap<String, Filing>> {
-    let cik = get_cik_for_query(query).await?;
-
-    // Fetch filings using the CIK and ADR status from query
-    let filings = get_company_filings(client, &cik, None, query.is_adr).await?;
-
-    let progress_tracker = multi_progress.map(|mp| {
-        Arc::new(ProgressTracker::new(
-            Some(mp),
-            &format!("Filing list for {}", query.tickers[0]),
-        ))
-    });
-    if let Some(tracker) = progress_tracker.as_ref() {
-        tracker.update_message("Retrieved filing list");
-    }
-    let matching_filings = process_filing_entries(&filings.filings.recent, query)?;
-
-    crate::utils::dirs::ensure_edgar_dirs()?;
+) -> Result<FilingsByCik> {
+    let ciks = get_ciks_for_query(client, query).await;
 
     let mut handles = Vec::new();
-    let (tx, mut rx) = tokio::sync::mpsc::channel::<Result<(String, Filing), anyhow::Error>>(100);
-
-    // Launch tasks concurrently
-    for filing in matching_filings {
-        let tx = tx.clone();
-        let client = client.clone();
-        let cik = cik.clone();
+    let (tx, mut rx) =
+        tokio::sync::mpsc::channel::<Result<(String, String, Filing), anyhow::Error>>(100);
+
+    for (ticker, cik) in ciks {
+        // Fetch filings using the CIK and ADR status from query
+        let filings = get_company_filings(client, &cik, None, query.is_adr).await?;
+
+        let progress_tracker = multi_progress.map(|mp| {
+            Arc::new(ProgressTracker::new(
+                Some(mp),
+                &format!("Filing list for {}", ticker),
+            ))
+        });
         if let Some(tracker) = progress_tracker.as_ref() {
-            tracker.start_progress(
-                100,
-                &format!("Filing {} {}", filing.report_type, filing.accession_number),
-            );
+            tracker.update_message("Retrieved filing list");
         }
+        let matching_filings = process_filing_entries(&filings.filings.recent, query)?;
+        log::debug!(
+            "Found {} matching filings for {} (CIK {})",
+            matching_filings.len(),
+            ticker,
+            cik
+        );
 
-        let handle = tokio::spawn(async move {
-            let result = fetch_and_process_filing(&client, &cik, &filing, None).await;
-            if let Ok(ref r) = result {
-                tx.send(Ok(r.clone())).await.expect("Channel send failed");
+        // Launch tasks concurrently
+        for mut filing in matching_filings {
+            filing.entity_name = Some(filings.name.clone());
+            let tx = tx.clone();
+            let client = client.clone();
+            let cik = cik.clone();
+            let exhibit_selection = query.exhibits.clone();
+            if let Some(tracker) = progress_tracker.as_ref() {
+                tracker.start_progress(
+                    100,
+                    &format!("Filing {} {}", filing.report_type, filing.accession_number),
+                );
             }
-            result
-        });
-        handles.push(handle);
+
+            let handle = tokio::spawn(async move {
+                let result =
+                    fetch_and_process_filing(&client, &cik, &filing, &exhibit_selection, None)
+                        .await;
+                if let Ok(ref documents) = result {
+                    for (path, filing) in documents {
+                        tx.send(Ok((cik.clone(), path.clone(), filing.clone())))
+                            .await
+                            .expect("Channel send failed");
+                    }
+                }
+                result
+            });
+            handles.push(handle);
+        }
     }
 
     drop(tx);
 
-    // Collect results
-    let mut filing_map = HashMap::new();
+    // Collect results, grouped by company
+    let mut filing_map: FilingsByCik = HashMap::new();
     while let Some(result) = rx.recv().await {
         match result {
-            Ok((path, filing)) => {
-                filing_map.insert(path, filing);
+            Ok((cik, path, filing)) => {
+                filing_map.entry(cik).or_default().insert(path, filing);
             }
             Err(e) => log::error!("Error processing filing: {}", e),
         }
@@ -594,45 +1067,129 @@ pub async fn fetch_matching_filings(
     Ok(filing_map)
 }
 
-pub async fn extract_complete_submission_filing(
+/// Process a downloaded filing document, dispatching on its kind: exhibits and primary HTML
+/// documents are converted to markdown text, everything else is parsed as an XBRL instance
+pub async fn extract_filing_document(
+    client: &EdgarClient,
     filepath: &str,
-    report_type: ReportType,
+    filing: &Filing,
     store: Arc<Store>,
+    pool: &Pool<Postgres>,
     progress_tracker: Option<Arc<ProgressTracker>>,
 ) -> Result<()> {
-    // Extract CIK and accession number from filepath
+    let is_html = is_html_document(filepath);
+
+    let exhibit = exhibits::document_for_path(Path::new(filepath))
+        .and_then(|document| document.document_type)
+        .filter(|document_type| document_type.starts_with("EX-"));
+
+    if filing.is_ownership_report() {
+        extract_ownership_document(client, filepath, filing, store, progress_tracker).await
+    } else if filing.is_holdings_report() {
+        extract_holdings_document(client, filepath, filing, store, pool, progress_tracker).await
+    } else if let Some(exhibit_type) = exhibit {
+        extract_exhibit_document(
+            client,
+            filepath,
+            &exhibit_type,
+            filing,
+            store,
+            progress_tracker,
+        )
+        .await
+    } else if is_html || filing.is_text_report() {
+        if is_inline_xbrl_source(filepath, filing) {
+            let result = extract_complete_submission_filing(
+                client,
+                filepath,
+                filing,
+                store.clone(),
+                progress_tracker.clone(),
+            )
+            .await;
+            if let Err(e) = result {
+                log::warn!(
+                    "Failed to extract inline XBRL facts from {}: {}",
+                    filepath,
+                    e
+                );
+            }
+        }
+        extract_primary_document(client, filepath, filing, store, progress_tracker).await
+    } else {
+        extract_complete_submission_filing(client, filepath, filing, store, progress_tracker).await
+    }
+}
+
+fn is_html_document(filepath: &str) -> bool {
+    Path::new(filepath)
+        .extension()
+        .and_then(|ext| ext.to_str())
+        .is_some_and(|ext| ext.eq_ignore_ascii_case("htm") || ext.eq_ignore_ascii_case("html"))
+}
+
+/// Inline XBRL filings carry their facts in the primary document. They are read from there
+/// when EDGAR has no extracted `_htm.xml` instance next to it.
+fn is_inline_xbrl_source(filepath: &str, filing: &Filing) -> bool {
     let path = Path::new(filepath);
-    let parts: Vec<&str> = path
+    let primary_document = Path::new(&filing.primary_document).file_name();
+    let instance = filing.primary_document.replace(".htm", "_htm.xml");
+    filing.is_inline_xbrl
+        && path.file_name() == primary_document
+        && Path::new(&instance)
+            .file_name()
+            .is_some_and(|instance| !path.with_file_name(instance).exists())
+}
+
+/// Extract the CIK and accession number from a `{filings_dir}/{cik}/{accession}/{doc}` path
+fn filing_path_parts(filepath: &str) -> Result<(String, String)> {
+    let parent = Path::new(filepath)
         .parent()
-        .unwrap()
-        .to_str()
-        .unwrap()
-        .split('/')
-        .collect();
-    let cik = parts[parts.len() - 2];
-    let accession_number = parts[parts.len() - 1];
+        .and_then(|p| p.to_str())
+        .ok_or_else(|| anyhow!("Invalid filing path: {}", filepath))?;
+    let parts: Vec<&str> = parent.split('/').collect();
+    if parts.len() < 2 {
+        return Err(anyhow!("Invalid filing path: {}", filepath));
+    }
+    Ok((
+        parts[parts.len() - 2].to_string(),
+        parts[parts.len() - 1].to_string(),
+    ))
+}
 
-    // Check if already processed and stored
-    let markdown_dir = format!("data/edgar/parsed/{}/{}", cik, accession_number);
-    let markdown_path = format!("{}/filing.md", markdown_dir);
+/// Documents of a filing already in the vector store. Filings with several text documents
+/// (primary document, exhibits) are told apart by `filepath`.
+async fn find_indexed_documents(
+    store: &Store,
+    doc_type: &str,
+    cik: &str,
+    accession_number: &str,
+    filepath: Option<&str>,
+) -> Vec<Document> {
+    let mut filters = vec![
+        Eq(
+            JsonField(vec!["doc_type".to_string()]),
+            RawJson(Value::String(doc_type.to_string())),
+        ),
+        Eq(
+            JsonField(vec!["accession_number".to_string()]),
+            LitStr(accession_number.to_string()),
+        ),
+        Eq(JsonField(vec!["cik".to_string()]), LitStr(cik.to_string())),
+    ];
+    if let Some(filepath) = filepath {
+        filters.push(Eq(
+            JsonField(vec!["filepath".to_string()]),
+            LitStr(filepath.to_string()),
+        ));
+    }
 
-    // First check vector store
-    let existing_docs = match store
+    match store
         .similarity_search(
             "",
             1,
             &langchain_rust::vectorstore::VecStoreOptions {
-                filters: Some(And(vec![
-                    Eq(
-                        JsonField(vec!["doc_type".to_string()]),
-                        RawJson(Value::String("edgar_filing".to_string())),
-                    ),
-                    Eq(
-                        JsonField(vec!["accession_number".to_string()]),
-                        LitStr(accession_number.to_string()),
-                    ),
-                    Eq(JsonField(vec!["cik".to_string()]), LitStr(cik.to_string())),
-                ])),
+                filters: Some(And(filters)),
                 ..Default::default()
             },
         )
@@ -643,7 +1200,137 @@ pub async fn extract_complete_submission_filing(
             log::warn!("Failed to check vector store, will reprocess: {}", e);
             Vec::new()
         }
-    };
+    }
+}
+
+fn read_decoded(filepath: &str) -> Result<String> {
+    log::debug!("Reading file: {}", filepath);
+    let raw_text = fs::read(filepath)?;
+    let charenc = detect(&raw_text).0;
+
+    log::debug!("Detected character encoding: {}", charenc);
+    let mut reader = DecodeReaderBytesBuilder::new()
+        .encoding(Encoding::for_label(charenc.as_bytes()))
+        .build(BufReader::new(File::open(filepath)?));
+
+    let mut decoded = String::new();
+    reader.read_to_string(&mut decoded)?;
+    Ok(decoded)
+}
+
+fn filing_metadata(
+    doc_type: &str,
+    filepath: &str,
+    filing: &Filing,
+    cik: &str,
+    accession_number: &str,
+    symbol: Option<String>,
+) -> HashMap<String, Value> {
+    let items = filing.form_8k_items();
+    let mut metadata = HashMap::new();
+    metadata.insert("doc_type".to_string(), Value::String(doc_type.to_string()));
+    metadata.insert("filepath".to_string(), Value::String(filepath.to_string()));
+    metadata.insert(
+        "report_type".to_string(),
+        Value::String(filing.report_type.to_string()),
+    );
+    metadata.insert("cik".to_string(), Value::String(cik.to_string()));
+    metadata.insert(
+        "accession_number".to_string(),
+        Value::String(accession_number.to_string()),
+    );
+    if let Some(symbol) = symbol {
+        metadata.insert("symbol".to_string(), Value::String(symbol));
+    }
+    if let Some(entity_name) = &filing.entity_name {
+        metadata.insert(
+            "entity_name".to_string(),
+            Value::String(entity_name.clone()),
+        );
+    }
+    if !items.is_empty() {
+        metadata.insert(
+            "items".to_string(),
+            Value::String(items.iter().map(|item| item.code()).join(",")),
+        );
+    }
+    if let Some(amends) = &filing.amends {
+        metadata.insert("amends".to_string(), Value::String(amends.replace('-', "")));
+    }
+    metadata.insert(
+        "chunk_index".to_string(),
+        Value::Number(serde_json::Number::from(0)),
+    );
+    metadata.insert(
+        "total_chunks".to_string(),
+        Value::Number(serde_json::Number::from(1)),
+    );
+    metadata
+}
+
+/// Directory of the artifacts parsed from the documents of a filing
+fn parsed_filing_dir(client: &EdgarClient, cik: &str, accession_number: &str) -> String {
+    format!(
+        "{}/{}/{}",
+        client.parsed_dir().display(),
+        cik,
+        accession_number
+    )
+}
+
+/// Ticker of a filer for the `symbol` metadata. Delisted issuers, private debt issuers, funds
+/// and old filers have none; their documents are found by CIK and entity name instead.
+async fn symbol_for_cik(client: &EdgarClient, cik: &str) -> Option<String> {
+    match crate::edgar::tickers::get_ticker_for_cik(client, cik).await {
+        Ok(symbol) => Some(symbol),
+        Err(e) => {
+            log::debug!("Indexing CIK {} without a ticker: {}", cik, e);
+            None
+        }
+    }
+}
+
+/// Linkbase saved next to an XBRL document whose file name ends with `suffix`, if the filing
+/// has one that parses
+fn load_linkbase<T>(
+    filepath: &str,
+    suffix: &str,
+    parse: impl FnOnce(&str) -> Result<T, super::xbrl::XbrlError>,
+) -> Option<T> {
+    let dir = Path::new(filepath).parent()?;
+    let path = fs::read_dir(dir)
+        .ok()?
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .find(|path| path.to_string_lossy().ends_with(suffix))?;
+    match read_decoded(&path.to_string_lossy()).map(|xml| parse(&xml)) {
+        Ok(Ok(linkbase)) => Some(linkbase),
+        Ok(Err(e)) => {
+            log::warn!("Failed to parse linkbase {}: {}", path.display(), e);
+            None
+        }
+        Err(e) => {
+            log::warn!("Failed to read linkbase {}: {}", path.display(), e);
+            None
+        }
+    }
+}
+
+pub async fn extract_complete_submission_filing(
+    client: &EdgarClient,
+    filepath: &str,
+    filing: &Filing,
+    store: Arc<Store>,
+    progress_tracker: Option<Arc<ProgressTracker>>,
+) -> Result<()> {
+    let (cik, accession_number) = filing_path_parts(filepath)?;
+
+    // Check if already processed and stored
+    let markdown_dir = parsed_filing_dir(client, &cik, &accession_number);
+    let markdown_path = format!("{}/filing.md", markdown_dir);
+
+    // First check vector store
+    let existing_docs =
+        find_indexed_documents(&store, "edgar_filing", &cik, &accession_number, None).await;
 
     if !existing_docs.is_empty() && Path::new(&markdown_path).exists() {
         log::info!(
@@ -661,26 +1348,38 @@ pub async fn extract_complete_submission_filing(
     log::info!("Parsing new XBRL file: {}/{}", cik, accession_number);
 
     // Read and decode the file content
-    log::debug!("Reading file: {}", filepath);
-    let raw_text = fs::read(filepath)?;
-    let charenc = detect(&raw_text).0;
-
-    log::debug!("Detected character encoding: {}", charenc);
-    let mut reader = DecodeReaderBytesBuilder::new()
-        .encoding(Encoding::for_label(charenc.as_bytes()))
-        .build(BufReader::new(File::open(filepath)?));
+    let raw_text_string = read_decoded(filepath)?;
 
-    let mut raw_text_string = String::new();
-    reader.read_to_string(&mut raw_text_string)?;
+    // Parse the XBRL instance, or the inline XBRL of a primary document
+    let mut facts = if is_html_document(filepath) {
+        super::ixbrl::parse_ixbrl_to_facts(&raw_text_string)
+    } else {
+        super::xbrl::parse_xml_to_facts(raw_text_string)
+    }
+    .map_err(|e| {
+        anyhow!(
+            "Failed to parse XBRL filing {}/{}: {}",
+            cik,
+            accession_number,
+            e
+        )
+    })?;
 
-    // Parse XBRL using the xml module
-    let facts = super::xbrl::parse_xml_to_facts(raw_text_string);
+    let labels = load_linkbase(filepath, LABEL_LINKBASE_SUFFIX, Labels::parse);
+    if let Some(labels) = &labels {
+        labels.apply(&mut facts);
+    }
+    let statements = load_linkbase(filepath, PRESENTATION_LINKBASE_SUFFIX, Presentation::parse)
+        .map(|presentation| presentation.statements(labels.as_ref()));
+    let calculation = load_linkbase(filepath, CALCULATION_LINKBASE_SUFFIX, Calculation::parse);
 
     // Generate markdown
     let xbrl_filing = super::xbrl::XBRLFiling {
         raw_facts: Some(facts.clone()),
         fact_table: None,
         dimensions: None,
+        statements,
+        calculation,
     };
 
     let markdown_content = xbrl_filing.to_markdown();
@@ -688,24 +1387,8 @@ pub async fn extract_complete_submission_filing(
         log::debug!("Generated markdown content:\n{}", markdown_content);
     }
 
-    // Extract CIK and accession number from filepath
-    let path = Path::new(filepath);
-    let parts: Vec<&str> = path
-        .parent()
-        .unwrap()
-        .to_str()
-        .unwrap()
-        .split('/')
-        .collect();
-    let cik = parts[parts.len() - 2];
-    let accession_number = parts[parts.len() - 1];
-
-    // Create markdown directory
-    let markdown_dir = format!("data/edgar/parsed/{}/{}", cik, accession_number);
+    // Create markdown directory and save markdown file
     fs::create_dir_all(&markdown_dir)?;
-
-    // Save markdown file
-    let markdown_path = format!("{}/filing.md", markdown_dir);
     fs::write(&markdown_path, &markdown_content)?;
     log::info!("Saved parsed filing to: {}", markdown_path);
 
@@ -724,32 +1407,15 @@ pub async fn extract_complete_submission_filing(
         accession_number
     );
 
-    let symbol = crate::edgar::tickers::get_ticker_for_cik(cik).await?;
+    let symbol = symbol_for_cik(client, &cik).await;
 
-    // Create metadata directly as HashMap
-    let mut metadata = HashMap::new();
-    metadata.insert(
-        "doc_type".to_string(),
-        Value::String("edgar_filing".to_string()),
-    );
-    metadata.insert("filepath".to_string(), Value::String(filepath.to_string()));
-    metadata.insert(
-        "report_type".to_string(),
-        Value::String(report_type.to_string()),
-    );
-    metadata.insert("cik".to_string(), Value::String(cik.to_string()));
-    metadata.insert(
-        "accession_number".to_string(),
-        Value::String(accession_number.to_string()),
-    );
-    metadata.insert("symbol".to_string(), Value::String(symbol));
-    metadata.insert(
-        "chunk_index".to_string(),
-        Value::Number(serde_json::Number::from(0)),
-    );
-    metadata.insert(
-        "total_chunks".to_string(),
-        Value::Number(serde_json::Number::from(1)),
+    let metadata = filing_metadata(
+        "edgar_filing",
+        filepath,
+        filing,
+        &cik,
+        &accession_number,
+        symbol,
     );
 
     // Save metadata alongside markdown
@@ -765,3 +1431,336 @@ pub async fn extract_complete_submission_filing(
     log::debug!("Filing processed and converted to markdown");
     Ok(())
 }
+
+/// Convert the primary HTML document of a filing to markdown, save it next to the XBRL
+/// markdown as `primary.md` and index it with the `edgar_filing_text` doc type
+pub async fn extract_primary_document(
+    client: &EdgarClient,
+    filepath: &str,
+    filing: &Filing,
+    store: Arc<Store>,
+    progress_tracker: Option<Arc<ProgressTracker>>,
+) -> Result<()> {
+    let (cik, accession_number) = filing_path_parts(filepath)?;
+
+    let markdown_dir = parsed_filing_dir(client, &cik, &accession_number);
+    let markdown_path = format!("{}/primary.md", markdown_dir);
+
+    let existing_docs = find_indexed_documents(
+        &store,
+        "edgar_filing_text",
+        &cik,
+        &accession_number,
+        Some(filepath),
+    )
+    .await;
+
+    if !existing_docs.is_empty() && Path::new(&markdown_path).exists() {
+        log::info!(
+            "Primary document already processed and stored: {}/{}",
+            cik,
+            accession_number
+        );
+        return Ok(());
+    }
+
+    if let Some(ref tracker) = progress_tracker {
+        tracker.update_message("Converting primary document...");
+        tracker.update_progress(33);
+    }
+    log::info!("Parsing primary document: {}/{}", cik, accession_number);
+
+    let html = read_decoded(filepath)?;
+    let markdown_content = super::html::html_to_markdown(&html);
+
+    fs::create_dir_all(&markdown_dir)?;
+    fs::write(&markdown_path, &markdown_content)?;
+    log::info!("Saved primary document text to: {}", markdown_path);
+
+    if !existing_docs.is_empty() {
+        log::info!(
+            "Primary document already exists in vector store: {}/{}",
+            cik,
+            accession_number
+        );
+        return Ok(());
+    }
+
+    let symbol = symbol_for_cik(client, &cik).await;
+
+    let metadata = filing_metadata(
+        "edgar_filing_text",
+        filepath,
+        filing,
+        &cik,
+        &accession_number,
+        symbol,
+    );
+
+    let metadata_path = format!("{}/primary.json", markdown_dir);
+    fs::write(&metadata_path, serde_json::to_string_pretty(&metadata)?)?;
+
+    if sections::supports_sections(&filing.report_type) {
+        let sections = sections::split_sections(&markdown_content, &filing.report_type)
+            .into_iter()
+            .map(|section| {
+                let mut section_metadata = HashMap::new();
+                if let Some(item) = section.item {
+                    section_metadata.insert("item".to_string(), Value::String(item));
+                }
+                section_metadata.insert(
+                    "section_title".to_string(),
+                    Value::String(section.section_title),
+                );
+                (section.content, section_metadata)
+            })
+            .collect::<Vec<_>>();
+        log::info!(
+            "Split {} into {} sections",
+            accession_number,
+            sections.len()
+        );
+        crate::vectorstore::store_sectioned_document(sections, metadata, store.as_ref()).await?;
+    } else {
+        crate::vectorstore::store_document(markdown_content, metadata, store.as_ref()).await?;
+    }
+
+    log::info!("Added primary document to vector store: {}", filepath);
+    Ok(())
+}
+
+/// Convert an exhibit (press release, subsidiary list, material contract) to markdown, save it
+/// as `{document}.md` next to the primary document text and index it with the
+/// `edgar_filing_text` doc type and its `exhibit_type`
+pub async fn extract_exhibit_document(
+    client: &EdgarClient,
+    filepath: &str,
+    exhibit_type: &str,
+    filing: &Filing,
+    store: Arc<Store>,
+    progress_tracker: Option<Arc<ProgressTracker>>,
+) -> Result<()> {
+    let (cik, accession_number) = filing_path_parts(filepath)?;
+    let document = Path::new(filepath)
+        .file_stem()
+        .and_then(|stem| stem.to_str())
+        .ok_or_else(|| anyhow!("Invalid filing path: {}", filepath))?;
+
+    let markdown_dir = parsed_filing_dir(client, &cik, &accession_number);
+    let markdown_path = format!("{}/{}.md", markdown_dir, document);
+
+    let existing_docs = find_indexed_documents(
+        &store,
+        "edgar_filing_text",
+        &cik,
+        &accession_number,
+        Some(filepath),
+    )
+    .await;
+
+    if !existing_docs.is_empty() && Path::new(&markdown_path).exists() {
+        log::info!(
+            "Exhibit {} already processed and stored: {}/{}",
+            exhibit_type,
+            cik,
+            accession_number
+        );
+        return Ok(());
+    }
+
+    if let Some(ref tracker) = progress_tracker {
+        tracker.update_message(&format!("Converting {}...", exhibit_type));
+        tracker.update_progress(33);
+    }
+    log::info!(
+        "Parsing exhibit {}: {}/{}",
+        exhibit_type,
+        cik,
+        accession_number
+    );
+
+    let markdown_content = super::html::html_to_markdown(&read_decoded(filepath)?);
+
+    fs::create_dir_all(&markdown_dir)?;
+    fs::write(&markdown_path, &markdown_content)?;
+    log::info!("Saved exhibit text to: {}", markdown_path);
+
+    if !existing_docs.is_empty() {
+        return Ok(());
+    }
+
+    let symbol = symbol_for_cik(client, &cik).await;
+
+    let mut metadata = filing_metadata(
+        "edgar_filing_text",
+        filepath,
+        filing,
+        &cik,
+        &accession_number,
+        symbol,
+    );
+    metadata.insert(
+        "exhibit_type".to_string(),
+        Value::String(exhibit_type.to_string()),
+    );
+
+    let metadata_path = format!("{}/{}.json", markdown_dir, document);
+    fs::write(&metadata_path, serde_json::to_string_pretty(&metadata)?)?;
+
+    crate::vectorstore::store_document(markdown_content, metadata, store.as_ref()).await?;
+
+    log::info!(
+        "Added exhibit {} to vector store: {}",
+        exhibit_type,
+        filepath
+    );
+    Ok(())
+}
+
+/// Parse the ownership XML of a Form 4/5, save the typed document as `ownership.json` for the
+/// insider activity summary and index its markdown rendering with the `edgar_filing` doc type
+pub async fn extract_ownership_document(
+    client: &EdgarClient,
+    filepath: &str,
+    filing: &Filing,
+    store: Arc<Store>,
+    progress_tracker: Option<Arc<ProgressTracker>>,
+) -> Result<()> {
+    let (cik, accession_number) = filing_path_parts(filepath)?;
+
+    let parsed_dir = parsed_filing_dir(client, &cik, &accession_number);
+    let ownership_path = format!("{}/{}", parsed_dir, super::ownership::OWNERSHIP_JSON);
+
+    let existing_docs =
+        find_indexed_documents(&store, "edgar_filing", &cik, &accession_number, None).await;
+
+    if !existing_docs.is_empty() && Path::new(&ownership_path).exists() {
+        log::info!(
+            "Ownership document already processed and stored: {}/{}",
+            cik,
+            accession_number
+        );
+        return Ok(());
+    }
+
+    if let Some(ref tracker) = progress_tracker {
+        tracker.update_message("Parsing ownership document...");
+        tracker.update_progress(33);
+    }
+
+    let mut document = super::ownership::parse_ownership_xml(&read_decoded(filepath)?)?;
+    document.filing_date = Some(filing.filing_date);
+    let markdown_content = document.to_markdown();
+
+    fs::create_dir_all(&parsed_dir)?;
+    fs::write(&ownership_path, serde_json::to_string_pretty(&document)?)?;
+    fs::write(format!("{}/ownership.md", parsed_dir), &markdown_content)?;
+    log::info!("Saved ownership document to: {}", ownership_path);
+
+    if !existing_docs.is_empty() {
+        return Ok(());
+    }
+
+    let symbol = symbol_for_cik(client, &cik).await;
+    let metadata = filing_metadata(
+        "edgar_filing",
+        filepath,
+        filing,
+        &cik,
+        &accession_number,
+        symbol,
+    );
+
+    crate::vectorstore::store_document(markdown_content, metadata, store.as_ref()).await?;
+
+    log::info!("Added ownership document to vector store: {}", filepath);
+    Ok(())
+}
+
+/// Parse the information table of a 13F-HR, store its holdings in Postgres for the aggregate
+/// views and index a summary of the largest positions with the `edgar_filing` doc type
+pub async fn extract_holdings_document(
+    client: &EdgarClient,
+    filepath: &str,
+    filing: &Filing,
+    store: Arc<Store>,
+    pool: &Pool<Postgres>,
+    progress_tracker: Option<Arc<ProgressTracker>>,
+) -> Result<()> {
+    let (cik, accession_number) = filing_path_parts(filepath)?;
+
+    let parsed_dir = parsed_filing_dir(client, &cik, &accession_number);
+    let holdings_path = format!("{}/{}", parsed_dir, holdings::HOLDINGS_JSON);
+
+    let existing_docs =
+        find_indexed_documents(&store, "edgar_filing", &cik, &accession_number, None).await;
+
+    if !existing_docs.is_empty() && Path::new(&holdings_path).exists() {
+        log::info!(
+            "Holdings already processed and stored: {}/{}",
+            cik,
+            accession_number
+        );
+        return Ok(());
+    }
+
+    if let Some(ref tracker) = progress_tracker {
+        tracker.update_message("Parsing information table...");
+        tracker.update_progress(33);
+    }
+
+    let cover_page_path = Path::new(filepath).with_file_name(filing.raw_primary_document());
+    let cover_page = holdings::parse_cover_page(&read_decoded(
+        cover_page_path
+            .to_str()
+            .ok_or_else(|| anyhow!("Invalid cover page path: {:?}", cover_page_path))?,
+    )?)?;
+
+    let multiplier = holdings::value_multiplier(filing.filing_date);
+    let mut positions = holdings::parse_information_table(&read_decoded(filepath)?)?;
+    for position in positions.iter_mut() {
+        position.value *= multiplier;
+    }
+
+    let report = holdings::HoldingsReport {
+        filer_cik: cik.clone(),
+        filer_name: cover_page.filer_name,
+        accession_number: filing.accession_number.clone(),
+        period_of_report: cover_page.period_of_report,
+        filing_date: filing.filing_date,
+        amendment_type: cover_page.amendment_type,
+        holdings: positions,
+    };
+
+    crate::db::insert_holdings_report(pool, &report).await?;
+    log::info!(
+        "Stored {} holdings of {} for {}",
+        report.holdings.len(),
+        report.filer_name,
+        report.period_of_report
+    );
+
+    let markdown_content = report.to_markdown(HOLDINGS_SUMMARY_POSITIONS);
+    fs::create_dir_all(&parsed_dir)?;
+    fs::write(&holdings_path, serde_json::to_string_pretty(&report)?)?;
+    fs::write(format!("{}/holdings.md", parsed_dir), &markdown_content)?;
+
+    if !existing_docs.is_empty() {
+        return Ok(());
+    }
+
+    let symbol = symbol_for_cik(client, &cik).await;
+    let metadata = filing_metadata(
+        "edgar_filing",
+        filepath,
+        filing,
+        &cik,
+        &accession_number,
+        symbol,
+    );
+
+    crate::vectorstore::store_document(markdown_content, metadata, store.as_ref()).await?;
+
+    log::info!("Added holdings summary to vector store: {}", filepath);
+    Ok(())
+}
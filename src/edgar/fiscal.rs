@@ -0,0 +1,406 @@
+use anyhow::Result;
+use chrono::{Datelike, Days, Months, NaiveDate};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+use super::client::EdgarClient;
+use super::filing::{self, CompanyFilings};
+use super::report::ReportType;
+use super::tickers;
+
+/// How far the report date of a filing may be from the nominal end of its period. Companies
+/// with 52-53 week years end them on a weekday near the nominal date (Apple: last Saturday of
+/// September, reported as fiscal year end `0928`).
+const PERIOD_END_TOLERANCE_DAYS: i64 = 10;
+
+/// A quarter of a fiscal year. Fiscal years are named by the calendar year they end in, so
+/// Microsoft's FY2025 runs from July 2024 to June 2025.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub struct FiscalQuarter {
+    pub year: i32,
+    pub quarter: u32,
+}
+
+impl FiscalQuarter {
+    pub fn new(year: i32, quarter: u32) -> Self {
+        FiscalQuarter { year, quarter }
+    }
+
+    pub fn previous(self) -> Self {
+        match self.quarter {
+            1 => FiscalQuarter::new(self.year - 1, 4),
+            q => FiscalQuarter::new(self.year, q - 1),
+        }
+    }
+
+    pub fn next(self) -> Self {
+        match self.quarter {
+            4 => FiscalQuarter::new(self.year + 1, 1),
+            q => FiscalQuarter::new(self.year, q + 1),
+        }
+    }
+}
+
+impl fmt::Display for FiscalQuarter {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Q{} FY{}", self.quarter, self.year)
+    }
+}
+
+/// A period named in fiscal terms by a question, `{"year": 2024, "quarter": 3}`,
+/// `{"year": 2024}` or `"latest_quarter"`. Quarters outside 1 to 4 are rejected.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(untagged, try_from = "RawFiscalPeriod")]
+pub enum FiscalPeriod {
+    Latest(LatestPeriod),
+    Quarter { year: i32, quarter: u32 },
+    Year { year: i32 },
+}
+
+/// A [`FiscalPeriod`] as written in a question, before its quarter is checked
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum RawFiscalPeriod {
+    Latest(LatestPeriod),
+    Quarter { year: i32, quarter: u32 },
+    Year { year: i32 },
+}
+
+impl TryFrom<RawFiscalPeriod> for FiscalPeriod {
+    type Error = String;
+
+    fn try_from(period: RawFiscalPeriod) -> Result<Self, Self::Error> {
+        match period {
+            RawFiscalPeriod::Latest(latest) => Ok(FiscalPeriod::Latest(latest)),
+            RawFiscalPeriod::Quarter { year, quarter } if (1..=4).contains(&quarter) => {
+                Ok(FiscalPeriod::Quarter { year, quarter })
+            }
+            RawFiscalPeriod::Quarter { quarter, .. } => Err(format!(
+                "Invalid fiscal quarter {}, expected 1 to 4",
+                quarter
+            )),
+            RawFiscalPeriod::Year { year } => Ok(FiscalPeriod::Year { year }),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LatestPeriod {
+    LatestQuarter,
+    LatestYear,
+}
+
+/// Fiscal years and quarters of a company, from the `fiscalYearEnd` (`MMDD`) of its
+/// submissions and the report dates of its past 10-K and 10-Q filings, which pin down the
+/// actual period ends of 52-53 week years
+#[derive(Debug, Clone, PartialEq)]
+pub struct FiscalCalendar {
+    year_end_month: u32,
+    year_end_day: u32,
+    year_ends: Vec<NaiveDate>,
+    quarter_ends: Vec<NaiveDate>,
+}
+
+fn last_day_of_month(year: i32, month: u32) -> NaiveDate {
+    let first = NaiveDate::from_ymd_opt(year, month, 1).unwrap();
+    first + Months::new(1) - Days::new(1)
+}
+
+/// The date, or the last day of the month for days it doesn't have (`0229`, `0931`)
+fn clamped_date(year: i32, month: u32, day: u32) -> NaiveDate {
+    NaiveDate::from_ymd_opt(year, month, day).unwrap_or_else(|| last_day_of_month(year, month))
+}
+
+/// Parse `MMDD` into month and day
+pub fn parse_month_day(mmdd: &str) -> Option<(u32, u32)> {
+    if mmdd.len() != 4 {
+        return None;
+    }
+    let month = mmdd[..2].parse().ok().filter(|m| (1..=12).contains(m))?;
+    let day = mmdd[2..].parse().ok().filter(|d| (1..=31).contains(d))?;
+    Some((month, day))
+}
+
+impl Default for FiscalCalendar {
+    fn default() -> Self {
+        FiscalCalendar::calendar_year()
+    }
+}
+
+impl FiscalCalendar {
+    pub fn new(year_end_month: u32, year_end_day: u32) -> Self {
+        FiscalCalendar {
+            year_end_month,
+            year_end_day,
+            year_ends: Vec::new(),
+            quarter_ends: Vec::new(),
+        }
+    }
+
+    /// Fiscal years ending on December 31st, used when a company's calendar is unknown
+    pub fn calendar_year() -> Self {
+        FiscalCalendar::new(12, 31)
+    }
+
+    /// Actual ends of past fiscal years and quarters
+    pub fn with_period_ends(
+        mut self,
+        year_ends: Vec<NaiveDate>,
+        quarter_ends: Vec<NaiveDate>,
+    ) -> Self {
+        self.year_ends = year_ends;
+        self.year_ends.sort();
+        self.quarter_ends = quarter_ends;
+        self.quarter_ends.sort();
+        self
+    }
+
+    /// Calendar of a company from its submissions. Without a `fiscalYearEnd` the latest annual
+    /// report date is used, and without either the calendar year.
+    pub fn from_filings(filings: &CompanyFilings) -> Self {
+        let recent = &filings.filings.recent;
+        let report_dates = |report_type: ReportType| {
+            recent
+                .report_type
+                .iter()
+                .zip(&recent.report_date)
+                .filter(|(form, _)| form.parse::<ReportType>().ok() == Some(report_type.clone()))
+                .filter_map(|(_, date)| date.as_deref())
+                .filter_map(|date| NaiveDate::parse_from_str(date, "%Y-%m-%d").ok())
+                .collect::<Vec<_>>()
+        };
+        let year_ends = report_dates(ReportType::Form10K);
+        let quarter_ends = report_dates(ReportType::Form10Q);
+
+        let calendar = filings
+            .fiscal_year_end
+            .as_deref()
+            .and_then(parse_month_day)
+            .or_else(|| {
+                year_ends
+                    .iter()
+                    .max()
+                    .map(|date| (date.month(), date.day()))
+            })
+            .map_or_else(FiscalCalendar::calendar_year, |(month, day)| {
+                FiscalCalendar::new(month, day)
+            });
+        calendar.with_period_ends(year_ends, quarter_ends)
+    }
+
+    /// The reported period end closest to `nominal`, if one is within the tolerance
+    fn actual_end(ends: &[NaiveDate], nominal: NaiveDate) -> NaiveDate {
+        ends.iter()
+            .filter(|end| (**end - nominal).num_days().abs() <= PERIOD_END_TOLERANCE_DAYS)
+            .min_by_key(|end| (**end - nominal).num_days().abs())
+            .copied()
+            .unwrap_or(nominal)
+    }
+
+    fn nominal_year_end(&self, fiscal_year: i32) -> NaiveDate {
+        clamped_date(fiscal_year, self.year_end_month, self.year_end_day)
+    }
+
+    pub fn fiscal_year_end(&self, fiscal_year: i32) -> NaiveDate {
+        FiscalCalendar::actual_end(&self.year_ends, self.nominal_year_end(fiscal_year))
+    }
+
+    pub fn fiscal_year_start(&self, fiscal_year: i32) -> NaiveDate {
+        self.fiscal_year_end(fiscal_year - 1) + Days::new(1)
+    }
+
+    /// Last day of a fiscal quarter. Quarters of years ending on a month end end on month
+    /// ends too; otherwise they end on the same day of the month as the year.
+    pub fn quarter_end(&self, quarter: FiscalQuarter) -> NaiveDate {
+        if quarter.quarter >= 4 {
+            return self.fiscal_year_end(quarter.year);
+        }
+        let previous_end = self.nominal_year_end(quarter.year - 1);
+        let months = Months::new(3 * quarter.quarter);
+        let nominal =
+            if previous_end == last_day_of_month(previous_end.year(), previous_end.month()) {
+                let date = previous_end + months;
+                last_day_of_month(date.year(), date.month())
+            } else {
+                let date = previous_end + months;
+                clamped_date(date.year(), date.month(), self.year_end_day)
+            };
+        FiscalCalendar::actual_end(&self.quarter_ends, nominal)
+    }
+
+    pub fn quarter_start(&self, quarter: FiscalQuarter) -> NaiveDate {
+        self.quarter_end(quarter.previous()) + Days::new(1)
+    }
+
+    /// The fiscal quarter a calendar date falls in
+    pub fn quarter_of(&self, date: NaiveDate) -> FiscalQuarter {
+        let mut year = date.year();
+        while date <= self.fiscal_year_end(year - 1) {
+            year -= 1;
+        }
+        while date > self.fiscal_year_end(year) {
+            year += 1;
+        }
+        (1..=4)
+            .map(|quarter| FiscalQuarter::new(year, quarter))
+            .find(|quarter| date <= self.quarter_end(*quarter))
+            .unwrap_or(FiscalQuarter::new(year, 4))
+    }
+
+    /// The latest quarter that has ended on or before `date`
+    pub fn last_completed_quarter(&self, date: NaiveDate) -> FiscalQuarter {
+        let quarter = self.quarter_of(date);
+        if self.quarter_end(quarter) == date {
+            quarter
+        } else {
+            quarter.previous()
+        }
+    }
+
+    /// Quarters ending between `start` and `end`, or the last one completed by `end` if none does
+    pub fn quarters_between(&self, start: NaiveDate, end: NaiveDate) -> Vec<FiscalQuarter> {
+        let mut quarters = Vec::new();
+        let mut quarter = self.quarter_of(start);
+        while self.quarter_end(quarter) <= end {
+            quarters.push(quarter);
+            quarter = quarter.next();
+        }
+        if quarters.is_empty() {
+            quarters.push(self.last_completed_quarter(end));
+        }
+        quarters
+    }
+
+    /// First and last day of a fiscal period, the latest ones completed by `today` for
+    /// `latest_quarter` and `latest_year`
+    pub fn period_dates(&self, period: &FiscalPeriod, today: NaiveDate) -> (NaiveDate, NaiveDate) {
+        let quarter = match period {
+            FiscalPeriod::Latest(LatestPeriod::LatestQuarter) => self.last_completed_quarter(today),
+            FiscalPeriod::Latest(LatestPeriod::LatestYear) => {
+                let year = self.last_completed_quarter(today).year;
+                let year = if self.fiscal_year_end(year) <= today {
+                    year
+                } else {
+                    year - 1
+                };
+                return (self.fiscal_year_start(year), self.fiscal_year_end(year));
+            }
+            FiscalPeriod::Quarter { year, quarter } => FiscalQuarter::new(*year, *quarter),
+            FiscalPeriod::Year { year } => {
+                return (self.fiscal_year_start(*year), self.fiscal_year_end(*year))
+            }
+        };
+        (self.quarter_start(quarter), self.quarter_end(quarter))
+    }
+}
+
+/// Fiscal calendar of a company by CIK, from its (cached) submissions
+pub async fn fiscal_calendar_for_cik(client: &EdgarClient, cik: &str) -> Result<FiscalCalendar> {
+    let filings = filing::get_company_filings(client, cik, Some(1), false).await?;
+    Ok(FiscalCalendar::from_filings(&filings))
+}
+
+/// Fiscal calendar of the company behind a ticker or CIK reference, the calendar year if it
+/// can't be determined
+pub async fn fiscal_calendar_for_ticker(client: &EdgarClient, ticker: &str) -> FiscalCalendar {
+    let calendar = match tickers::resolve_cik(client, ticker).await {
+        Ok(cik) => fiscal_calendar_for_cik(client, &cik).await,
+        Err(e) => Err(e),
+    };
+    calendar.unwrap_or_else(|e| {
+        log::warn!(
+            "No fiscal calendar for {}, using calendar quarters: {}",
+            ticker,
+            e
+        );
+        FiscalCalendar::calendar_year()
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(s: &str) -> NaiveDate {
+        NaiveDate::parse_from_str(s, "%Y-%m-%d").unwrap()
+    }
+
+    #[test]
+    fn test_fiscal_calendar() {
+        // Apple: 52-53 week years ending on the last Saturday of September
+        let apple = FiscalCalendar::new(9, 28).with_period_ends(
+            vec![date("2023-09-30"), date("2024-09-28")],
+            vec![date("2023-12-30"), date("2024-03-30"), date("2024-06-29")],
+        );
+        assert_eq!(apple.fiscal_year_end(2023), date("2023-09-30"));
+        assert_eq!(
+            apple.quarter_of(date("2023-10-01")),
+            FiscalQuarter::new(2024, 1)
+        );
+        assert_eq!(
+            apple.quarter_of(date("2023-12-30")),
+            FiscalQuarter::new(2024, 1)
+        );
+        assert_eq!(
+            apple.quarter_of(date("2023-12-31")),
+            FiscalQuarter::new(2024, 2)
+        );
+        assert_eq!(
+            apple.quarter_start(FiscalQuarter::new(2024, 2)),
+            date("2023-12-31")
+        );
+        assert_eq!(
+            apple.last_completed_quarter(date("2024-12-01")),
+            FiscalQuarter::new(2024, 4)
+        );
+
+        // Microsoft: fiscal years ending June 30th
+        let microsoft = FiscalCalendar::new(6, 30);
+        assert_eq!(
+            microsoft.quarter_of(date("2024-12-15")),
+            FiscalQuarter::new(2025, 2)
+        );
+        assert_eq!(
+            microsoft.quarter_end(FiscalQuarter::new(2025, 1)),
+            date("2024-09-30")
+        );
+        assert_eq!(
+            microsoft.period_dates(&FiscalPeriod::Year { year: 2024 }, date("2024-12-15")),
+            (date("2023-07-01"), date("2024-06-30"))
+        );
+        assert_eq!(
+            microsoft.quarters_between(date("2024-01-01"), date("2024-12-31")),
+            vec![
+                FiscalQuarter::new(2024, 3),
+                FiscalQuarter::new(2024, 4),
+                FiscalQuarter::new(2025, 1),
+                FiscalQuarter::new(2025, 2)
+            ]
+        );
+
+        let calendar = FiscalCalendar::calendar_year();
+        assert_eq!(
+            calendar.last_completed_quarter(date("2024-12-15")),
+            FiscalQuarter::new(2024, 3)
+        );
+        assert_eq!(
+            calendar.period_dates(
+                &FiscalPeriod::Latest(LatestPeriod::LatestYear),
+                date("2024-12-15")
+            ),
+            (date("2023-01-01"), date("2023-12-31"))
+        );
+        assert_eq!(
+            serde_json::from_str::<FiscalPeriod>(r#"{"year": 2024, "quarter": 3}"#).unwrap(),
+            FiscalPeriod::Quarter {
+                year: 2024,
+                quarter: 3
+            }
+        );
+        for quarter in [0, 5] {
+            let period = format!(r#"{{"year": 2024, "quarter": {}}}"#, quarter);
+            assert!(serde_json::from_str::<FiscalPeriod>(&period).is_err());
+        }
+    }
+}
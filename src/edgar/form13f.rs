@@ -0,0 +1,261 @@
+// Importer for the SEC Form 13F data sets (https://www.sec.gov/dera/data/form-13f):
+// quarterly zip files with the cover page and information table of every 13F filing as
+// tab-separated tables. `SUBMISSION.tsv` lists the filings, `COVERPAGE.tsv` their managers and
+// amendment types and `INFOTABLE.tsv` the reported positions. Per-company lookups only see the
+// 13F filings of the company itself; the data sets fill in every other manager's holdings, so
+// the top holders and position changes of an issuer can be answered.
+use anyhow::Result;
+use chrono::NaiveDate;
+use serde::{Deserialize, Deserializer};
+use sqlx::{Pool, Postgres};
+use std::collections::HashMap;
+use std::path::Path;
+
+use super::bulk::BulkSummary;
+use super::datasets::{padded_cik, read_table};
+use super::holdings::{self, Holding, HoldingsReport};
+use crate::db;
+
+/// A filing, from `SUBMISSION.tsv`
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct Form13fSubmission {
+    #[serde(rename = "ACCESSION_NUMBER")]
+    pub accession_number: String,
+    #[serde(rename = "FILING_DATE", deserialize_with = "dd_mon_yyyy")]
+    pub filing_date: NaiveDate,
+    /// `13F-HR`, `13F-HR/A`, `13F-NT`, `13F-NT/A`
+    #[serde(rename = "SUBMISSIONTYPE")]
+    pub submission_type: String,
+    #[serde(rename = "CIK", deserialize_with = "padded_cik")]
+    pub cik: String,
+    #[serde(rename = "PERIODOFREPORT", deserialize_with = "dd_mon_yyyy")]
+    pub period_of_report: NaiveDate,
+}
+
+/// Cover page of a filing, from `COVERPAGE.tsv`
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct Form13fCoverPage {
+    #[serde(rename = "ACCESSION_NUMBER")]
+    pub accession_number: String,
+    /// `Y` for amendments
+    #[serde(rename = "ISAMENDMENT", default)]
+    pub is_amendment: Option<String>,
+    /// `RESTATEMENT` or `NEW HOLDINGS`
+    #[serde(rename = "AMENDMENTTYPE", default)]
+    pub amendment_type: Option<String>,
+    #[serde(rename = "FILINGMANAGER_NAME")]
+    pub filing_manager_name: String,
+}
+
+/// A position, from `INFOTABLE.tsv`
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct Form13fHolding {
+    #[serde(rename = "ACCESSION_NUMBER")]
+    pub accession_number: String,
+    #[serde(rename = "NAMEOFISSUER")]
+    pub name_of_issuer: String,
+    #[serde(rename = "TITLEOFCLASS")]
+    pub title_of_class: String,
+    #[serde(rename = "CUSIP")]
+    pub cusip: String,
+    /// Market value as reported, see [`holdings::value_multiplier`]
+    #[serde(rename = "VALUE")]
+    pub value: i64,
+    #[serde(rename = "SSHPRNAMT")]
+    pub shares: i64,
+    #[serde(rename = "SSHPRNAMTTYPE")]
+    pub share_type: String,
+    #[serde(rename = "PUTCALL", default)]
+    pub put_call: Option<String>,
+    #[serde(rename = "INVESTMENTDISCRETION")]
+    pub investment_discretion: String,
+    #[serde(rename = "VOTING_AUTH_SOLE", default)]
+    pub voting_sole: Option<i64>,
+    #[serde(rename = "VOTING_AUTH_SHARED", default)]
+    pub voting_shared: Option<i64>,
+    #[serde(rename = "VOTING_AUTH_NONE", default)]
+    pub voting_none: Option<i64>,
+}
+
+impl Form13fHolding {
+    /// Information table row with the value in USD
+    fn into_holding(self, value_multiplier: i64) -> Holding {
+        Holding {
+            name_of_issuer: self.name_of_issuer,
+            title_of_class: self.title_of_class,
+            cusip: self.cusip,
+            value: self.value * value_multiplier,
+            shares: self.shares,
+            share_type: self.share_type,
+            put_call: self.put_call,
+            investment_discretion: self.investment_discretion,
+            voting_sole: self.voting_sole.unwrap_or(0),
+            voting_shared: self.voting_shared.unwrap_or(0),
+            voting_none: self.voting_none.unwrap_or(0),
+        }
+    }
+}
+
+/// Dates are `31-MAR-2024`
+fn dd_mon_yyyy<'de, D: Deserializer<'de>>(deserializer: D) -> Result<NaiveDate, D::Error> {
+    let date = String::deserialize(deserializer)?;
+    NaiveDate::parse_from_str(&date, "%d-%b-%Y").map_err(serde::de::Error::custom)
+}
+
+/// Whether a file is a Form 13F data set, `2024q3_form13f.zip` or `01jun2024-31aug2024_form13f.zip`
+pub fn is_form13f_dataset(path: &Path) -> bool {
+    path.file_stem()
+        .and_then(|stem| stem.to_str())
+        .is_some_and(|stem| stem.to_lowercase().ends_with("form13f"))
+}
+
+/// Load the 13F-HR filings of a Form 13F data set into Postgres, replacing earlier copies of the
+/// same filings. Notices (13F-NT) hold no positions and are skipped, as are positions of filings
+/// missing from `SUBMISSION.tsv` or `COVERPAGE.tsv`.
+pub async fn import_holdings(pool: &Pool<Postgres>, path: &Path) -> Result<BulkSummary> {
+    let mut summary = BulkSummary::default();
+
+    let mut submissions = HashMap::new();
+    let mut batches = read_table::<Form13fSubmission>(path, "SUBMISSION.tsv");
+    while let Some(batch) = batches.recv().await {
+        let batch = batch?;
+        summary.skipped += batch.skipped;
+        for submission in batch.rows {
+            if submission.submission_type.starts_with("13F-HR") {
+                submissions.insert(submission.accession_number.clone(), submission);
+            } else {
+                summary.skipped += 1;
+            }
+        }
+    }
+
+    let mut reports = Vec::new();
+    let mut batches = read_table::<Form13fCoverPage>(path, "COVERPAGE.tsv");
+    while let Some(batch) = batches.recv().await {
+        let batch = batch?;
+        summary.skipped += batch.skipped;
+        for cover_page in batch.rows {
+            let Some(submission) = submissions.get(&cover_page.accession_number) else {
+                continue;
+            };
+            reports.push(HoldingsReport {
+                filer_cik: submission.cik.clone(),
+                filer_name: cover_page.filing_manager_name,
+                accession_number: cover_page.accession_number,
+                period_of_report: submission.period_of_report,
+                filing_date: submission.filing_date,
+                amendment_type: holdings::amendment_type(
+                    cover_page.is_amendment.as_deref(),
+                    cover_page.amendment_type.as_deref(),
+                ),
+                holdings: Vec::new(),
+            });
+        }
+    }
+    summary.skipped += submissions.len() - reports.len();
+    summary.imported = reports.len();
+    let multipliers: HashMap<String, i64> = reports
+        .iter()
+        .map(|report| {
+            (
+                report.accession_number.clone(),
+                holdings::value_multiplier(report.filing_date),
+            )
+        })
+        .collect();
+
+    // A half-imported data set would mix old and new copies of the quarter's filings
+    let mut tx = pool.begin().await?;
+    db::replace_institutional_filings(&mut tx, &reports).await?;
+
+    let mut batches = read_table::<Form13fHolding>(path, "INFOTABLE.tsv");
+    while let Some(batch) = batches.recv().await {
+        let batch = batch?;
+        let total = batch.rows.len();
+        let rows = batch
+            .rows
+            .into_iter()
+            .filter_map(|row| {
+                let multiplier = *multipliers.get(&row.accession_number)?;
+                Some((row.accession_number.clone(), row.into_holding(multiplier)))
+            })
+            .collect::<Vec<_>>();
+        summary.skipped += batch.skipped + total - rows.len();
+        summary.facts += rows.len();
+        let rows = rows
+            .iter()
+            .map(|(accession_number, holding)| (accession_number.as_str(), holding))
+            .collect::<Vec<_>>();
+        db::insert_institutional_holdings(&mut tx, &rows).await?;
+    }
+    tx.commit().await?;
+
+    log::info!("Imported {}: {}", path.display(), summary);
+    Ok(summary)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_form13f_tables() {
+        assert!(is_form13f_dataset(Path::new("/tmp/2024q3_form13f.zip")));
+        assert!(is_form13f_dataset(Path::new(
+            "01jun2024-31aug2024_form13f.zip"
+        )));
+        assert!(!is_form13f_dataset(Path::new("2024q3.zip")));
+
+        let submission = "ACCESSION_NUMBER\tFILING_DATE\tSUBMISSIONTYPE\tCIK\tPERIODOFREPORT\n\
+            0000950123-24-011775\t14-NOV-2024\t13F-HR/A\t1067983\t30-SEP-2024\n";
+        let submissions = csv::ReaderBuilder::new()
+            .delimiter(b'\t')
+            .quoting(false)
+            .from_reader(submission.as_bytes())
+            .deserialize::<Form13fSubmission>()
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        assert_eq!(submissions[0].cik, "0001067983");
+        assert_eq!(
+            submissions[0].period_of_report,
+            NaiveDate::from_ymd_opt(2024, 9, 30).unwrap()
+        );
+
+        let cover_page = "ACCESSION_NUMBER\tREPORTCALENDARORQUARTER\tISAMENDMENT\tAMENDMENTNO\tAMENDMENTTYPE\tFILINGMANAGER_NAME\n\
+            0000950123-24-011775\t30-SEP-2024\tY\t1\tNEW HOLDINGS\tBerkshire Hathaway Inc\n";
+        let cover_pages = csv::ReaderBuilder::new()
+            .delimiter(b'\t')
+            .quoting(false)
+            .from_reader(cover_page.as_bytes())
+            .deserialize::<Form13fCoverPage>()
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        assert_eq!(
+            holdings::amendment_type(
+                cover_pages[0].is_amendment.as_deref(),
+                cover_pages[0].amendment_type.as_deref()
+            )
+            .as_deref(),
+            Some(holdings::NEW_HOLDINGS)
+        );
+
+        // Voting authority can be left blank
+        let info_table = "ACCESSION_NUMBER\tINFOTABLE_SK\tNAMEOFISSUER\tTITLEOFCLASS\tCUSIP\tFIGI\tVALUE\tSSHPRNAMT\tSSHPRNAMTTYPE\tPUTCALL\tINVESTMENTDISCRETION\tOTHERMANAGER\tVOTING_AUTH_SOLE\tVOTING_AUTH_SHARED\tVOTING_AUTH_NONE\n\
+            0000950123-24-011775\t1\tAPPLE INC\tCOM\t037833100\t\t69900000000\t300000000\tSH\t\tDFND\t4,8,11\t300000000\t\t\n";
+        let holding = csv::ReaderBuilder::new()
+            .delimiter(b'\t')
+            .quoting(false)
+            .from_reader(info_table.as_bytes())
+            .deserialize::<Form13fHolding>()
+            .next()
+            .unwrap()
+            .unwrap()
+            .into_holding(1);
+        assert_eq!(
+            (holding.cusip.as_str(), holding.shares),
+            ("037833100", 300000000)
+        );
+        assert_eq!((holding.voting_sole, holding.voting_shared), (300000000, 0));
+        assert_eq!(holding.put_call, None);
+    }
+}
@@ -0,0 +1,500 @@
+// Parser for 13F-HR information tables (institutional holdings) and the aggregate views built
+// on top of the stored holdings: quarter over quarter changes and top holders of an issuer.
+use anyhow::{anyhow, Result};
+use chrono::{Datelike, Months, NaiveDate};
+use roxmltree::Node;
+use serde::{Deserialize, Serialize};
+use sqlx::{Pool, Postgres};
+
+use super::client::EdgarClient;
+use super::tickers;
+use crate::db;
+
+/// File name of the parsed information table, next to the other parsed filing artifacts
+pub const HOLDINGS_JSON: &str = "holdings.json";
+
+/// Amendment types of a 13F-HR/A: a restatement replaces the quarter's report, new holdings
+/// only add positions to it
+pub const RESTATEMENT: &str = "RESTATEMENT";
+pub const NEW_HOLDINGS: &str = "NEW HOLDINGS";
+
+/// Number of holders listed in the institutional activity summary
+const TOP_HOLDERS: i64 = 20;
+
+/// Company name suffixes dropped when matching 13F issuer names against ticker company names
+const NAME_SUFFIXES: [&str; 12] = [
+    "INC",
+    "CORP",
+    "CORPORATION",
+    "CO",
+    "COMPANY",
+    "LTD",
+    "PLC",
+    "LLC",
+    "LP",
+    "SA",
+    "NV",
+    "AG",
+];
+
+/// One row of a 13F information table
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Holding {
+    pub name_of_issuer: String,
+    pub title_of_class: String,
+    pub cusip: String,
+    /// Market value in USD
+    pub value: i64,
+    pub shares: i64,
+    /// "SH" for shares, "PRN" for principal amount
+    pub share_type: String,
+    /// "Put" or "Call" for option positions
+    pub put_call: Option<String>,
+    /// "SOLE", "DFND" (shared-defined) or "OTR" (shared-other)
+    pub investment_discretion: String,
+    pub voting_sole: i64,
+    pub voting_shared: i64,
+    pub voting_none: i64,
+}
+
+/// A parsed 13F-HR filing: cover page and information table
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct HoldingsReport {
+    pub filer_cik: String,
+    pub filer_name: String,
+    pub accession_number: String,
+    pub period_of_report: NaiveDate,
+    pub filing_date: NaiveDate,
+    /// [`RESTATEMENT`] or [`NEW_HOLDINGS`] for amendments, `None` for original reports
+    #[serde(default)]
+    pub amendment_type: Option<String>,
+    pub holdings: Vec<Holding>,
+}
+
+/// Change of one filer's position in an issuer between two quarters
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PositionChange {
+    pub filer_cik: String,
+    pub filer_name: String,
+    pub cusip: String,
+    pub issuer_name: String,
+    pub previous_shares: Option<i64>,
+    pub current_shares: Option<i64>,
+    pub previous_value: Option<i64>,
+    pub current_value: Option<i64>,
+}
+
+impl PositionChange {
+    pub fn shares_change(&self) -> i64 {
+        self.current_shares.unwrap_or(0) - self.previous_shares.unwrap_or(0)
+    }
+
+    pub fn describe(&self) -> &'static str {
+        match (self.previous_shares, self.current_shares) {
+            (None, Some(_)) => "New",
+            (Some(_), None) => "Exited",
+            _ if self.shares_change() > 0 => "Added",
+            _ if self.shares_change() < 0 => "Reduced",
+            _ => "Unchanged",
+        }
+    }
+}
+
+/// A filer's position in an issuer for one quarter
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct HolderPosition {
+    pub filer_cik: String,
+    pub filer_name: String,
+    pub cusip: String,
+    pub issuer_name: String,
+    pub shares: i64,
+    pub value: i64,
+}
+
+/// Cover page fields of a 13F-HR `primary_doc.xml`
+#[derive(Debug, Clone, PartialEq)]
+pub struct CoverPage {
+    pub filer_name: String,
+    pub period_of_report: NaiveDate,
+    pub amendment_type: Option<String>,
+}
+
+fn child<'a, 'input>(node: Node<'a, 'input>, name: &str) -> Option<Node<'a, 'input>> {
+    node.children()
+        .find(|n| n.is_element() && n.tag_name().name().eq_ignore_ascii_case(name))
+}
+
+fn text_at(node: Node, path: &[&str]) -> Option<String> {
+    let mut current = node;
+    for name in path {
+        current = child(current, name)?;
+    }
+    current
+        .text()
+        .map(|t| t.trim().to_string())
+        .filter(|t| !t.is_empty())
+}
+
+fn number_at(node: Node, path: &[&str]) -> Option<i64> {
+    text_at(node, path).and_then(|t| t.replace(',', "").parse::<f64>().ok().map(|v| v as i64))
+}
+
+/// Information tables filed before 2023-01-03 report values in thousands of dollars
+pub fn value_multiplier(filing_date: NaiveDate) -> i64 {
+    if filing_date < NaiveDate::from_ymd_opt(2023, 1, 3).unwrap() {
+        1000
+    } else {
+        1
+    }
+}
+
+/// Parse a 13F information table, with values as reported (see [`value_multiplier`])
+pub fn parse_information_table(xml: &str) -> Result<Vec<Holding>> {
+    let doc = roxmltree::Document::parse(xml)
+        .map_err(|e| anyhow!("Failed to parse information table XML: {}", e))?;
+    let root = doc.root_element();
+    if root.tag_name().name() != "informationTable" {
+        return Err(anyhow!(
+            "Not a 13F information table: <{}>",
+            root.tag_name().name()
+        ));
+    }
+
+    let holdings = root
+        .children()
+        .filter(|n| n.is_element() && n.tag_name().name() == "infoTable")
+        .filter_map(|row| {
+            let holding = Holding {
+                name_of_issuer: text_at(row, &["nameOfIssuer"]).unwrap_or_default(),
+                title_of_class: text_at(row, &["titleOfClass"]).unwrap_or_default(),
+                cusip: text_at(row, &["cusip"])?.to_uppercase(),
+                value: number_at(row, &["value"])?,
+                shares: number_at(row, &["shrsOrPrnAmt", "sshPrnamt"])?,
+                share_type: text_at(row, &["shrsOrPrnAmt", "sshPrnamtType"])
+                    .unwrap_or_else(|| "SH".to_string()),
+                put_call: text_at(row, &["putCall"]),
+                investment_discretion: text_at(row, &["investmentDiscretion"]).unwrap_or_default(),
+                voting_sole: number_at(row, &["votingAuthority", "Sole"]).unwrap_or(0),
+                voting_shared: number_at(row, &["votingAuthority", "Shared"]).unwrap_or(0),
+                voting_none: number_at(row, &["votingAuthority", "None"]).unwrap_or(0),
+            };
+            if holding.cusip.is_empty() {
+                log::warn!("Skipping information table row without CUSIP");
+                return None;
+            }
+            Some(holding)
+        })
+        .collect();
+
+    Ok(holdings)
+}
+
+/// Parse the filer name and report period out of a 13F-HR cover page
+pub fn parse_cover_page(xml: &str) -> Result<CoverPage> {
+    let doc = roxmltree::Document::parse(xml)
+        .map_err(|e| anyhow!("Failed to parse 13F cover page XML: {}", e))?;
+    let root = doc.root_element();
+
+    let filer_name = text_at(root, &["formData", "coverPage", "filingManager", "name"])
+        .ok_or_else(|| anyhow!("13F cover page without filing manager name"))?;
+    let period = text_at(root, &["formData", "coverPage", "reportCalendarOrQuarter"])
+        .or_else(|| text_at(root, &["headerData", "filerInfo", "periodOfReport"]))
+        .ok_or_else(|| anyhow!("13F cover page without report period"))?;
+    let period_of_report = NaiveDate::parse_from_str(&period, "%m-%d-%Y")
+        .or_else(|_| NaiveDate::parse_from_str(&period, "%Y-%m-%d"))
+        .map_err(|e| anyhow!("Invalid 13F report period {}: {}", period, e))?;
+
+    let amendment_type = amendment_type(
+        text_at(root, &["formData", "coverPage", "isAmendment"]).as_deref(),
+        text_at(
+            root,
+            &["formData", "coverPage", "amendmentInfo", "amendmentType"],
+        )
+        .as_deref(),
+    );
+
+    Ok(CoverPage {
+        filer_name,
+        period_of_report,
+        amendment_type,
+    })
+}
+
+/// Amendment type of a 13F-HR from the `isAmendment` flag (`true` or `Y`) and the declared
+/// type of the cover page; amendments that don't say what they amend are taken as restatements
+pub fn amendment_type(is_amendment: Option<&str>, kind: Option<&str>) -> Option<String> {
+    let is_amendment =
+        is_amendment.is_some_and(|flag| flag.eq_ignore_ascii_case("true") || flag == "Y");
+    is_amendment.then(|| match kind {
+        Some(kind) if kind.trim().eq_ignore_ascii_case(NEW_HOLDINGS) => NEW_HOLDINGS.to_string(),
+        _ => RESTATEMENT.to_string(),
+    })
+}
+
+/// End of the calendar quarter preceding the one that ends on (or contains) `period`
+pub fn previous_quarter_end(period: NaiveDate) -> NaiveDate {
+    let quarter_start_month = (period.month0() / 3) * 3 + 1;
+    NaiveDate::from_ymd_opt(period.year(), quarter_start_month, 1)
+        .and_then(|d| d.pred_opt())
+        .unwrap_or(period - Months::new(3))
+}
+
+/// Uppercase issuer name without punctuation and corporate suffixes, so that
+/// "NVIDIA CORPORATION" (13F) and "NVIDIA CORP" (company tickers) compare equal
+pub fn normalize_issuer_name(name: &str) -> String {
+    let cleaned = name
+        .to_uppercase()
+        .chars()
+        .map(|c| if c.is_alphanumeric() { c } else { ' ' })
+        .collect::<String>();
+    let mut words = cleaned.split_whitespace().collect::<Vec<_>>();
+    while words.len() > 1 && NAME_SUFFIXES.contains(words.last().unwrap()) {
+        words.pop();
+    }
+    words.join(" ")
+}
+
+impl HoldingsReport {
+    pub fn total_value(&self) -> i64 {
+        self.holdings.iter().map(|h| h.value).sum()
+    }
+
+    /// Markdown summary of the largest positions, used as the indexed text of the filing
+    pub fn to_markdown(&self, limit: usize) -> String {
+        let total = self.total_value();
+        let mut md = format!(
+            "# 13F Holdings - {} (CIK {}) for {}\n\nFiled: {}. Positions: {}. Total value: ${}\n\n",
+            self.filer_name,
+            self.filer_cik,
+            self.period_of_report,
+            self.filing_date,
+            self.holdings.len(),
+            total
+        );
+
+        let mut holdings = self.holdings.iter().collect::<Vec<_>>();
+        holdings.sort_by_key(|h| std::cmp::Reverse(h.value));
+
+        md.push_str("| Issuer | Class | CUSIP | Value (USD) | % of Portfolio | Shares | Put/Call | Discretion |\n");
+        md.push_str("| --- | --- | --- | --- | --- | --- | --- | --- |\n");
+        for h in holdings.into_iter().take(limit) {
+            md.push_str(&format!(
+                "| {} | {} | {} | {} | {:.2}% | {} {} | {} | {} |\n",
+                h.name_of_issuer,
+                h.title_of_class,
+                h.cusip,
+                h.value,
+                if total > 0 {
+                    h.value as f64 * 100.0 / total as f64
+                } else {
+                    0.0
+                },
+                h.shares,
+                h.share_type,
+                h.put_call.as_deref().unwrap_or("-"),
+                h.investment_discretion,
+            ));
+        }
+        md
+    }
+}
+
+/// Markdown table of position changes, largest share changes first
+pub fn position_changes_markdown(title: &str, changes: &[PositionChange]) -> String {
+    let mut md = format!("# {}\n\n", title);
+    if changes.is_empty() {
+        md.push_str("No institutional holdings stored for this period.\n");
+        return md;
+    }
+
+    let mut changes = changes.iter().collect::<Vec<_>>();
+    changes.sort_by_key(|c| std::cmp::Reverse(c.shares_change().abs()));
+
+    md.push_str("| Filer | Issuer | CUSIP | Change | Previous Shares | Current Shares | Share Change | Current Value (USD) |\n");
+    md.push_str("| --- | --- | --- | --- | --- | --- | --- | --- |\n");
+    for c in changes {
+        md.push_str(&format!(
+            "| {} | {} | {} | {} | {} | {} | {:+} | {} |\n",
+            c.filer_name,
+            c.issuer_name,
+            c.cusip,
+            c.describe(),
+            c.previous_shares.unwrap_or(0),
+            c.current_shares.unwrap_or(0),
+            c.shares_change(),
+            c.current_value.unwrap_or(0),
+        ));
+    }
+    md
+}
+
+/// CUSIPs under which 13F filers reported the company behind a ticker: the CUSIPs mapped to the
+/// ticker with `/cusip`, otherwise those of issuers whose normalized name equals the company's
+pub async fn issuer_cusips(
+    client: &EdgarClient,
+    pool: &Pool<Postgres>,
+    ticker: &str,
+) -> Result<Vec<String>> {
+    let mapped = db::mapped_issuer_cusips(pool, ticker).await?;
+    if !mapped.is_empty() {
+        return Ok(mapped);
+    }
+
+    let company_name =
+        normalize_issuer_name(&tickers::get_company_name_for_ticker(client, ticker).await?);
+    let first_word = company_name.split_whitespace().next().unwrap_or_default();
+    let mut cusips = db::find_holdings_issuers(pool, first_word)
+        .await?
+        .into_iter()
+        .filter(|(_, issuer_name)| normalize_issuer_name(issuer_name) == company_name)
+        .map(|(cusip, _)| cusip)
+        .collect::<Vec<_>>();
+    cusips.sort();
+    cusips.dedup();
+    if !cusips.is_empty() {
+        log::info!(
+            "Matched 13F issuer {} to CUSIPs {} by name, map them with /cusip if they are wrong",
+            company_name,
+            cusips.join(", ")
+        );
+    }
+    Ok(cusips)
+}
+
+/// Whether `cusip` looks like a CUSIP: nine characters, letters and digits
+pub fn is_cusip(cusip: &str) -> bool {
+    cusip.len() == 9 && cusip.chars().all(|c| c.is_ascii_alphanumeric())
+}
+
+/// Top holders of a ticker and the changes in their positions against the previous quarter,
+/// for the latest quarter with stored 13F data unless `period` is given
+pub async fn institutional_activity_for_ticker(
+    client: &EdgarClient,
+    pool: &Pool<Postgres>,
+    ticker: &str,
+    period: Option<NaiveDate>,
+) -> Result<String> {
+    let cusips = issuer_cusips(client, pool, ticker).await?;
+    if cusips.is_empty() {
+        return Err(anyhow!(
+            "No 13F holdings stored for {}, /import a Form 13F data set like 2024q3_form13f.zip",
+            ticker
+        ));
+    }
+    let period = match period {
+        Some(period) => period,
+        None => db::latest_holdings_period(pool, &cusips)
+            .await?
+            .ok_or_else(|| anyhow!("No 13F holdings stored for {}", ticker))?,
+    };
+    let previous_period = previous_quarter_end(period);
+
+    let holders = db::top_holders(pool, &cusips, period, TOP_HOLDERS).await?;
+    let mut md = format!("# {} Top Institutional Holders for {}\n\n", ticker, period);
+    md.push_str("| Filer | CIK | Shares | Value (USD) |\n| --- | --- | --- | --- |\n");
+    for holder in &holders {
+        md.push_str(&format!(
+            "| {} | {} | {} | {} |\n",
+            holder.filer_name, holder.filer_cik, holder.shares, holder.value
+        ));
+    }
+
+    let changes = db::issuer_position_changes(pool, &cusips, period, previous_period).await?;
+    md.push('\n');
+    md.push_str(&position_changes_markdown(
+        &format!(
+            "{} Institutional Position Changes {} to {}",
+            ticker, previous_period, period
+        ),
+        &changes,
+    ));
+    Ok(md)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const INFO_TABLE: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<informationTable xmlns="http://www.sec.gov/edgar/document/thirteenf/informationtable">
+  <infoTable>
+    <nameOfIssuer>APPLE INC</nameOfIssuer>
+    <titleOfClass>COM</titleOfClass>
+    <cusip>037833100</cusip>
+    <value>69900000000</value>
+    <shrsOrPrnAmt><sshPrnamt>300000000</sshPrnamt><sshPrnamtType>SH</sshPrnamtType></shrsOrPrnAmt>
+    <investmentDiscretion>DFND</investmentDiscretion>
+    <otherManager>4,8,11</otherManager>
+    <votingAuthority><Sole>300000000</Sole><Shared>0</Shared><None>0</None></votingAuthority>
+  </infoTable>
+  <infoTable>
+    <nameOfIssuer>NVIDIA CORPORATION</nameOfIssuer>
+    <titleOfClass>COM</titleOfClass>
+    <cusip>67066G104</cusip>
+    <value>1215000</value>
+    <shrsOrPrnAmt><sshPrnamt>10000</sshPrnamt><sshPrnamtType>SH</sshPrnamtType></shrsOrPrnAmt>
+    <putCall>Put</putCall>
+    <investmentDiscretion>SOLE</investmentDiscretion>
+    <votingAuthority><Sole>0</Sole><Shared>0</Shared><None>10000</None></votingAuthority>
+  </infoTable>
+</informationTable>"#;
+
+    #[test]
+    fn test_parse_information_table() {
+        let holdings = parse_information_table(INFO_TABLE).unwrap();
+
+        assert_eq!(holdings.len(), 2);
+        assert_eq!(holdings[0].cusip, "037833100");
+        assert_eq!(holdings[0].value, 69_900_000_000);
+        assert_eq!(holdings[0].shares, 300_000_000);
+        assert_eq!(holdings[0].put_call, None);
+        assert_eq!(holdings[0].investment_discretion, "DFND");
+        assert_eq!(holdings[1].put_call.as_deref(), Some("Put"));
+        assert_eq!(holdings[1].voting_none, 10_000);
+    }
+
+    #[test]
+    fn test_parse_cover_page() {
+        let xml = r#"<edgarSubmission xmlns="http://www.sec.gov/edgar/thirteenffiler">
+          <headerData><filerInfo><periodOfReport>09-30-2024</periodOfReport></filerInfo></headerData>
+          <formData><coverPage>
+            <reportCalendarOrQuarter>09-30-2024</reportCalendarOrQuarter>
+            <filingManager><name>BERKSHIRE HATHAWAY INC</name></filingManager>
+          </coverPage></formData>
+        </edgarSubmission>"#;
+
+        let cover = parse_cover_page(xml).unwrap();
+        assert_eq!(cover.filer_name, "BERKSHIRE HATHAWAY INC");
+        assert_eq!(
+            cover.period_of_report,
+            NaiveDate::from_ymd_opt(2024, 9, 30).unwrap()
+        );
+        assert_eq!(cover.amendment_type, None);
+
+        let amendment = xml.replace(
+            "<filingManager>",
+            "<isAmendment>true</isAmendment><amendmentInfo><amendmentType>NEW HOLDINGS</amendmentType></amendmentInfo><filingManager>",
+        );
+        let cover = parse_cover_page(&amendment).unwrap();
+        assert_eq!(cover.amendment_type.as_deref(), Some(NEW_HOLDINGS));
+    }
+
+    #[test]
+    fn test_quarters_and_names() {
+        assert_eq!(
+            previous_quarter_end(NaiveDate::from_ymd_opt(2024, 9, 30).unwrap()),
+            NaiveDate::from_ymd_opt(2024, 6, 30).unwrap()
+        );
+        assert_eq!(
+            previous_quarter_end(NaiveDate::from_ymd_opt(2024, 3, 31).unwrap()),
+            NaiveDate::from_ymd_opt(2023, 12, 31).unwrap()
+        );
+        assert_eq!(normalize_issuer_name("NVIDIA CORPORATION"), "NVIDIA");
+        assert_eq!(normalize_issuer_name("Apple Inc."), "APPLE");
+        assert!(is_cusip("67066G104") && !is_cusip("67066G10"));
+        assert_eq!(
+            value_multiplier(NaiveDate::from_ymd_opt(2022, 11, 14).unwrap()),
+            1000
+        );
+    }
+}
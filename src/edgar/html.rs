@@ -0,0 +1,312 @@
+// Conversion of EDGAR primary HTML documents (10-K, 10-Q, 8-K) into markdown that keeps
+// headings and tables, so narrative sections can be chunked and embedded.
+use scraper::{ElementRef, Html, Node};
+
+const SKIPPED_ELEMENTS: [&str; 6] = ["head", "script", "style", "title", "noscript", "ix:header"];
+const BLOCK_ELEMENTS: [&str; 12] = [
+    "html",
+    "body",
+    "div",
+    "p",
+    "section",
+    "article",
+    "center",
+    "blockquote",
+    "ul",
+    "ol",
+    "hr",
+    "pre",
+];
+
+/// Widest `colspan` honoured in tables, guarding against malformed attributes
+const MAX_COLSPAN: usize = 64;
+
+pub fn html_to_markdown(html: &str) -> String {
+    let document = Html::parse_document(html);
+    let mut writer = MarkdownWriter::default();
+    writer.element(document.root_element());
+    writer.finish()
+}
+
+#[derive(Default)]
+struct MarkdownWriter {
+    blocks: Vec<String>,
+    current: String,
+}
+
+impl MarkdownWriter {
+    fn element(&mut self, element: ElementRef) {
+        let name = element.value().name();
+        if SKIPPED_ELEMENTS.contains(&name) || is_hidden(&element) {
+            return;
+        }
+
+        match name {
+            "h1" | "h2" | "h3" | "h4" | "h5" | "h6" => {
+                self.flush();
+                let level = name[1..].parse::<usize>().unwrap_or(1);
+                let text = collapse_whitespace(&element.text().collect::<String>());
+                if !text.is_empty() {
+                    self.blocks.push(format!("{} {}", "#".repeat(level), text));
+                }
+            }
+            "table" => {
+                self.flush();
+                if let Some(table) = table_to_markdown(&element) {
+                    self.blocks.push(table);
+                }
+            }
+            "li" => {
+                self.flush();
+                self.children(&element);
+                let item = collapse_whitespace(&self.current);
+                self.current.clear();
+                if !item.is_empty() {
+                    self.blocks.push(format!("- {}", item));
+                }
+            }
+            "br" => self.flush(),
+            _ if BLOCK_ELEMENTS.contains(&name) => {
+                self.flush();
+                self.children(&element);
+                self.flush();
+            }
+            // Inline elements (span, font, b, a, ix:nonNumeric...) just contribute their text
+            _ => self.children(&element),
+        }
+    }
+
+    fn children(&mut self, element: &ElementRef) {
+        for child in element.children() {
+            match child.value() {
+                Node::Text(text) => self.current.push_str(text),
+                Node::Element(_) => {
+                    if let Some(child_element) = ElementRef::wrap(child) {
+                        self.element(child_element);
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+
+    fn flush(&mut self) {
+        let block = collapse_whitespace(&self.current);
+        if !block.is_empty() {
+            self.blocks.push(block);
+        }
+        self.current.clear();
+    }
+
+    fn finish(mut self) -> String {
+        self.flush();
+        let mut md = self.blocks.join("\n\n");
+        md.push('\n');
+        md
+    }
+}
+
+fn is_hidden(element: &ElementRef) -> bool {
+    element
+        .value()
+        .attr("style")
+        .map(|style| {
+            style
+                .to_lowercase()
+                .replace(' ', "")
+                .contains("display:none")
+        })
+        .unwrap_or(false)
+}
+
+fn collapse_whitespace(text: &str) -> String {
+    text.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+/// Render an HTML table as a markdown table.
+///
+/// EDGAR financial tables use separate cells for currency symbols, closing parentheses and
+/// percent signs plus plenty of empty spacer cells. Cells are laid out on a grid first, with
+/// `colspan` expanded and blanks kept so every value stays under its header; symbols are merged
+/// into their amounts, and neighbouring columns that never both hold a value in the same row
+/// are then folded together. Single-row tables are layout tables (typically "Item 7. |
+/// Management's Discussion...") and are rendered as a plain paragraph instead.
+fn table_to_markdown(table: &ElementRef) -> Option<String> {
+    let mut rows: Vec<Vec<String>> = Vec::new();
+
+    for row in table_rows(table) {
+        let mut cells: Vec<String> = Vec::new();
+        for cell in row
+            .child_elements()
+            .filter(|e| matches!(e.value().name(), "td" | "th"))
+        {
+            let text = collapse_whitespace(&cell.text().collect::<String>()).replace('|', "\\|");
+            let span = cell
+                .value()
+                .attr("colspan")
+                .and_then(|span| span.trim().parse::<usize>().ok())
+                .unwrap_or(1)
+                .clamp(1, MAX_COLSPAN);
+            cells.push(text);
+            cells.resize(cells.len() + span - 1, String::new());
+        }
+
+        merge_symbols(&mut cells);
+        if cells.iter().any(|cell| !cell.is_empty()) {
+            rows.push(cells);
+        }
+    }
+
+    match rows.len() {
+        0 => None,
+        1 => Some(
+            rows[0]
+                .iter()
+                .filter(|cell| !cell.is_empty())
+                .cloned()
+                .collect::<Vec<_>>()
+                .join(" "),
+        ),
+        _ => {
+            let columns = fold_columns(&rows);
+            let mut md = String::new();
+            for i in 0..rows.len() {
+                md.push('|');
+                for column in &columns {
+                    md.push_str(&format!(" {} |", column[i]));
+                }
+                md.push('\n');
+                if i == 0 {
+                    md.push('|');
+                    for _ in &columns {
+                        md.push_str(" --- |");
+                    }
+                    md.push('\n');
+                }
+            }
+            Some(md.trim_end().to_string())
+        }
+    }
+}
+
+/// Rows of the table itself: its `tr` children and those of its `thead`, `tbody` and `tfoot`,
+/// but not the rows of tables nested in its cells
+fn table_rows<'a>(table: &ElementRef<'a>) -> Vec<ElementRef<'a>> {
+    table
+        .child_elements()
+        .flat_map(|child| match child.value().name() {
+            "tr" => vec![child],
+            "thead" | "tbody" | "tfoot" => child
+                .child_elements()
+                .filter(|e| e.value().name() == "tr")
+                .collect(),
+            _ => Vec::new(),
+        })
+        .collect()
+}
+
+/// Move amounts into the currency cell before them and closing parentheses and percent signs
+/// into the value before them, leaving the cells they came from blank
+fn merge_symbols(cells: &mut [String]) {
+    let mut currency: Option<usize> = None;
+    for i in 0..cells.len() {
+        match cells[i].as_str() {
+            "" => {}
+            "$" | "€" | "£" => currency = Some(i),
+            ")" | "%" | ")%" | "%)" => {
+                if let Some(previous) = (0..i).rev().find(|&j| !cells[j].is_empty()) {
+                    let symbol = std::mem::take(&mut cells[i]);
+                    cells[previous].push_str(&symbol);
+                }
+            }
+            _ => {
+                if let Some(currency) = currency.take() {
+                    let amount = std::mem::take(&mut cells[i]);
+                    cells[currency].push_str(&amount);
+                }
+            }
+        }
+    }
+}
+
+/// Columns of the table, with each column folded into the one before it when no row has a
+/// value in both, which drops spacer columns and lines up amounts with and without a currency
+fn fold_columns(rows: &[Vec<String>]) -> Vec<Vec<String>> {
+    let width = rows.iter().map(|row| row.len()).max().unwrap_or(0);
+    let mut columns: Vec<Vec<String>> = Vec::new();
+    for col in 0..width {
+        let column = rows
+            .iter()
+            .map(|row| row.get(col).cloned().unwrap_or_default())
+            .collect::<Vec<_>>();
+        match columns.last_mut() {
+            Some(last)
+                if last
+                    .iter()
+                    .zip(&column)
+                    .all(|(a, b)| a.is_empty() || b.is_empty()) =>
+            {
+                for (a, b) in last.iter_mut().zip(column) {
+                    if a.is_empty() {
+                        *a = b;
+                    }
+                }
+            }
+            _ => columns.push(column),
+        }
+    }
+    columns
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_html_to_markdown() {
+        let html = r#"
+        <html><head><title>10-K</title><style>p { color: red; }</style></head>
+        <body>
+            <div style="display: none"><ix:header>hidden facts</ix:header></div>
+            <h2>PART I</h2>
+            <p><span>Item&#160;1A.</span> <b>Risk Factors</b></p>
+            <p>Our business is subject to   numerous risks.</p>
+            <ul><li>Competition</li><li>Regulation</li></ul>
+            <table>
+                <tr><td></td><td colspan="2">2024</td><td></td><td colspan="2">2023</td></tr>
+                <tr><td>Net sales</td><td>$</td><td>391,035</td><td></td><td>$</td><td>383,285</td></tr>
+                <tr><td>Services</td><td></td><td>96,169</td><td></td><td></td><td>85,200</td></tr>
+                <tr><td>Change</td><td>2</td><td>%</td><td></td><td>(3</td><td>)%</td></tr>
+                <tr><td>Unearned</td><td></td><td></td><td></td><td></td><td>1,200</td></tr>
+            </table>
+            <table><tbody>
+                <tr><th>Segment</th><th>Notes</th></tr>
+                <tr><td>Americas</td><td><table><tr><td>Inner</td></tr> <tr><td>rows</td></tr></table></td></tr>
+            </tbody></table>
+        </body></html>
+        "#;
+
+        let md = html_to_markdown(html);
+
+        assert!(!md.contains("hidden facts"));
+        assert!(!md.contains("color: red"));
+        assert!(md.contains("## PART I"));
+        assert!(md.contains("Item 1A. Risk Factors"));
+        assert!(md.contains("Our business is subject to numerous risks."));
+        assert!(md.contains("- Competition\n\n- Regulation"));
+        assert!(md.contains("|  | 2024 | 2023 |\n| --- | --- | --- |"));
+        assert!(md.contains("| Net sales | $391,035 | $383,285 |"));
+        assert!(md.contains("| Services | 96,169 | 85,200 |"));
+        assert!(md.contains("| Change | 2% | (3)% |"));
+        assert!(md.contains("| Unearned |  | 1,200 |"));
+        // Rows of a nested table stay inside the cell that holds it
+        assert!(md.contains("| Americas | Inner rows |"));
+        assert_eq!(md.matches("Inner").count(), 1);
+    }
+
+    #[test]
+    fn test_single_row_table_is_paragraph() {
+        let html = "<table><tr><td>Item 7.</td><td>Management's Discussion</td></tr></table>";
+        assert_eq!(html_to_markdown(html), "Item 7. Management's Discussion\n");
+    }
+}
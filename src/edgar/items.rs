@@ -0,0 +1,242 @@
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use std::{fmt, str::FromStr};
+use strum::{EnumIter, IntoEnumIterator};
+
+/// Item of a Form 8-K current report, as listed in the submissions `items` field
+/// (e.g. "2.02,9.01")
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, EnumIter)]
+#[serde(try_from = "String", into = "String")]
+pub enum Form8KItem {
+    MaterialAgreement,
+    TerminationOfMaterialAgreement,
+    Bankruptcy,
+    MineSafety,
+    CybersecurityIncident,
+    AcquisitionOrDisposition,
+    ResultsOfOperations,
+    DirectFinancialObligation,
+    AcceleratedFinancialObligation,
+    ExitCosts,
+    MaterialImpairments,
+    Delisting,
+    UnregisteredEquitySales,
+    SecurityHolderRights,
+    AccountantChange,
+    NonReliance,
+    ChangeInControl,
+    OfficerChanges,
+    BylawAmendments,
+    BenefitPlanSuspension,
+    CodeOfEthics,
+    ShellCompanyStatus,
+    ShareholderVote,
+    DirectorNominations,
+    AbsInformationalMaterial,
+    AbsServicerChange,
+    AbsCreditEnhancement,
+    AbsDistributionFailure,
+    AbsSecuritiesActUpdate,
+    RegulationFD,
+    OtherEvents,
+    FinancialStatementsAndExhibits,
+    Other(String),
+}
+
+impl Form8KItem {
+    pub fn code(&self) -> &str {
+        match self {
+            Form8KItem::MaterialAgreement => "1.01",
+            Form8KItem::TerminationOfMaterialAgreement => "1.02",
+            Form8KItem::Bankruptcy => "1.03",
+            Form8KItem::MineSafety => "1.04",
+            Form8KItem::CybersecurityIncident => "1.05",
+            Form8KItem::AcquisitionOrDisposition => "2.01",
+            Form8KItem::ResultsOfOperations => "2.02",
+            Form8KItem::DirectFinancialObligation => "2.03",
+            Form8KItem::AcceleratedFinancialObligation => "2.04",
+            Form8KItem::ExitCosts => "2.05",
+            Form8KItem::MaterialImpairments => "2.06",
+            Form8KItem::Delisting => "3.01",
+            Form8KItem::UnregisteredEquitySales => "3.02",
+            Form8KItem::SecurityHolderRights => "3.03",
+            Form8KItem::AccountantChange => "4.01",
+            Form8KItem::NonReliance => "4.02",
+            Form8KItem::ChangeInControl => "5.01",
+            Form8KItem::OfficerChanges => "5.02",
+            Form8KItem::BylawAmendments => "5.03",
+            Form8KItem::BenefitPlanSuspension => "5.04",
+            Form8KItem::CodeOfEthics => "5.05",
+            Form8KItem::ShellCompanyStatus => "5.06",
+            Form8KItem::ShareholderVote => "5.07",
+            Form8KItem::DirectorNominations => "5.08",
+            Form8KItem::AbsInformationalMaterial => "6.01",
+            Form8KItem::AbsServicerChange => "6.02",
+            Form8KItem::AbsCreditEnhancement => "6.03",
+            Form8KItem::AbsDistributionFailure => "6.04",
+            Form8KItem::AbsSecuritiesActUpdate => "6.05",
+            Form8KItem::RegulationFD => "7.01",
+            Form8KItem::OtherEvents => "8.01",
+            Form8KItem::FinancialStatementsAndExhibits => "9.01",
+            Form8KItem::Other(code) => code,
+        }
+    }
+
+    pub fn description(&self) -> &str {
+        match self {
+            Form8KItem::MaterialAgreement => "Entry into a Material Definitive Agreement",
+            Form8KItem::TerminationOfMaterialAgreement => {
+                "Termination of a Material Definitive Agreement"
+            }
+            Form8KItem::Bankruptcy => "Bankruptcy or Receivership",
+            Form8KItem::MineSafety => {
+                "Mine Safety - Reporting of Shutdowns and Patterns of Violations"
+            }
+            Form8KItem::CybersecurityIncident => "Material Cybersecurity Incidents",
+            Form8KItem::AcquisitionOrDisposition => {
+                "Completion of Acquisition or Disposition of Assets"
+            }
+            Form8KItem::ResultsOfOperations => "Results of Operations and Financial Condition",
+            Form8KItem::DirectFinancialObligation => {
+                "Creation of a Direct Financial Obligation or an Off-Balance Sheet Arrangement"
+            }
+            Form8KItem::AcceleratedFinancialObligation => {
+                "Triggering Events That Accelerate or Increase a Direct Financial Obligation"
+            }
+            Form8KItem::ExitCosts => "Costs Associated with Exit or Disposal Activities",
+            Form8KItem::MaterialImpairments => "Material Impairments",
+            Form8KItem::Delisting => {
+                "Notice of Delisting or Failure to Satisfy a Continued Listing Rule; Transfer of Listing"
+            }
+            Form8KItem::UnregisteredEquitySales => "Unregistered Sales of Equity Securities",
+            Form8KItem::SecurityHolderRights => "Material Modification to Rights of Security Holders",
+            Form8KItem::AccountantChange => "Changes in Registrant's Certifying Accountant",
+            Form8KItem::NonReliance => {
+                "Non-Reliance on Previously Issued Financial Statements or a Related Audit Report"
+            }
+            Form8KItem::ChangeInControl => "Changes in Control of Registrant",
+            Form8KItem::OfficerChanges => {
+                "Departure of Directors or Certain Officers; Election of Directors; Appointment of Certain Officers"
+            }
+            Form8KItem::BylawAmendments => {
+                "Amendments to Articles of Incorporation or Bylaws; Change in Fiscal Year"
+            }
+            Form8KItem::BenefitPlanSuspension => {
+                "Temporary Suspension of Trading Under Registrant's Employee Benefit Plans"
+            }
+            Form8KItem::CodeOfEthics => {
+                "Amendment to Registrant's Code of Ethics, or Waiver of a Provision of the Code of Ethics"
+            }
+            Form8KItem::ShellCompanyStatus => "Change in Shell Company Status",
+            Form8KItem::ShareholderVote => "Submission of Matters to a Vote of Security Holders",
+            Form8KItem::DirectorNominations => "Shareholder Director Nominations",
+            Form8KItem::AbsInformationalMaterial => "ABS Informational and Computational Material",
+            Form8KItem::AbsServicerChange => "Change of Servicer or Trustee",
+            Form8KItem::AbsCreditEnhancement => {
+                "Change in Credit Enhancement or Other External Support"
+            }
+            Form8KItem::AbsDistributionFailure => "Failure to Make a Required Distribution",
+            Form8KItem::AbsSecuritiesActUpdate => "Securities Act Updating Disclosure",
+            Form8KItem::RegulationFD => "Regulation FD Disclosure",
+            Form8KItem::OtherEvents => "Other Events",
+            Form8KItem::FinancialStatementsAndExhibits => "Financial Statements and Exhibits",
+            Form8KItem::Other(_) => "Unknown item",
+        }
+    }
+
+    /// Parse the comma separated `items` field of a submissions entry.
+    /// Older filings use pre-2004 item numbers or leave the field empty.
+    pub fn parse_list(items: &str) -> Vec<Form8KItem> {
+        items
+            .split(',')
+            .map(str::trim)
+            .filter(|s| !s.is_empty())
+            .filter_map(|s| s.parse().ok())
+            .collect()
+    }
+}
+
+impl fmt::Display for Form8KItem {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.code())
+    }
+}
+
+impl FromStr for Form8KItem {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let code = s.trim();
+        let code = code
+            .strip_prefix("Item ")
+            .or_else(|| code.strip_prefix("ITEM "))
+            .unwrap_or(code)
+            .trim();
+        if code.is_empty() {
+            return Err("Empty 8-K item code".to_string());
+        }
+
+        Ok(Form8KItem::iter()
+            .find(|item| item.code() == code)
+            .unwrap_or_else(|| Form8KItem::Other(code.to_string())))
+    }
+}
+
+impl TryFrom<String> for Form8KItem {
+    type Error = String;
+
+    fn try_from(s: String) -> Result<Self, Self::Error> {
+        Form8KItem::from_str(&s)
+    }
+}
+
+impl From<Form8KItem> for String {
+    fn from(item: Form8KItem) -> Self {
+        item.code().to_string()
+    }
+}
+
+/// "code (description)" list of the known 8-K items, for LLM prompts
+pub static FORM_8K_ITEMS: Lazy<String> = Lazy::new(|| {
+    Form8KItem::iter()
+        .filter(|item| !matches!(item, Form8KItem::Other(_)))
+        .map(|item| format!("{} ({})", item.code(), item.description()))
+        .collect::<Vec<_>>()
+        .join(", ")
+});
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_item_list() {
+        assert_eq!(
+            Form8KItem::parse_list("2.02,7.01, 9.01"),
+            vec![
+                Form8KItem::ResultsOfOperations,
+                Form8KItem::RegulationFD,
+                Form8KItem::FinancialStatementsAndExhibits
+            ]
+        );
+        assert_eq!(Form8KItem::parse_list(""), vec![]);
+        assert_eq!(
+            Form8KItem::parse_list("12"),
+            vec![Form8KItem::Other("12".to_string())]
+        );
+    }
+
+    #[test]
+    fn test_item_serde_roundtrip() {
+        let items: Vec<Form8KItem> = serde_json::from_str(r#"["Item 5.02", "1.01"]"#).unwrap();
+        assert_eq!(
+            items,
+            vec![Form8KItem::OfficerChanges, Form8KItem::MaterialAgreement]
+        );
+        assert_eq!(serde_json::to_string(&items).unwrap(), r#"["5.02","1.01"]"#);
+        assert_eq!(
+            Form8KItem::OfficerChanges.description(),
+            "Departure of Directors or Certain Officers; Election of Directors; Appointment of Certain Officers"
+        );
+    }
+}
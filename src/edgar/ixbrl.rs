@@ -0,0 +1,364 @@
+// Parser for Inline XBRL documents: facts are tagged inside the primary HTML document with
+// `ix:nonFraction` and `ix:nonNumeric`, their contexts and units live in the `ix:resources` of
+// the hidden `ix:header`. Displayed values are turned back into XBRL values with the `format`,
+// `scale` and `sign` attributes, producing the same `FactItem`s as an extracted instance.
+use chrono::NaiveDate;
+use roxmltree::{Node, ParsingOptions};
+use std::collections::{HashMap, HashSet};
+
+use super::xbrl::{sanitize_html, split_qname, Contexts, FactItem, XbrlError, XSI_NAMESPACE};
+
+/// Inline XBRL 1.1, and 1.0 used by the earliest inline filings
+const IX_NAMESPACES: [&str; 2] = [
+    "http://www.xbrl.org/2013/inlineXBRL",
+    "http://www.xbrl.org/2008/inlineXBRL",
+];
+const XBRLI_NAMESPACE: &str = "http://www.xbrl.org/2003/instance";
+
+/// HTML elements whose text is kept apart from the text around them
+const BLOCK_ELEMENTS: [&str; 14] = [
+    "p", "div", "br", "table", "tr", "td", "th", "li", "h1", "h2", "h3", "h4", "h5", "h6",
+];
+
+const SMALL_NUMBERS: [&str; 20] = [
+    "zero",
+    "one",
+    "two",
+    "three",
+    "four",
+    "five",
+    "six",
+    "seven",
+    "eight",
+    "nine",
+    "ten",
+    "eleven",
+    "twelve",
+    "thirteen",
+    "fourteen",
+    "fifteen",
+    "sixteen",
+    "seventeen",
+    "eighteen",
+    "nineteen",
+];
+const TENS: [&str; 8] = [
+    "twenty", "thirty", "forty", "fifty", "sixty", "seventy", "eighty", "ninety",
+];
+
+fn is_ix(node: Node, name: &str) -> bool {
+    node.is_element()
+        && node.tag_name().name() == name
+        && node
+            .tag_name()
+            .namespace()
+            .is_some_and(|ns| IX_NAMESPACES.contains(&ns))
+}
+
+/// Text of an element, leaving out its `ix:exclude` parts
+fn collect_text(node: Node, text: &mut String) {
+    for child in node.children() {
+        if child.is_text() {
+            text.push_str(child.text().unwrap_or(""));
+        } else if child.is_element() && !is_ix(child, "exclude") {
+            collect_text(child, text);
+            if BLOCK_ELEMENTS.contains(&child.tag_name().name()) {
+                text.push(' ');
+            }
+        }
+    }
+}
+
+/// Text of a fact followed by the `ix:continuation` chain its `continuedAt` starts
+fn fact_text(node: Node, continuations: &HashMap<&str, Node>) -> String {
+    let mut text = String::new();
+    collect_text(node, &mut text);
+
+    // A malformed document could chain continuations into a loop
+    let mut seen = HashSet::new();
+    let mut next = node.attribute("continuedAt");
+    while let Some(id) = next.filter(|id| seen.insert(*id)) {
+        let Some(continuation) = continuations.get(id) else {
+            log::warn!("Missing ix:continuation {}", id);
+            break;
+        };
+        text.push(' ');
+        collect_text(*continuation, &mut text);
+        next = continuation.attribute("continuedAt");
+    }
+    text
+}
+
+/// Local name of a transformation, `num-dot-decimal` for `ixt:num-dot-decimal`
+fn format_name(format: &str) -> &str {
+    format.rsplit(':').next().unwrap_or(format)
+}
+
+/// `ixt-sec:numwordsen`: "no", "none", "twenty-one", "one hundred thousand"
+fn number_words(text: &str) -> Option<u64> {
+    let mut total = 0;
+    let mut current = 0;
+    for word in text
+        .to_lowercase()
+        .replace(['-', ','], " ")
+        .split_whitespace()
+    {
+        match word {
+            "no" | "none" | "nil" | "and" => {}
+            "hundred" => current *= 100,
+            "thousand" => (total, current) = (total + current * 1_000, 0),
+            "million" => (total, current) = (total + current * 1_000_000, 0),
+            "billion" => (total, current) = (total + current * 1_000_000_000, 0),
+            word => {
+                current += match SMALL_NUMBERS.iter().position(|n| *n == word) {
+                    Some(n) => n as u64,
+                    None => (TENS.iter().position(|n| *n == word)? as u64 + 2) * 10,
+                }
+            }
+        }
+    }
+    Some(total + current)
+}
+
+/// Number displayed as `text` in XBRL notation, `1234.5` for `1,234.5` with `num-dot-decimal`
+fn transform_number(format: Option<&str>, text: &str) -> Option<String> {
+    let number = match format.map(format_name) {
+        Some("fixed-zero" | "fixedzero" | "zerodash") => "0".to_string(),
+        Some("num-comma-decimal" | "numcommadecimal" | "numdotcomma" | "numspacecomma") => text
+            .chars()
+            .filter(|c| c.is_ascii_digit() || *c == ',')
+            .collect::<String>()
+            .replace(',', "."),
+        Some("num-word-en" | "numwordsen") => number_words(text)?.to_string(),
+        _ => text
+            .chars()
+            .filter(|c| c.is_ascii_digit() || *c == '.')
+            .collect(),
+    };
+    let valid = number.chars().any(|c| c.is_ascii_digit()) && number.matches('.').count() <= 1;
+    valid.then_some(number)
+}
+
+/// Multiply a decimal number by 10^`scale`, shifting the decimal point instead of going
+/// through floating point
+fn apply_scale(number: &str, scale: i32) -> String {
+    let (int, frac) = number.split_once('.').unwrap_or((number, ""));
+    let digits = format!("{}{}", int, frac);
+    let point = int.len() as i64 + scale as i64;
+
+    let (int, frac) = if point <= 0 {
+        (String::new(), "0".repeat(-point as usize) + &digits)
+    } else if point as usize >= digits.len() {
+        (
+            digits.clone() + &"0".repeat(point as usize - digits.len()),
+            String::new(),
+        )
+    } else {
+        let (int, frac) = digits.split_at(point as usize);
+        (int.to_string(), frac.to_string())
+    };
+
+    let int = match int.trim_start_matches('0') {
+        "" => "0",
+        int => int,
+    };
+    match frac.trim_end_matches('0') {
+        "" => int.to_string(),
+        frac => format!("{}.{}", int, frac),
+    }
+}
+
+/// Date displayed as `text` with one of the `date-*` transformations, `None` for
+/// transformations of partial dates (month and year, ...) that are kept as displayed
+fn transform_date(format: &str, text: &str) -> Option<Option<NaiveDate>> {
+    let (separators, date_format): (&[char], &str) = match format.replace('-', "").as_str() {
+        "datemonthnamedayyearen" | "datemonthdayyearen" => (&[',', '.'], "%B %d %Y"),
+        "datedaymonthnameyearen" | "datedaymonthyearen" => (&[',', '.'], "%d %B %Y"),
+        "datemonthdayyear" | "dateslashus" | "datedotus" => (&['/', '.', '-'], "%m %d %Y"),
+        "datedaymonthyear" | "dateslasheu" | "datedoteu" => (&['/', '.', '-'], "%d %m %Y"),
+        "dateyearmonthday" => (&['/', '.', '-'], "%Y %m %d"),
+        _ => return None,
+    };
+    let text = text
+        .replace(separators, " ")
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ");
+    Some(NaiveDate::parse_from_str(&text, date_format).ok())
+}
+
+/// Value of an `ix:nonNumeric` displayed as `text`
+fn transform_text(format: Option<&str>, text: &str) -> Option<String> {
+    let Some(format) = format.map(format_name) else {
+        return Some(text.to_string());
+    };
+    match format {
+        "fixed-true" | "booleantrue" => Some("true".to_string()),
+        "fixed-false" | "booleanfalse" => Some("false".to_string()),
+        "boolballotbox" => match text.trim() {
+            "☒" | "☑" => Some("true".to_string()),
+            "☐" => Some("false".to_string()),
+            _ => None,
+        },
+        _ => match transform_date(format, text) {
+            Some(date) => date.map(|date| date.format("%Y-%m-%d").to_string()),
+            None => Some(text.to_string()),
+        },
+    }
+}
+
+fn inline_fact(
+    node: Node,
+    contexts: &Contexts,
+    continuations: &HashMap<&str, Node>,
+) -> Result<FactItem, XbrlError> {
+    let qname = node.attribute("name").unwrap_or("");
+    let (prefix, name) = split_qname(qname)?;
+    let format = node.attribute("format");
+    let invalid = |value: &str| XbrlError::InvalidValue {
+        fact: qname.to_string(),
+        value: value.trim().to_string(),
+    };
+
+    let value = if !is_ix(node, "nonFraction") {
+        let text = fact_text(node, continuations);
+        let value = transform_text(format, &text).ok_or_else(|| invalid(&text))?;
+        sanitize_html(value).trim().to_string()
+    } else if node.attribute((XSI_NAMESPACE, "nil")) == Some("true") {
+        String::new()
+    } else {
+        let mut text = String::new();
+        collect_text(node, &mut text);
+        let number = transform_number(format, &text).ok_or_else(|| invalid(&text))?;
+        let scale = match node.attribute("scale") {
+            Some(scale) => scale.trim().parse().map_err(|_| invalid(scale))?,
+            None => 0,
+        };
+        let number = apply_scale(&number, scale);
+        if node.attribute("sign") == Some("-") {
+            format!("-{}", number)
+        } else {
+            number
+        }
+    };
+
+    contexts.fact(node, prefix, name, value)
+}
+
+/// Facts of an Inline XBRL document, hidden facts of the `ix:header` included
+pub fn parse_ixbrl_to_facts(html: &str) -> Result<Vec<FactItem>, XbrlError> {
+    let options = ParsingOptions {
+        allow_dtd: true,
+        ..ParsingOptions::default()
+    };
+    let document = roxmltree::Document::parse_with_options(html, options)?;
+
+    let contexts = Contexts::parse(
+        document
+            .descendants()
+            .filter(|e| e.tag_name().namespace() == Some(XBRLI_NAMESPACE)),
+    );
+    let continuations: HashMap<&str, Node> = document
+        .descendants()
+        .filter(|e| is_ix(*e, "continuation"))
+        .filter_map(|e| Some((e.attribute("id")?, e)))
+        .collect();
+
+    let mut facts = Vec::new();
+    for node in document
+        .descendants()
+        .filter(|e| is_ix(*e, "nonFraction") || is_ix(*e, "nonNumeric"))
+    {
+        match inline_fact(node, &contexts, &continuations) {
+            Ok(fact) => facts.push(fact),
+            Err(e) => log::warn!("Skipping fact: {}", e),
+        }
+    }
+    Ok(facts)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DOCUMENT: &str = r#"<?xml version="1.0" encoding="utf-8"?>
+<html xmlns="http://www.w3.org/1999/xhtml" xmlns:ix="http://www.xbrl.org/2013/inlineXBRL"
+    xmlns:ixt="http://www.xbrl.org/inlineXBRL/transformation/2020-02-12" xmlns:ixt-sec="http://www.sec.gov/inlineXBRL/transformation/2015-08-31"
+    xmlns:xbrli="http://www.xbrl.org/2003/instance" xmlns:xbrldi="http://xbrl.org/2006/xbrldi" xmlns:iso4217="http://www.xbrl.org/2003/iso4217"
+    xmlns:us-gaap="http://fasb.org/us-gaap/2023" xmlns:dei="http://xbrl.sec.gov/dei/2023" xmlns:aapl="http://www.apple.com/20230930">
+<body>
+  <div style="display:none"><ix:header>
+    <ix:hidden>
+      <ix:nonNumeric name="dei:AmendmentFlag" contextRef="FY2023">false</ix:nonNumeric>
+    </ix:hidden>
+    <ix:resources>
+      <xbrli:context id="FY2023">
+        <xbrli:entity><xbrli:identifier scheme="http://www.sec.gov/CIK">0000320193</xbrli:identifier></xbrli:entity>
+        <xbrli:period><xbrli:startDate>2022-09-25</xbrli:startDate><xbrli:endDate>2023-09-30</xbrli:endDate></xbrli:period>
+      </xbrli:context>
+      <xbrli:context id="FY2023_Europe">
+        <xbrli:entity>
+          <xbrli:identifier scheme="http://www.sec.gov/CIK">0000320193</xbrli:identifier>
+          <xbrli:segment><xbrldi:explicitMember dimension="us-gaap:StatementBusinessSegmentsAxis">aapl:EuropeSegmentMember</xbrldi:explicitMember></xbrli:segment>
+        </xbrli:entity>
+        <xbrli:period><xbrli:startDate>2022-09-25</xbrli:startDate><xbrli:endDate>2023-09-30</xbrli:endDate></xbrli:period>
+      </xbrli:context>
+      <xbrli:unit id="usd"><xbrli:measure>iso4217:USD</xbrli:measure></xbrli:unit>
+    </ix:resources>
+  </ix:header></div>
+  <p>Fiscal year ended <ix:nonNumeric name="dei:DocumentPeriodEndDate" contextRef="FY2023" format="ixt:date-monthname-day-year-en">September 30, 2023</ix:nonNumeric></p>
+  <table><tr>
+    <td>Net sales</td>
+    <td>$<ix:nonFraction name="us-gaap:Revenues" contextRef="FY2023" unitRef="usd" decimals="-6" scale="6" format="ixt:num-dot-decimal">383,285</ix:nonFraction></td>
+    <td><ix:nonFraction name="us-gaap:Revenues" contextRef="FY2023_Europe" unitRef="usd" decimals="-6" scale="6" format="ixt:num-dot-decimal">94,294</ix:nonFraction></td>
+    <td>(<ix:nonFraction name="us-gaap:OtherNonoperatingIncomeExpense" contextRef="FY2023" unitRef="usd" decimals="-6" scale="6" sign="-" format="ixt:num-dot-decimal">565</ix:nonFraction>)</td>
+    <td><ix:nonFraction name="us-gaap:GoodwillImpairmentLoss" contextRef="FY2023" unitRef="usd" decimals="-6" scale="6" format="ixt:fixed-zero">—</ix:nonFraction></td>
+    <td><ix:nonFraction name="us-gaap:Revenues" contextRef="FY2022" unitRef="usd" decimals="-6" scale="6">394,328</ix:nonFraction></td>
+  </tr></table>
+  <ix:nonNumeric name="us-gaap:RevenueRecognitionPolicyTextBlock" contextRef="FY2023" continuedAt="c1"><p>Net sales consist of products<ix:exclude> (page 12)</ix:exclude></p></ix:nonNumeric>
+  <p>Page break</p>
+  <ix:continuation id="c1"><p>and services.</p></ix:continuation>
+  <p>Number of segments: <ix:nonFraction name="us-gaap:NumberOfReportableSegments" contextRef="FY2023" decimals="INF" format="ixt-sec:numwordsen">five</ix:nonFraction></p>
+</body>
+</html>"#;
+
+    #[test]
+    fn test_parse_ixbrl_to_facts() {
+        let facts = parse_ixbrl_to_facts(DOCUMENT).unwrap();
+        let values = facts
+            .iter()
+            .map(|f| (format!("{}:{}", f.prefix, f.name), f.value.as_str()))
+            .collect::<Vec<_>>();
+
+        // The fact in the FY2022 context, which the resources don't define, is skipped
+        assert_eq!(
+            values,
+            [
+                ("dei:AmendmentFlag".to_string(), "false"),
+                ("dei:DocumentPeriodEndDate".to_string(), "2023-09-30"),
+                ("us-gaap:Revenues".to_string(), "383285000000"),
+                ("us-gaap:Revenues".to_string(), "94294000000"),
+                (
+                    "us-gaap:OtherNonoperatingIncomeExpense".to_string(),
+                    "-565000000"
+                ),
+                ("us-gaap:GoodwillImpairmentLoss".to_string(), "0"),
+                (
+                    "us-gaap:RevenueRecognitionPolicyTextBlock".to_string(),
+                    "Net sales consist of products and services."
+                ),
+                ("us-gaap:NumberOfReportableSegments".to_string(), "5"),
+            ]
+        );
+        assert_eq!(facts[2].units[0].unit_value, "iso4217:USD");
+        assert_eq!(facts[2].periods.len(), 2);
+        assert_eq!(facts[3].dimensions[0].member_name, "EuropeSegmentMember");
+
+        assert_eq!(apply_scale("1.25", -2), "0.0125");
+        assert_eq!(apply_scale("0.5", 3), "500");
+        assert_eq!(
+            number_words("one hundred twenty-one thousand"),
+            Some(121_000)
+        );
+    }
+}
@@ -0,0 +1,782 @@
+// XBRL linkbases published with a filing. The label linkbase (`*_lab.xml`) maps concepts,
+// standard and company extensions alike, to the labels the filer uses for them, so facts and
+// dimension members can be shown as "Net sales" instead of their QNames. The presentation
+// linkbase (`*_pre.xml`) orders the concepts of each statement the way the filer presented
+// them, and the calculation linkbase (`*_cal.xml`) declares which line items add up to which
+// subtotals.
+use roxmltree::Node;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+
+use super::numeric::{Decimal, NumericValue, Precision};
+use super::xbrl::{FactItem, XbrlError};
+
+const XLINK_NAMESPACE: &str = "http://www.w3.org/1999/xlink";
+const LABEL_ROLE: &str = "http://www.xbrl.org/2003/role/label";
+const TERSE_LABEL_ROLE: &str = "http://www.xbrl.org/2003/role/terseLabel";
+/// Label roles of line items whose values are shown with the opposite sign
+const NEGATED_LABEL_ROLES: [&str; 3] = [
+    "http://www.xbrl.org/2003/role/negatedLabel",
+    "http://www.xbrl.org/2003/role/negatedTerseLabel",
+    "http://www.xbrl.org/2003/role/negatedTotalLabel",
+];
+
+/// File name suffixes of the linkbases in a filing
+pub const LABEL_LINKBASE_SUFFIX: &str = "_lab.xml";
+pub const PRESENTATION_LINKBASE_SUFFIX: &str = "_pre.xml";
+pub const CALCULATION_LINKBASE_SUFFIX: &str = "_cal.xml";
+pub const LINKBASE_SUFFIXES: [&str; 3] = [
+    LABEL_LINKBASE_SUFFIX,
+    PRESENTATION_LINKBASE_SUFFIX,
+    CALCULATION_LINKBASE_SUFFIX,
+];
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ConceptLabels {
+    pub label: Option<String>,
+    pub terse_label: Option<String>,
+    /// Labels of the other roles a presentation arc may prefer (total, negated, period start
+    /// and end), keyed by role
+    pub role_labels: HashMap<String, String>,
+}
+
+impl ConceptLabels {
+    /// Terse label if there is one, otherwise the standard label without the type suffix
+    /// of axes and members (`Americas [Member]`)
+    pub fn short(&self) -> Option<String> {
+        self.terse_label.clone().or_else(|| {
+            let label = self.label.as_deref()?;
+            let label = match label.rsplit_once(" [") {
+                Some((label, suffix)) if suffix.ends_with(']') => label,
+                _ => label,
+            };
+            Some(label.to_string())
+        })
+    }
+
+    /// Label of a preferred label role, falling back to the standard label
+    pub fn for_role(&self, role: Option<&str>) -> Option<String> {
+        let label = match role {
+            None | Some(LABEL_ROLE) => None,
+            Some(TERSE_LABEL_ROLE) => self.terse_label.clone(),
+            Some(role) => self.role_labels.get(role).cloned(),
+        };
+        label.or_else(|| self.label.clone())
+    }
+}
+
+/// Labels of a label linkbase, keyed by concept QName (`us-gaap:Revenues`)
+#[derive(Debug, Clone, Default)]
+pub struct Labels {
+    concepts: HashMap<String, ConceptLabels>,
+}
+
+fn xlink<'a>(node: Node<'a, '_>, name: &str) -> Option<&'a str> {
+    node.attribute((XLINK_NAMESPACE, name))
+}
+
+/// QName of the concept a locator points at: `…/us-gaap-2023.xsd#us-gaap_Revenues` is
+/// `us-gaap:Revenues`
+pub(crate) fn locator_concept(href: &str) -> Option<String> {
+    let (_, id) = href.rsplit_once('#')?;
+    let (prefix, name) = id.split_once('_')?;
+    Some(format!("{}:{}", prefix, name))
+}
+
+/// Concepts of the `loc` elements of an extended link, keyed by their `xlink:label`
+pub(crate) fn locators<'a>(link: Node<'a, '_>) -> HashMap<&'a str, Vec<String>> {
+    let mut locators: HashMap<&str, Vec<String>> = HashMap::new();
+    for loc in link
+        .children()
+        .filter(|e| e.is_element() && e.tag_name().name() == "loc")
+    {
+        if let (Some(label), Some(concept)) = (
+            xlink(loc, "label"),
+            xlink(loc, "href").and_then(locator_concept),
+        ) {
+            locators.entry(label).or_default().push(concept);
+        }
+    }
+    locators
+}
+
+impl Labels {
+    pub fn parse(xml: &str) -> Result<Self, XbrlError> {
+        let document = roxmltree::Document::parse(xml)?;
+        let mut labels = Labels::default();
+
+        for link in document
+            .descendants()
+            .filter(|e| e.is_element() && e.tag_name().name() == "labelLink")
+        {
+            let locators = locators(link);
+
+            // Label resources keyed by their `xlink:label`, with their role and language
+            let mut resources: HashMap<&str, Vec<(&str, &str, String)>> = HashMap::new();
+            for label in link
+                .children()
+                .filter(|e| e.is_element() && e.tag_name().name() == "label")
+            {
+                let Some(key) = xlink(label, "label") else {
+                    continue;
+                };
+                let role = xlink(label, "role").unwrap_or(LABEL_ROLE);
+                let lang = label
+                    .attribute(("http://www.w3.org/XML/1998/namespace", "lang"))
+                    .unwrap_or("en");
+                let text = label
+                    .text()
+                    .unwrap_or("")
+                    .split_whitespace()
+                    .collect::<Vec<_>>()
+                    .join(" ");
+                resources.entry(key).or_default().push((role, lang, text));
+            }
+
+            for arc in link
+                .children()
+                .filter(|e| e.is_element() && e.tag_name().name() == "labelArc")
+            {
+                let (Some(from), Some(to)) = (xlink(arc, "from"), xlink(arc, "to")) else {
+                    continue;
+                };
+                let (Some(concepts), Some(resources)) = (locators.get(from), resources.get(to))
+                else {
+                    continue;
+                };
+                for concept in concepts {
+                    let labels = labels.concepts.entry(concept.clone()).or_default();
+                    for (role, lang, text) in resources {
+                        // English labels win over other languages
+                        let english = lang.starts_with("en");
+                        let slot = match *role {
+                            LABEL_ROLE => &mut labels.label,
+                            TERSE_LABEL_ROLE => &mut labels.terse_label,
+                            role => {
+                                let slot = labels
+                                    .role_labels
+                                    .entry(role.to_string())
+                                    .or_insert_with(|| text.clone());
+                                if english {
+                                    *slot = text.clone();
+                                }
+                                continue;
+                            }
+                        };
+                        if slot.is_none() || english {
+                            *slot = Some(text.clone());
+                        }
+                    }
+                }
+            }
+        }
+
+        Ok(labels)
+    }
+
+    pub fn get(&self, prefix: &str, name: &str) -> Option<&ConceptLabels> {
+        self.concepts.get(&format!("{}:{}", prefix, name))
+    }
+
+    pub fn len(&self) -> usize {
+        self.concepts.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.concepts.is_empty()
+    }
+
+    /// Attach the labels of the facts' concepts, axes and members
+    pub fn apply(&self, facts: &mut [FactItem]) {
+        for fact in facts {
+            if let Some(labels) = self.get(&fact.prefix, &fact.name) {
+                fact.label = labels.label.clone();
+                fact.terse_label = labels.terse_label.clone();
+            }
+            for dimension in &mut fact.dimensions {
+                dimension.axis_label = self
+                    .get(&dimension.axis_ns, &dimension.axis_name)
+                    .and_then(ConceptLabels::short);
+                dimension.member_label = self
+                    .get(&dimension.member_ns, &dimension.member_name)
+                    .and_then(ConceptLabels::short);
+            }
+        }
+    }
+}
+
+/// Extended links named `name` (`presentationLink`) with their `xlink:role`
+fn extended_links<'a, 'input>(
+    document: &'a roxmltree::Document<'input>,
+    name: &'a str,
+) -> impl Iterator<Item = (&'a str, Node<'a, 'input>)> + 'a {
+    document
+        .descendants()
+        .filter(move |e| e.is_element() && e.tag_name().name() == name)
+        .map(|link| (xlink(link, "role").unwrap_or_default(), link))
+}
+
+/// Arcs named `name` of an extended link, resolved to the concepts of their locators
+fn concept_arcs<'a, 'input: 'a>(
+    link: Node<'a, 'input>,
+    name: &'a str,
+) -> impl Iterator<Item = (String, String, Node<'a, 'input>)> + 'a {
+    let locators = locators(link);
+    link.children()
+        .filter(move |e| e.is_element() && e.tag_name().name() == name)
+        .flat_map(move |arc| {
+            let from = xlink(arc, "from").and_then(|from| locators.get(from));
+            let to = xlink(arc, "to").and_then(|to| locators.get(to));
+            let pairs: Vec<_> = match (from, to) {
+                (Some(from), Some(to)) => from
+                    .iter()
+                    .flat_map(|from| to.iter().map(move |to| (from.clone(), to.clone(), arc)))
+                    .collect(),
+                _ => Vec::new(),
+            };
+            pairs
+        })
+}
+
+fn arc_order(arc: Node) -> f64 {
+    arc.attribute("order")
+        .and_then(|order| order.parse().ok())
+        .unwrap_or(1.0)
+}
+
+/// Name of a role from its URI: `http://www.apple.com/role/CONSOLIDATEDBALANCESHEETS` is
+/// `CONSOLIDATEDBALANCESHEETS`
+fn role_name(role: &str) -> &str {
+    role.trim_end_matches('/')
+        .rsplit('/')
+        .next()
+        .unwrap_or(role)
+}
+
+/// Title of a role for display, `ConsolidatedStatementsOfOperations` is split into words
+fn role_title(role: &str) -> String {
+    let name = role_name(role);
+    if !name.chars().any(|c| c.is_lowercase()) {
+        return name.to_string();
+    }
+    let mut title = String::new();
+    for c in name.chars() {
+        if c.is_uppercase() && !title.is_empty() && !title.ends_with(' ') {
+            title.push(' ');
+        }
+        title.push(c);
+    }
+    title
+}
+
+/// Whether a role presents a primary financial statement rather than a note, a policy or the
+/// details of either. Roles are only identified by URI in the linkbase, so this goes by the
+/// names filers give them.
+pub fn is_statement_role(role: &str) -> bool {
+    let name = role_name(role).to_lowercase();
+    let statement = [
+        "statement",
+        "balancesheet",
+        "financialposition",
+        "financialcondition",
+    ];
+    let excluded = [
+        "parenthetical",
+        "details",
+        "tables",
+        "policies",
+        "narrative",
+    ];
+    statement.iter().any(|part| name.contains(part))
+        && !excluded.iter().any(|part| name.contains(part))
+}
+
+/// A line of a financial statement: a concept, its label and its indentation level
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub struct StatementLine {
+    pub concept: String,
+    pub label: String,
+    pub depth: usize,
+    /// Presented with a negated label, so values are shown with the opposite sign
+    #[serde(default)]
+    pub negated: bool,
+}
+
+/// The line items of a financial statement in the order the filer presented them
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub struct Statement {
+    pub role: String,
+    pub title: String,
+    pub lines: Vec<StatementLine>,
+}
+
+/// Child concepts of a presentation role, with the order and preferred label role of the arcs
+#[derive(Debug, Clone, Default)]
+struct PresentationTree {
+    roots: Vec<String>,
+    children: HashMap<String, Vec<(f64, String, Option<String>)>>,
+}
+
+impl PresentationTree {
+    fn flatten(
+        &self,
+        concept: &str,
+        preferred_label: Option<&str>,
+        depth: usize,
+        labels: Option<&Labels>,
+        ancestors: &mut Vec<String>,
+        lines: &mut Vec<StatementLine>,
+    ) {
+        // A concept may be presented more than once (cash at the start and the end of the
+        // period), only a concept among its own ancestors is a cycle
+        if ancestors.iter().any(|ancestor| ancestor == concept) {
+            return;
+        }
+        let name = concept.split_once(':').map_or(concept, |(_, name)| name);
+        // Dimensional breakdowns of a statement are rendered from the dimensional facts
+        if name.ends_with("Axis") {
+            return;
+        }
+
+        // The hypercube of a statement and its root abstract only group the line items
+        let structural = ["Table", "LineItems", "Domain", "Member"]
+            .iter()
+            .any(|suffix| name.ends_with(suffix))
+            || (name.ends_with("Abstract") && self.roots.iter().any(|root| root == concept));
+        let child_depth = if structural {
+            depth
+        } else {
+            let label = labels
+                .and_then(|labels| labels.concepts.get(concept))
+                .and_then(|concept_labels| concept_labels.for_role(preferred_label))
+                .unwrap_or_else(|| concept.to_string());
+            lines.push(StatementLine {
+                concept: concept.to_string(),
+                label,
+                depth,
+                negated: preferred_label.is_some_and(|role| NEGATED_LABEL_ROLES.contains(&role)),
+            });
+            depth + 1
+        };
+
+        ancestors.push(concept.to_string());
+        for (_, child, preferred_label) in self.children.get(concept).into_iter().flatten() {
+            self.flatten(
+                child,
+                preferred_label.as_deref(),
+                child_depth,
+                labels,
+                ancestors,
+                lines,
+            );
+        }
+        ancestors.pop();
+    }
+}
+
+/// Presentation trees of a presentation linkbase, one per role in document order
+#[derive(Debug, Clone, Default)]
+pub struct Presentation {
+    roles: Vec<(String, PresentationTree)>,
+}
+
+impl Presentation {
+    pub fn parse(xml: &str) -> Result<Self, XbrlError> {
+        let document = roxmltree::Document::parse(xml)?;
+        let mut presentation = Presentation::default();
+
+        for (role, link) in extended_links(&document, "presentationLink") {
+            let index = match presentation.roles.iter().position(|(r, _)| r == role) {
+                Some(index) => index,
+                None => {
+                    presentation
+                        .roles
+                        .push((role.to_string(), PresentationTree::default()));
+                    presentation.roles.len() - 1
+                }
+            };
+            let tree = &mut presentation.roles[index].1;
+
+            let mut targets = HashSet::new();
+            let mut sources = Vec::new();
+            for (from, to, arc) in concept_arcs(link, "presentationArc") {
+                let preferred_label = arc.attribute("preferredLabel").map(str::to_string);
+                if !sources.contains(&from) {
+                    sources.push(from.clone());
+                }
+                targets.insert(to.clone());
+                tree.children
+                    .entry(from)
+                    .or_default()
+                    .push((arc_order(arc), to, preferred_label));
+            }
+            tree.roots.extend(
+                sources
+                    .into_iter()
+                    .filter(|source| !targets.contains(source)),
+            );
+        }
+
+        for (_, tree) in &mut presentation.roles {
+            for children in tree.children.values_mut() {
+                children.sort_by(|a, b| a.0.total_cmp(&b.0));
+            }
+        }
+
+        Ok(presentation)
+    }
+
+    /// The financial statements among the presentation roles, see [`is_statement_role`]
+    pub fn statements(&self, labels: Option<&Labels>) -> Vec<Statement> {
+        self.roles
+            .iter()
+            .filter(|(role, _)| is_statement_role(role))
+            .map(|(role, tree)| {
+                let mut lines = Vec::new();
+                let mut ancestors = Vec::new();
+                for root in &tree.roots {
+                    tree.flatten(root, None, 0, labels, &mut ancestors, &mut lines);
+                }
+                Statement {
+                    role: role.clone(),
+                    title: role_title(role),
+                    lines,
+                }
+            })
+            .filter(|statement| !statement.lines.is_empty())
+            .collect()
+    }
+}
+
+/// A subtotal and the weighted line items that add up to it
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub struct Summation {
+    pub total: String,
+    pub contributors: Vec<(String, Decimal)>,
+}
+
+/// A reported subtotal that differs from the sum of its reported line items
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub struct Inconsistency {
+    pub role: String,
+    pub total: String,
+    pub context_ref: String,
+    pub reported: Decimal,
+    pub computed: Decimal,
+}
+
+/// Summations of a calculation linkbase, keyed by role
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct Calculation {
+    pub roles: Vec<(String, Vec<Summation>)>,
+}
+
+/// Value of a numeric fact rounded to the precision of a subtotal, as XBRL compares
+/// calculations
+fn at_precision(value: Decimal, precision: Option<Precision>) -> Decimal {
+    match precision {
+        Some(Precision::Decimals(decimals)) => value.round(decimals),
+        _ => value,
+    }
+}
+
+impl Calculation {
+    pub fn parse(xml: &str) -> Result<Self, XbrlError> {
+        let document = roxmltree::Document::parse(xml)?;
+        let mut calculation = Calculation::default();
+
+        for (role, link) in extended_links(&document, "calculationLink") {
+            let mut summations: Vec<Summation> = Vec::new();
+            let mut arcs: Vec<_> = concept_arcs(link, "calculationArc").collect();
+            arcs.sort_by(|a, b| arc_order(a.2).total_cmp(&arc_order(b.2)));
+            for (from, to, arc) in arcs {
+                let weight = arc
+                    .attribute("weight")
+                    .and_then(|weight| weight.parse().ok())
+                    .unwrap_or(Decimal::from(1));
+                match summations.iter_mut().find(|s| s.total == from) {
+                    Some(summation) => summation.contributors.push((to, weight)),
+                    None => summations.push(Summation {
+                        total: from,
+                        contributors: vec![(to, weight)],
+                    }),
+                }
+            }
+            calculation.roles.push((role.to_string(), summations));
+        }
+
+        Ok(calculation)
+    }
+
+    /// Subtotals whose reported value differs from the weighted sum of the line items reported
+    /// in the same context and unit. Values are compared at the precision of the subtotal;
+    /// summations without any reported line item are not checked.
+    pub fn check(&self, facts: &[FactItem]) -> Vec<Inconsistency> {
+        // Numeric facts keyed by concept, context and unit
+        let mut numeric: HashMap<(String, &str, Option<&str>), &NumericValue> = HashMap::new();
+        for fact in facts {
+            let (Some(context_ref), Some(value)) = (&fact.context_ref, &fact.numeric) else {
+                continue;
+            };
+            if value.is_nil() {
+                continue;
+            }
+            numeric
+                .entry((
+                    format!("{}:{}", fact.prefix, fact.name),
+                    context_ref.as_str(),
+                    fact.unit_ref.as_deref(),
+                ))
+                .or_insert(value);
+        }
+
+        let mut inconsistencies = Vec::new();
+        for (role, summations) in &self.roles {
+            for summation in summations {
+                for ((concept, context_ref, unit_ref), total) in &numeric {
+                    if *concept != summation.total {
+                        continue;
+                    }
+                    let (Some(reported), precision) = (total.value, total.precision) else {
+                        continue;
+                    };
+                    let contributions: Vec<Decimal> = summation
+                        .contributors
+                        .iter()
+                        .filter_map(|(contributor, weight)| {
+                            let value = numeric
+                                .get(&(contributor.clone(), *context_ref, *unit_ref))?
+                                .value?;
+                            weight.checked_mul(at_precision(value, precision))
+                        })
+                        .collect();
+                    if contributions.is_empty() {
+                        continue;
+                    }
+                    let Some(computed) = contributions
+                        .into_iter()
+                        .try_fold(Decimal::ZERO, Decimal::checked_add)
+                    else {
+                        continue;
+                    };
+                    let computed = at_precision(computed, precision);
+                    let reported = at_precision(reported, precision);
+                    if computed != reported {
+                        inconsistencies.push(Inconsistency {
+                            role: role.clone(),
+                            total: concept.clone(),
+                            context_ref: context_ref.to_string(),
+                            reported,
+                            computed,
+                        });
+                    }
+                }
+            }
+        }
+        inconsistencies.sort_by(|a, b| (&a.total, &a.context_ref).cmp(&(&b.total, &b.context_ref)));
+        inconsistencies.dedup_by(|a, b| a.total == b.total && a.context_ref == b.context_ref);
+        inconsistencies
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::edgar::xbrl::{parse_xml_to_facts, XBRLFiling};
+
+    const LABELS: &str = r#"<?xml version="1.0" encoding="utf-8"?>
+<link:linkbase xmlns:link="http://www.xbrl.org/2003/linkbase" xmlns:xlink="http://www.w3.org/1999/xlink">
+  <link:labelLink xlink:type="extended" xlink:role="http://www.xbrl.org/2003/role/link">
+    <link:loc xlink:type="locator" xlink:href="https://xbrl.fasb.org/us-gaap/2023/elts/us-gaap-2023.xsd#us-gaap_RevenueFromContractWithCustomerExcludingAssessedTax" xlink:label="loc_revenue"/>
+    <link:label xlink:type="resource" xlink:label="lab_revenue" xlink:role="http://www.xbrl.org/2003/role/label" xml:lang="en-US">Revenue from Contract with Customer, Excluding Assessed Tax</link:label>
+    <link:label xlink:type="resource" xlink:label="lab_revenue" xlink:role="http://www.xbrl.org/2003/role/terseLabel" xml:lang="en-US">Net sales</link:label>
+    <link:labelArc xlink:type="arc" xlink:arcrole="http://www.xbrl.org/2003/arcrole/concept-label" xlink:from="loc_revenue" xlink:to="lab_revenue"/>
+    <link:loc xlink:type="locator" xlink:href="https://xbrl.fasb.org/us-gaap/2023/elts/us-gaap-2023.xsd#us-gaap_StatementBusinessSegmentsAxis" xlink:label="loc_segments"/>
+    <link:label xlink:type="resource" xlink:label="lab_segments" xlink:role="http://www.xbrl.org/2003/role/label" xml:lang="en-US">Segments [Axis]</link:label>
+    <link:labelArc xlink:type="arc" xlink:arcrole="http://www.xbrl.org/2003/arcrole/concept-label" xlink:from="loc_segments" xlink:to="lab_segments"/>
+    <link:loc xlink:type="locator" xlink:href="aapl-20230930.xsd#aapl_AmericasSegmentMember" xlink:label="loc_americas"/>
+    <link:label xlink:type="resource" xlink:label="lab_americas" xlink:role="http://www.xbrl.org/2003/role/label" xml:lang="en-US">Americas [Member]</link:label>
+    <link:labelArc xlink:type="arc" xlink:arcrole="http://www.xbrl.org/2003/arcrole/concept-label" xlink:from="loc_americas" xlink:to="lab_americas"/>
+  </link:labelLink>
+</link:linkbase>"#;
+
+    const INSTANCE: &str = r#"<xbrli:xbrl xmlns:xbrli="http://www.xbrl.org/2003/instance" xmlns:xbrldi="http://xbrl.org/2006/xbrldi"
+    xmlns:us-gaap="http://fasb.org/us-gaap/2023" xmlns:aapl="http://www.apple.com/20230930">
+  <xbrli:context id="c1">
+    <xbrli:entity>
+      <xbrli:identifier scheme="http://www.sec.gov/CIK">0000320193</xbrli:identifier>
+      <xbrli:segment><xbrldi:explicitMember dimension="us-gaap:StatementBusinessSegmentsAxis">aapl:AmericasSegmentMember</xbrldi:explicitMember></xbrli:segment>
+    </xbrli:entity>
+    <xbrli:period><xbrli:startDate>2022-09-25</xbrli:startDate><xbrli:endDate>2023-09-30</xbrli:endDate></xbrli:period>
+  </xbrli:context>
+  <us-gaap:RevenueFromContractWithCustomerExcludingAssessedTax contextRef="c1" decimals="-6">162560000000</us-gaap:RevenueFromContractWithCustomerExcludingAssessedTax>
+</xbrli:xbrl>"#;
+
+    #[test]
+    fn test_apply_labels() {
+        let labels = Labels::parse(LABELS).unwrap();
+        assert_eq!(labels.len(), 3);
+
+        let mut facts = parse_xml_to_facts(INSTANCE.to_string()).unwrap();
+        labels.apply(&mut facts);
+        assert_eq!(facts[0].terse_label.as_deref(), Some("Net sales"));
+        assert_eq!(
+            facts[0].display_name(),
+            "Revenue from Contract with Customer, Excluding Assessed Tax \
+             (us-gaap:RevenueFromContractWithCustomerExcludingAssessedTax)"
+        );
+        assert_eq!(facts[0].dimensions[0].axis_display(), "Segments");
+        assert_eq!(facts[0].dimensions[0].member_display(), "Americas");
+    }
+
+    const PRESENTATION: &str = r#"<link:linkbase xmlns:link="http://www.xbrl.org/2003/linkbase" xmlns:xlink="http://www.w3.org/1999/xlink">
+  <link:presentationLink xlink:type="extended" xlink:role="http://www.apple.com/role/CONSOLIDATEDSTATEMENTSOFOPERATIONS">
+    <link:loc xlink:type="locator" xlink:href="us-gaap.xsd#us-gaap_IncomeStatementAbstract" xlink:label="loc_abstract"/>
+    <link:loc xlink:type="locator" xlink:href="us-gaap.xsd#us-gaap_OperatingExpensesAbstract" xlink:label="loc_opex_abstract"/>
+    <link:loc xlink:type="locator" xlink:href="us-gaap.xsd#us-gaap_ResearchAndDevelopmentExpense" xlink:label="loc_rd"/>
+    <link:loc xlink:type="locator" xlink:href="us-gaap.xsd#us-gaap_SellingGeneralAndAdministrativeExpense" xlink:label="loc_sga"/>
+    <link:loc xlink:type="locator" xlink:href="us-gaap.xsd#us-gaap_OperatingExpenses" xlink:label="loc_opex"/>
+    <link:presentationArc xlink:type="arc" xlink:from="loc_abstract" xlink:to="loc_opex_abstract" order="1"/>
+    <link:presentationArc xlink:type="arc" xlink:from="loc_opex_abstract" xlink:to="loc_opex" order="3"/>
+    <link:presentationArc xlink:type="arc" xlink:from="loc_opex_abstract" xlink:to="loc_sga" order="2"/>
+    <link:presentationArc xlink:type="arc" xlink:from="loc_opex_abstract" xlink:to="loc_rd" order="1"/>
+  </link:presentationLink>
+  <link:presentationLink xlink:type="extended" xlink:role="http://www.apple.com/role/OperatingExpensesDetails">
+    <link:loc xlink:type="locator" xlink:href="us-gaap.xsd#us-gaap_OperatingExpenses" xlink:label="loc_opex"/>
+    <link:loc xlink:type="locator" xlink:href="us-gaap.xsd#us-gaap_ResearchAndDevelopmentExpense" xlink:label="loc_rd"/>
+    <link:presentationArc xlink:type="arc" xlink:from="loc_opex" xlink:to="loc_rd" order="1"/>
+  </link:presentationLink>
+</link:linkbase>"#;
+
+    const CALCULATION: &str = r#"<link:linkbase xmlns:link="http://www.xbrl.org/2003/linkbase" xmlns:xlink="http://www.w3.org/1999/xlink">
+  <link:calculationLink xlink:type="extended" xlink:role="http://www.apple.com/role/CONSOLIDATEDSTATEMENTSOFOPERATIONS">
+    <link:loc xlink:type="locator" xlink:href="us-gaap.xsd#us-gaap_ResearchAndDevelopmentExpense" xlink:label="loc_rd"/>
+    <link:loc xlink:type="locator" xlink:href="us-gaap.xsd#us-gaap_SellingGeneralAndAdministrativeExpense" xlink:label="loc_sga"/>
+    <link:loc xlink:type="locator" xlink:href="us-gaap.xsd#us-gaap_OperatingExpenses" xlink:label="loc_opex"/>
+    <link:calculationArc xlink:type="arc" xlink:from="loc_opex" xlink:to="loc_rd" order="1" weight="1.0"/>
+    <link:calculationArc xlink:type="arc" xlink:from="loc_opex" xlink:to="loc_sga" order="2" weight="1.0"/>
+  </link:calculationLink>
+</link:linkbase>"#;
+
+    const OPERATIONS: &str = r#"<xbrli:xbrl xmlns:xbrli="http://www.xbrl.org/2003/instance" xmlns:us-gaap="http://fasb.org/us-gaap/2023">
+  <xbrli:context id="FY2023">
+    <xbrli:entity><xbrli:identifier scheme="http://www.sec.gov/CIK">0000320193</xbrli:identifier></xbrli:entity>
+    <xbrli:period><xbrli:startDate>2022-09-25</xbrli:startDate><xbrli:endDate>2023-09-30</xbrli:endDate></xbrli:period>
+  </xbrli:context>
+  <xbrli:context id="FY2022">
+    <xbrli:entity><xbrli:identifier scheme="http://www.sec.gov/CIK">0000320193</xbrli:identifier></xbrli:entity>
+    <xbrli:period><xbrli:startDate>2021-09-26</xbrli:startDate><xbrli:endDate>2022-09-24</xbrli:endDate></xbrli:period>
+  </xbrli:context>
+  <xbrli:unit id="usd"><xbrli:measure>iso4217:USD</xbrli:measure></xbrli:unit>
+  <us-gaap:ResearchAndDevelopmentExpense contextRef="FY2023" unitRef="usd" decimals="-6">29915000000</us-gaap:ResearchAndDevelopmentExpense>
+  <us-gaap:SellingGeneralAndAdministrativeExpense contextRef="FY2023" unitRef="usd" decimals="-6">24932000000</us-gaap:SellingGeneralAndAdministrativeExpense>
+  <us-gaap:OperatingExpenses contextRef="FY2023" unitRef="usd" decimals="-6">54847000000</us-gaap:OperatingExpenses>
+  <us-gaap:ResearchAndDevelopmentExpense contextRef="FY2022" unitRef="usd" decimals="-6">26251000000</us-gaap:ResearchAndDevelopmentExpense>
+  <us-gaap:SellingGeneralAndAdministrativeExpense contextRef="FY2022" unitRef="usd" decimals="-6">25094000000</us-gaap:SellingGeneralAndAdministrativeExpense>
+  <us-gaap:OperatingExpenses contextRef="FY2022" unitRef="usd" decimals="-6">51000000000</us-gaap:OperatingExpenses>
+</xbrli:xbrl>"#;
+
+    #[test]
+    fn test_statements_and_calculation_checks() {
+        let statements = Presentation::parse(PRESENTATION).unwrap().statements(None);
+        // The details role is not a statement, the root abstract only groups the line items
+        assert_eq!(statements.len(), 1);
+        assert_eq!(statements[0].title, "CONSOLIDATEDSTATEMENTSOFOPERATIONS");
+        let lines: Vec<(&str, usize)> = statements[0]
+            .lines
+            .iter()
+            .map(|line| (line.concept.as_str(), line.depth))
+            .collect();
+        assert_eq!(
+            lines,
+            [
+                ("us-gaap:OperatingExpensesAbstract", 0),
+                ("us-gaap:ResearchAndDevelopmentExpense", 1),
+                ("us-gaap:SellingGeneralAndAdministrativeExpense", 1),
+                ("us-gaap:OperatingExpenses", 1),
+            ]
+        );
+
+        let facts = parse_xml_to_facts(OPERATIONS.to_string()).unwrap();
+        let calculation = Calculation::parse(CALCULATION).unwrap();
+        let inconsistencies = calculation.check(&facts);
+        assert_eq!(inconsistencies.len(), 1);
+        assert_eq!(inconsistencies[0].context_ref, "FY2022");
+        assert_eq!(
+            inconsistencies[0].computed,
+            "51345000000".parse::<Decimal>().unwrap()
+        );
+
+        let markdown = XBRLFiling {
+            raw_facts: Some(facts),
+            fact_table: None,
+            dimensions: None,
+            statements: Some(statements),
+            calculation: Some(calculation),
+        }
+        .to_markdown();
+        assert!(markdown.contains("| Line item | 2022-09-25/2023-09-30 | 2021-09-26/2022-09-24 |"));
+        assert!(markdown.contains(
+            "| &nbsp;&nbsp;us-gaap:ResearchAndDevelopmentExpense | 29915000000 | 26251000000 |"
+        ));
+        // Statement facts are not repeated as standalone facts
+        assert!(!markdown.contains("# Facts"));
+        assert!(markdown
+            .contains("us-gaap:OperatingExpenses (2021-09-26/2022-09-24): reported 51000000000"));
+    }
+
+    const CASH_FLOW_LABELS: &str = r#"<link:linkbase xmlns:link="http://www.xbrl.org/2003/linkbase" xmlns:xlink="http://www.w3.org/1999/xlink">
+  <link:labelLink xlink:type="extended" xlink:role="http://www.xbrl.org/2003/role/link">
+    <link:loc xlink:type="locator" xlink:href="us-gaap.xsd#us-gaap_Cash" xlink:label="loc_cash"/>
+    <link:label xlink:type="resource" xlink:label="lab_cash" xlink:role="http://www.xbrl.org/2003/role/label" xml:lang="en-US">Cash</link:label>
+    <link:label xlink:type="resource" xlink:label="lab_cash" xlink:role="http://www.xbrl.org/2003/role/periodStartLabel" xml:lang="en-US">Cash, beginning balance</link:label>
+    <link:label xlink:type="resource" xlink:label="lab_cash" xlink:role="http://www.xbrl.org/2003/role/periodEndLabel" xml:lang="en-US">Cash, ending balance</link:label>
+    <link:labelArc xlink:type="arc" xlink:from="loc_cash" xlink:to="lab_cash"/>
+    <link:loc xlink:type="locator" xlink:href="us-gaap.xsd#us-gaap_PaymentsToAcquirePropertyPlantAndEquipment" xlink:label="loc_capex"/>
+    <link:label xlink:type="resource" xlink:label="lab_capex" xlink:role="http://www.xbrl.org/2003/role/label" xml:lang="en-US">Payments to Acquire Property, Plant, and Equipment</link:label>
+    <link:label xlink:type="resource" xlink:label="lab_capex" xlink:role="http://www.xbrl.org/2003/role/negatedLabel" xml:lang="en-US">Capital expenditures</link:label>
+    <link:labelArc xlink:type="arc" xlink:from="loc_capex" xlink:to="lab_capex"/>
+    <link:loc xlink:type="locator" xlink:href="us-gaap.xsd#us-gaap_CashPeriodIncreaseDecrease" xlink:label="loc_change"/>
+    <link:label xlink:type="resource" xlink:label="lab_change" xlink:role="http://www.xbrl.org/2003/role/label" xml:lang="en-US">Cash, Period Increase (Decrease)</link:label>
+    <link:label xlink:type="resource" xlink:label="lab_change" xlink:role="http://www.xbrl.org/2003/role/totalLabel" xml:lang="en-US">Change in cash</link:label>
+    <link:labelArc xlink:type="arc" xlink:from="loc_change" xlink:to="lab_change"/>
+  </link:labelLink>
+</link:linkbase>"#;
+
+    const CASH_FLOW_PRESENTATION: &str = r#"<link:linkbase xmlns:link="http://www.xbrl.org/2003/linkbase" xmlns:xlink="http://www.w3.org/1999/xlink">
+  <link:presentationLink xlink:type="extended" xlink:role="http://www.example.com/role/CONSOLIDATEDSTATEMENTSOFCASHFLOWS">
+    <link:loc xlink:type="locator" xlink:href="us-gaap.xsd#us-gaap_StatementOfCashFlowsAbstract" xlink:label="loc_abstract"/>
+    <link:loc xlink:type="locator" xlink:href="us-gaap.xsd#us-gaap_Cash" xlink:label="loc_cash"/>
+    <link:loc xlink:type="locator" xlink:href="us-gaap.xsd#us-gaap_PaymentsToAcquirePropertyPlantAndEquipment" xlink:label="loc_capex"/>
+    <link:loc xlink:type="locator" xlink:href="us-gaap.xsd#us-gaap_CashPeriodIncreaseDecrease" xlink:label="loc_change"/>
+    <link:presentationArc xlink:type="arc" xlink:from="loc_abstract" xlink:to="loc_cash" order="1" preferredLabel="http://www.xbrl.org/2003/role/periodStartLabel"/>
+    <link:presentationArc xlink:type="arc" xlink:from="loc_abstract" xlink:to="loc_capex" order="2" preferredLabel="http://www.xbrl.org/2003/role/negatedLabel"/>
+    <link:presentationArc xlink:type="arc" xlink:from="loc_abstract" xlink:to="loc_change" order="3" preferredLabel="http://www.xbrl.org/2003/role/totalLabel"/>
+    <link:presentationArc xlink:type="arc" xlink:from="loc_abstract" xlink:to="loc_cash" order="4" preferredLabel="http://www.xbrl.org/2003/role/periodEndLabel"/>
+    <link:presentationArc xlink:type="arc" xlink:from="loc_change" xlink:to="loc_change" order="1"/>
+  </link:presentationLink>
+</link:linkbase>"#;
+
+    #[test]
+    fn test_statement_preferred_labels() {
+        let labels = Labels::parse(CASH_FLOW_LABELS).unwrap();
+        let statements = Presentation::parse(CASH_FLOW_PRESENTATION)
+            .unwrap()
+            .statements(Some(&labels));
+        // Cash is presented at both ends of the period, the self-referencing arc is a cycle
+        let lines: Vec<(&str, bool)> = statements[0]
+            .lines
+            .iter()
+            .map(|line| (line.label.as_str(), line.negated))
+            .collect();
+        assert_eq!(
+            lines,
+            [
+                ("Cash, beginning balance", false),
+                ("Capital expenditures", true),
+                ("Change in cash", false),
+                ("Cash, ending balance", false),
+            ]
+        );
+    }
+}
@@ -1,14 +1,22 @@
-use once_cell::sync::OnceCell;
-use crate::utils::rate_limit::RateLimiter;
-
+pub mod bulk;
+pub mod client;
+pub mod datasets;
+pub mod exhibits;
+pub mod facts;
 pub mod filing;
+pub mod fiscal;
+pub mod form13f;
+pub mod holdings;
+pub mod html;
+pub mod items;
+pub mod ixbrl;
+pub mod linkbase;
+pub mod numeric;
+pub mod ownership;
+pub mod profile;
 pub mod query;
 pub mod report;
+pub mod sections;
+pub mod ticker_index;
 pub mod tickers;
 pub mod xbrl;
-
-static RATE_LIMITER: OnceCell<RateLimiter> = OnceCell::new();
-
-pub(crate) fn rate_limiter() -> &'static RateLimiter {
-    RATE_LIMITER.get_or_init(|| RateLimiter::new(10))
-}
@@ -0,0 +1,380 @@
+// Typed values of numeric XBRL facts. Values are exact decimals rather than floats, so line
+// items add up to their subtotals exactly, and carry the precision they were reported at
+// (`decimals`) and their unit of measure, divide units like USD per share included.
+use serde::{Deserialize, Serialize};
+use std::cmp::Ordering;
+use std::fmt;
+use std::ops::Neg;
+use std::str::FromStr;
+
+use super::xbrl::Unit;
+
+/// Exact decimal number, `mantissa` × 10^-`scale`. Trailing zeros after the decimal point are
+/// dropped, so equal numbers have equal representations.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct Decimal {
+    mantissa: i128,
+    scale: u32,
+}
+
+fn power_of_ten(exponent: u32) -> Option<i128> {
+    10i128.checked_pow(exponent)
+}
+
+impl Decimal {
+    pub const ZERO: Decimal = Decimal {
+        mantissa: 0,
+        scale: 0,
+    };
+
+    fn new(mut mantissa: i128, mut scale: u32) -> Self {
+        while scale > 0 && mantissa % 10 == 0 {
+            mantissa /= 10;
+            scale -= 1;
+        }
+        Decimal { mantissa, scale }
+    }
+
+    /// Mantissa of the number at a scale at least its own
+    fn mantissa_at(self, scale: u32) -> Option<i128> {
+        self.mantissa
+            .checked_mul(power_of_ten(scale.checked_sub(self.scale)?)?)
+    }
+
+    pub fn is_zero(self) -> bool {
+        self.mantissa == 0
+    }
+
+    pub fn is_negative(self) -> bool {
+        self.mantissa < 0
+    }
+
+    pub fn abs(self) -> Self {
+        Decimal::new(self.mantissa.abs(), self.scale)
+    }
+
+    pub fn checked_add(self, other: Decimal) -> Option<Decimal> {
+        let scale = self.scale.max(other.scale);
+        let mantissa = self
+            .mantissa_at(scale)?
+            .checked_add(other.mantissa_at(scale)?)?;
+        Some(Decimal::new(mantissa, scale))
+    }
+
+    pub fn checked_sub(self, other: Decimal) -> Option<Decimal> {
+        self.checked_add(-other)
+    }
+
+    pub fn checked_mul(self, other: Decimal) -> Option<Decimal> {
+        let mantissa = self.mantissa.checked_mul(other.mantissa)?;
+        Some(Decimal::new(mantissa, self.scale.checked_add(other.scale)?))
+    }
+
+    /// Rounded half away from zero to `decimals` places; negative `decimals` round to tens,
+    /// hundreds, and so on, as in the `decimals` attribute of a fact
+    pub fn round(self, decimals: i32) -> Decimal {
+        let dropped = self.scale as i64 - decimals as i64;
+        if dropped <= 0 {
+            return self;
+        }
+        let Some(divisor) = u32::try_from(dropped).ok().and_then(power_of_ten) else {
+            return Decimal::ZERO;
+        };
+        let mut quotient = self.mantissa / divisor;
+        let remainder = (self.mantissa % divisor).abs();
+        if remainder >= divisor - remainder {
+            quotient += self.mantissa.signum();
+        }
+        if decimals >= 0 {
+            Decimal::new(quotient, decimals as u32)
+        } else {
+            let mantissa = power_of_ten(decimals.unsigned_abs())
+                .and_then(|factor| quotient.checked_mul(factor))
+                .unwrap_or(0);
+            Decimal::new(mantissa, 0)
+        }
+    }
+
+    /// Nearest `f64`, for ratios and charts; comparisons should stay on the decimal
+    pub fn to_f64(self) -> f64 {
+        self.to_string().parse().unwrap_or(f64::NAN)
+    }
+}
+
+impl Neg for Decimal {
+    type Output = Decimal;
+
+    fn neg(self) -> Decimal {
+        Decimal::new(-self.mantissa, self.scale)
+    }
+}
+
+impl Ord for Decimal {
+    fn cmp(&self, other: &Self) -> Ordering {
+        let scale = self.scale.max(other.scale);
+        match (self.mantissa_at(scale), other.mantissa_at(scale)) {
+            (Some(a), Some(b)) => a.cmp(&b),
+            // Only numbers near the limits of i128 overflow when aligned
+            _ => self.to_f64().total_cmp(&other.to_f64()),
+        }
+    }
+}
+
+impl PartialOrd for Decimal {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl From<i64> for Decimal {
+    fn from(value: i64) -> Self {
+        Decimal::new(value as i128, 0)
+    }
+}
+
+/// Lexical `xs:decimal` (`-1234.50`), an exponent (`1.5E3`) is accepted as well
+impl FromStr for Decimal {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || format!("Invalid decimal number: {}", s);
+        let number = s.trim();
+        let (number, exponent) = match number.split_once(['e', 'E']) {
+            Some((number, exponent)) => (number, exponent.parse::<i64>().map_err(|_| invalid())?),
+            None => (number, 0),
+        };
+        let (negative, number) = match number.strip_prefix('-') {
+            Some(number) => (true, number),
+            None => (false, number.strip_prefix('+').unwrap_or(number)),
+        };
+        let (int, frac) = number.split_once('.').unwrap_or((number, ""));
+        let frac = frac.trim_end_matches('0');
+        if (int.is_empty() && frac.is_empty())
+            || !int.chars().chain(frac.chars()).all(|c| c.is_ascii_digit())
+        {
+            return Err(invalid());
+        }
+
+        let digits = format!("{}{}", int, frac);
+        let digits = match digits.trim_start_matches('0') {
+            "" => "0",
+            digits => digits,
+        };
+        let mut mantissa: i128 = digits.parse().map_err(|_| invalid())?;
+        if negative {
+            mantissa = -mantissa;
+        }
+
+        let scale = frac.len() as i64 - exponent;
+        if scale >= 0 {
+            Ok(Decimal::new(
+                mantissa,
+                u32::try_from(scale).map_err(|_| invalid())?,
+            ))
+        } else {
+            let factor = u32::try_from(-scale)
+                .ok()
+                .and_then(power_of_ten)
+                .ok_or_else(invalid)?;
+            Ok(Decimal::new(
+                mantissa.checked_mul(factor).ok_or_else(invalid)?,
+                0,
+            ))
+        }
+    }
+}
+
+impl fmt::Display for Decimal {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let digits = self.mantissa.unsigned_abs().to_string();
+        let sign = if self.is_negative() { "-" } else { "" };
+        let scale = self.scale as usize;
+        if scale == 0 {
+            write!(f, "{}{}", sign, digits)
+        } else if digits.len() > scale {
+            let (int, frac) = digits.split_at(digits.len() - scale);
+            write!(f, "{}{}.{}", sign, int, frac)
+        } else {
+            write!(
+                f,
+                "{}0.{}{}",
+                sign,
+                "0".repeat(scale - digits.len()),
+                digits
+            )
+        }
+    }
+}
+
+impl TryFrom<String> for Decimal {
+    type Error = String;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        value.parse()
+    }
+}
+
+impl From<Decimal> for String {
+    fn from(value: Decimal) -> Self {
+        value.to_string()
+    }
+}
+
+/// Precision of a numeric fact, from its `decimals` attribute
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Precision {
+    /// Accurate to this many decimal places, negative for thousands (-3), millions (-6), ...
+    Decimals(i32),
+    /// `INF`, the value is exact
+    Exact,
+}
+
+impl Precision {
+    pub fn parse(decimals: &str) -> Option<Self> {
+        match decimals.trim() {
+            "INF" => Some(Precision::Exact),
+            decimals => decimals.parse().ok().map(Precision::Decimals),
+        }
+    }
+}
+
+/// Unit of measure of a numeric fact: the product of its measures, divided by the product of
+/// its denominator measures for ratios like USD per share
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct NumericUnit {
+    /// Measure QNames, `iso4217:USD`, `xbrli:shares`, `xbrli:pure`
+    pub numerator: Vec<String>,
+    pub denominator: Vec<String>,
+}
+
+fn measure_name(measure: &str) -> &str {
+    measure.rsplit(':').next().unwrap_or(measure)
+}
+
+impl NumericUnit {
+    /// Unit from the measures of a `unit` element, as collected by the instance parser
+    pub fn from_units(units: &[Unit]) -> Self {
+        let mut unit = NumericUnit::default();
+        for measure in units {
+            if measure.unit_type == "unitDenominator" {
+                unit.denominator.push(measure.unit_value.clone());
+            } else {
+                unit.numerator.push(measure.unit_value.clone());
+            }
+        }
+        unit
+    }
+
+    /// ISO 4217 code of the currency of a monetary amount or a per-share amount
+    pub fn currency(&self) -> Option<&str> {
+        self.numerator
+            .iter()
+            .find_map(|measure| measure.strip_prefix("iso4217:"))
+    }
+
+    pub fn is_monetary(&self) -> bool {
+        self.currency().is_some() && self.denominator.is_empty()
+    }
+
+    pub fn is_per_share(&self) -> bool {
+        self.currency().is_some()
+            && self
+                .denominator
+                .iter()
+                .any(|measure| measure_name(measure) == "shares")
+    }
+}
+
+/// `USD`, `shares`, `USD/shares`
+impl fmt::Display for NumericUnit {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let product = |measures: &[String]| {
+            measures
+                .iter()
+                .map(|measure| measure_name(measure))
+                .collect::<Vec<_>>()
+                .join("*")
+        };
+        write!(f, "{}", product(&self.numerator))?;
+        if !self.denominator.is_empty() {
+            write!(f, "/{}", product(&self.denominator))?;
+        }
+        Ok(())
+    }
+}
+
+/// Value of a numeric fact, with its precision and unit
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct NumericValue {
+    /// `None` for facts reported as nil (`xsi:nil="true"`)
+    pub value: Option<Decimal>,
+    /// `None` when the fact has no `decimals` attribute
+    pub precision: Option<Precision>,
+    pub unit: NumericUnit,
+}
+
+impl NumericValue {
+    pub fn is_nil(&self) -> bool {
+        self.value.is_none()
+    }
+
+    /// Value at the precision it was reported at, which is how XBRL compares values
+    pub fn rounded(&self) -> Option<Decimal> {
+        let value = self.value?;
+        Some(match self.precision {
+            Some(Precision::Decimals(decimals)) => value.round(decimals),
+            _ => value,
+        })
+    }
+
+    pub fn to_f64(&self) -> Option<f64> {
+        self.value.map(Decimal::to_f64)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_decimal_arithmetic() {
+        let value: Decimal = "-1234.500".parse().unwrap();
+        assert_eq!(value.to_string(), "-1234.5");
+        assert_eq!("1.5E3".parse::<Decimal>().unwrap(), Decimal::from(1500));
+        assert_eq!("0.0125".parse::<Decimal>().unwrap().to_string(), "0.0125");
+        assert!("1,234".parse::<Decimal>().is_err());
+        assert!("".parse::<Decimal>().is_err());
+
+        // 0.1 + 0.2 is exactly 0.3, unlike in floating point
+        let sum = "0.1"
+            .parse::<Decimal>()
+            .unwrap()
+            .checked_add("0.2".parse().unwrap())
+            .unwrap();
+        assert_eq!(sum, "0.3".parse().unwrap());
+        assert!(value < sum);
+
+        let revenue: Decimal = "383285499999".parse().unwrap();
+        assert_eq!(revenue.round(-6).to_string(), "383285000000");
+        assert_eq!(value.round(0), Decimal::from(-1235));
+        assert_eq!(
+            serde_json::to_string(&value).unwrap(),
+            r#""-1234.5""#.to_string()
+        );
+
+        let eps = NumericUnit::from_units(&[
+            Unit {
+                unit_type: "unitNumerator".to_string(),
+                unit_value: "iso4217:USD".to_string(),
+            },
+            Unit {
+                unit_type: "unitDenominator".to_string(),
+                unit_value: "xbrli:shares".to_string(),
+            },
+        ]);
+        assert_eq!(eps.to_string(), "USD/shares");
+        assert_eq!(eps.currency(), Some("USD"));
+        assert!(eps.is_per_share() && !eps.is_monetary());
+    }
+}
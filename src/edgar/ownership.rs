@@ -0,0 +1,839 @@
+// Parser for the ownership XML of Forms 3, 4 and 5 (insider holdings and transactions),
+// plus a per-insider summary of open market activity that is fed into the LLM context.
+use anyhow::{anyhow, Result};
+use chrono::NaiveDate;
+use roxmltree::Node;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+use std::{fmt, str::FromStr};
+use strum::{EnumIter, IntoEnumIterator};
+
+use super::client::EdgarClient;
+
+/// File name of the parsed ownership document, next to the other parsed filing artifacts
+pub const OWNERSHIP_JSON: &str = "ownership.json";
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct OwnershipDocument {
+    pub document_type: String,
+    pub period_of_report: Option<NaiveDate>,
+    /// Filing date of the document an amendment (`4/A`) amends
+    #[serde(default)]
+    pub date_of_original_submission: Option<NaiveDate>,
+    /// Filing date from the submissions, set when the filing is ingested
+    #[serde(default)]
+    pub filing_date: Option<NaiveDate>,
+    pub issuer: Issuer,
+    pub reporting_owners: Vec<ReportingOwner>,
+    pub non_derivative_transactions: Vec<Transaction>,
+    pub derivative_transactions: Vec<Transaction>,
+    pub footnotes: HashMap<String, String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Issuer {
+    pub cik: String,
+    pub name: String,
+    pub trading_symbol: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ReportingOwner {
+    pub cik: String,
+    pub name: String,
+    pub relationship: Relationship,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Relationship {
+    pub is_director: bool,
+    pub is_officer: bool,
+    pub is_ten_percent_owner: bool,
+    pub is_other: bool,
+    pub officer_title: Option<String>,
+    pub other_text: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Transaction {
+    pub security_title: String,
+    pub date: NaiveDate,
+    pub code: TransactionCode,
+    pub shares: f64,
+    pub price_per_share: Option<f64>,
+    pub acquired_disposed: AcquiredDisposed,
+    pub shares_owned_following: Option<f64>,
+    pub direct_or_indirect: Option<String>,
+    /// Derivative transactions only
+    pub exercise_price: Option<f64>,
+    pub underlying_security_title: Option<String>,
+    pub underlying_shares: Option<f64>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum AcquiredDisposed {
+    Acquired,
+    Disposed,
+}
+
+/// Form 4 transaction codes
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, EnumIter)]
+#[serde(try_from = "String", into = "String")]
+pub enum TransactionCode {
+    Purchase,
+    Sale,
+    VoluntaryReport,
+    Grant,
+    DispositionToIssuer,
+    TaxWithholding,
+    Discretionary,
+    DerivativeExercise,
+    Conversion,
+    ShortExpiration,
+    LongExpiration,
+    OutOfTheMoneyExercise,
+    InTheMoneyExercise,
+    Gift,
+    SmallAcquisition,
+    Inheritance,
+    VotingTrust,
+    OtherAcquisitionOrDisposition,
+    EquitySwap,
+    TenderOffer,
+    Other(String),
+}
+
+impl TransactionCode {
+    pub fn code(&self) -> &str {
+        match self {
+            TransactionCode::Purchase => "P",
+            TransactionCode::Sale => "S",
+            TransactionCode::VoluntaryReport => "V",
+            TransactionCode::Grant => "A",
+            TransactionCode::DispositionToIssuer => "D",
+            TransactionCode::TaxWithholding => "F",
+            TransactionCode::Discretionary => "I",
+            TransactionCode::DerivativeExercise => "M",
+            TransactionCode::Conversion => "C",
+            TransactionCode::ShortExpiration => "E",
+            TransactionCode::LongExpiration => "H",
+            TransactionCode::OutOfTheMoneyExercise => "O",
+            TransactionCode::InTheMoneyExercise => "X",
+            TransactionCode::Gift => "G",
+            TransactionCode::SmallAcquisition => "L",
+            TransactionCode::Inheritance => "W",
+            TransactionCode::VotingTrust => "Z",
+            TransactionCode::OtherAcquisitionOrDisposition => "J",
+            TransactionCode::EquitySwap => "K",
+            TransactionCode::TenderOffer => "U",
+            TransactionCode::Other(code) => code,
+        }
+    }
+
+    pub fn description(&self) -> &str {
+        match self {
+            TransactionCode::Purchase => "Open market or private purchase",
+            TransactionCode::Sale => "Open market or private sale",
+            TransactionCode::VoluntaryReport => {
+                "Transaction voluntarily reported earlier than required"
+            }
+            TransactionCode::Grant => "Grant, award or other acquisition",
+            TransactionCode::DispositionToIssuer => "Disposition to the issuer",
+            TransactionCode::TaxWithholding => {
+                "Payment of exercise price or tax liability with securities"
+            }
+            TransactionCode::Discretionary => "Discretionary transaction",
+            TransactionCode::DerivativeExercise => "Exercise or conversion of derivative security",
+            TransactionCode::Conversion => "Conversion of derivative security",
+            TransactionCode::ShortExpiration => "Expiration of short derivative position",
+            TransactionCode::LongExpiration => "Expiration of long derivative position",
+            TransactionCode::OutOfTheMoneyExercise => "Exercise of out-of-the-money derivative",
+            TransactionCode::InTheMoneyExercise => "Exercise of in-the-money derivative",
+            TransactionCode::Gift => "Bona fide gift",
+            TransactionCode::SmallAcquisition => "Small acquisition",
+            TransactionCode::Inheritance => "Acquisition or disposition by will or laws of descent",
+            TransactionCode::VotingTrust => "Deposit into or withdrawal from voting trust",
+            TransactionCode::OtherAcquisitionOrDisposition => "Other acquisition or disposition",
+            TransactionCode::EquitySwap => "Equity swap",
+            TransactionCode::TenderOffer => "Disposition in a change of control tender",
+            TransactionCode::Other(_) => "Unknown transaction code",
+        }
+    }
+}
+
+impl fmt::Display for TransactionCode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.code())
+    }
+}
+
+impl FromStr for TransactionCode {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let code = s.trim().to_uppercase();
+        if code.is_empty() {
+            return Err("Empty transaction code".to_string());
+        }
+        Ok(TransactionCode::iter()
+            .find(|c| c.code() == code)
+            .unwrap_or(TransactionCode::Other(code)))
+    }
+}
+
+impl TryFrom<String> for TransactionCode {
+    type Error = String;
+
+    fn try_from(s: String) -> Result<Self, Self::Error> {
+        TransactionCode::from_str(&s)
+    }
+}
+
+impl From<TransactionCode> for String {
+    fn from(code: TransactionCode) -> Self {
+        code.code().to_string()
+    }
+}
+
+impl Relationship {
+    pub fn describe(&self) -> String {
+        let mut roles = Vec::new();
+        if self.is_director {
+            roles.push("Director".to_string());
+        }
+        if self.is_officer {
+            roles.push(
+                self.officer_title
+                    .clone()
+                    .unwrap_or_else(|| "Officer".to_string()),
+            );
+        }
+        if self.is_ten_percent_owner {
+            roles.push("10% Owner".to_string());
+        }
+        if self.is_other {
+            roles.push(
+                self.other_text
+                    .clone()
+                    .unwrap_or_else(|| "Other".to_string()),
+            );
+        }
+        roles.join(", ")
+    }
+}
+
+fn child<'a, 'input>(node: Node<'a, 'input>, name: &str) -> Option<Node<'a, 'input>> {
+    node.children()
+        .find(|n| n.is_element() && n.tag_name().name() == name)
+}
+
+/// Text of a nested element path; ownership values are usually wrapped in a `<value>` element
+fn text_at(node: Node, path: &[&str]) -> Option<String> {
+    let mut current = node;
+    for name in path {
+        current = child(current, name)?;
+    }
+    let target = child(current, "value").unwrap_or(current);
+    target
+        .text()
+        .map(|t| t.trim().to_string())
+        .filter(|t| !t.is_empty())
+}
+
+fn number_at(node: Node, path: &[&str]) -> Option<f64> {
+    text_at(node, path).and_then(|t| t.replace(',', "").parse().ok())
+}
+
+fn flag_at(node: Node, path: &[&str]) -> bool {
+    text_at(node, path).is_some_and(|t| t == "1" || t.eq_ignore_ascii_case("true"))
+}
+
+/// Dates are `YYYY-MM-DD`, occasionally followed by a timezone offset
+fn parse_date(text: &str) -> Option<NaiveDate> {
+    text.get(..10)
+        .and_then(|d| NaiveDate::parse_from_str(d, "%Y-%m-%d").ok())
+}
+
+fn parse_transaction(node: Node) -> Result<Transaction> {
+    let date = text_at(node, &["transactionDate"])
+        .and_then(|d| parse_date(&d))
+        .ok_or_else(|| anyhow!("Transaction without a valid date"))?;
+    let code = text_at(node, &["transactionCoding", "transactionCode"])
+        .ok_or_else(|| anyhow!("Transaction without a code"))?
+        .parse::<TransactionCode>()
+        .map_err(|e| anyhow!(e))?;
+    let acquired_disposed = match text_at(
+        node,
+        &["transactionAmounts", "transactionAcquiredDisposedCode"],
+    )
+    .as_deref()
+    {
+        Some("A") => AcquiredDisposed::Acquired,
+        Some("D") => AcquiredDisposed::Disposed,
+        other => return Err(anyhow!("Invalid acquired/disposed code: {:?}", other)),
+    };
+
+    Ok(Transaction {
+        security_title: text_at(node, &["securityTitle"]).unwrap_or_default(),
+        date,
+        code,
+        shares: number_at(node, &["transactionAmounts", "transactionShares"]).unwrap_or(0.0),
+        price_per_share: number_at(node, &["transactionAmounts", "transactionPricePerShare"]),
+        acquired_disposed,
+        shares_owned_following: number_at(
+            node,
+            &["postTransactionAmounts", "sharesOwnedFollowingTransaction"],
+        ),
+        direct_or_indirect: text_at(node, &["ownershipNature", "directOrIndirectOwnership"]),
+        exercise_price: number_at(node, &["conversionOrExercisePrice"]),
+        underlying_security_title: text_at(
+            node,
+            &["underlyingSecurity", "underlyingSecurityTitle"],
+        ),
+        underlying_shares: number_at(node, &["underlyingSecurity", "underlyingSecurityShares"]),
+    })
+}
+
+fn parse_transactions(root: Node, table: &str, row: &str) -> Vec<Transaction> {
+    let Some(table) = child(root, table) else {
+        return Vec::new();
+    };
+    table
+        .children()
+        .filter(|n| n.is_element() && n.tag_name().name() == row)
+        .filter_map(|n| match parse_transaction(n) {
+            Ok(transaction) => Some(transaction),
+            Err(e) => {
+                log::warn!("Skipping malformed {}: {}", row, e);
+                None
+            }
+        })
+        .collect()
+}
+
+/// Parse a Form 3/4/5 ownership XML document
+pub fn parse_ownership_xml(xml: &str) -> Result<OwnershipDocument> {
+    let doc = roxmltree::Document::parse(xml)
+        .map_err(|e| anyhow!("Failed to parse ownership XML: {}", e))?;
+    let root = doc.root_element();
+    if root.tag_name().name() != "ownershipDocument" {
+        return Err(anyhow!(
+            "Not an ownership document: <{}>",
+            root.tag_name().name()
+        ));
+    }
+
+    let issuer = child(root, "issuer").ok_or_else(|| anyhow!("Missing issuer"))?;
+    let issuer = Issuer {
+        cik: text_at(issuer, &["issuerCik"]).unwrap_or_default(),
+        name: text_at(issuer, &["issuerName"]).unwrap_or_default(),
+        trading_symbol: text_at(issuer, &["issuerTradingSymbol"]).unwrap_or_default(),
+    };
+
+    let reporting_owners = root
+        .children()
+        .filter(|n| n.is_element() && n.tag_name().name() == "reportingOwner")
+        .map(|owner| ReportingOwner {
+            cik: text_at(owner, &["reportingOwnerId", "rptOwnerCik"]).unwrap_or_default(),
+            name: text_at(owner, &["reportingOwnerId", "rptOwnerName"]).unwrap_or_default(),
+            relationship: Relationship {
+                is_director: flag_at(owner, &["reportingOwnerRelationship", "isDirector"]),
+                is_officer: flag_at(owner, &["reportingOwnerRelationship", "isOfficer"]),
+                is_ten_percent_owner: flag_at(
+                    owner,
+                    &["reportingOwnerRelationship", "isTenPercentOwner"],
+                ),
+                is_other: flag_at(owner, &["reportingOwnerRelationship", "isOther"]),
+                officer_title: text_at(owner, &["reportingOwnerRelationship", "officerTitle"]),
+                other_text: text_at(owner, &["reportingOwnerRelationship", "otherText"]),
+            },
+        })
+        .collect::<Vec<_>>();
+    if reporting_owners.is_empty() {
+        return Err(anyhow!("Ownership document without reporting owners"));
+    }
+
+    let footnotes = child(root, "footnotes")
+        .map(|footnotes| {
+            footnotes
+                .children()
+                .filter(|n| n.is_element() && n.tag_name().name() == "footnote")
+                .filter_map(|n| {
+                    let text = n.descendants().filter_map(|d| d.text()).collect::<String>();
+                    Some((n.attribute("id")?.to_string(), text.trim().to_string()))
+                })
+                .collect()
+        })
+        .unwrap_or_default();
+
+    Ok(OwnershipDocument {
+        document_type: text_at(root, &["documentType"]).unwrap_or_default(),
+        period_of_report: text_at(root, &["periodOfReport"]).and_then(|d| parse_date(&d)),
+        date_of_original_submission: text_at(root, &["dateOfOriginalSubmission"])
+            .and_then(|d| parse_date(&d)),
+        filing_date: None,
+        issuer,
+        reporting_owners,
+        non_derivative_transactions: parse_transactions(
+            root,
+            "nonDerivativeTable",
+            "nonDerivativeTransaction",
+        ),
+        derivative_transactions: parse_transactions(
+            root,
+            "derivativeTable",
+            "derivativeTransaction",
+        ),
+        footnotes,
+    })
+}
+
+fn format_number(value: Option<f64>) -> String {
+    value.map_or_else(|| "-".to_string(), |v| format!("{}", v))
+}
+
+impl OwnershipDocument {
+    pub fn is_amendment(&self) -> bool {
+        self.document_type.ends_with("/A")
+    }
+
+    /// Joined CIKs of the reporting owners, which identify an insider across documents
+    fn owner_ciks(&self) -> String {
+        self.reporting_owners
+            .iter()
+            .map(|o| o.cik.as_str())
+            .collect::<Vec<_>>()
+            .join("/")
+    }
+
+    /// Names of the reporting owners, joint filers are listed together
+    pub fn owner_names(&self) -> String {
+        self.reporting_owners
+            .iter()
+            .map(|o| o.name.as_str())
+            .collect::<Vec<_>>()
+            .join(" / ")
+    }
+
+    pub fn to_markdown(&self) -> String {
+        let mut md = format!(
+            "# Form {} - {} ({})\n\n",
+            self.document_type, self.issuer.name, self.issuer.trading_symbol
+        );
+        if let Some(period) = self.period_of_report {
+            md.push_str(&format!("Period of report: {}\n\n", period));
+        }
+
+        md.push_str("## Reporting Owners\n\n");
+        for owner in &self.reporting_owners {
+            md.push_str(&format!(
+                "- {} (CIK {}): {}\n",
+                owner.name,
+                owner.cik,
+                owner.relationship.describe()
+            ));
+        }
+
+        for (title, transactions) in [
+            (
+                "Non-Derivative Transactions",
+                &self.non_derivative_transactions,
+            ),
+            ("Derivative Transactions", &self.derivative_transactions),
+        ] {
+            if transactions.is_empty() {
+                continue;
+            }
+            md.push_str(&format!("\n## {}\n\n", title));
+            md.push_str(
+                "| Date | Security | Code | Description | A/D | Shares | Price | Owned After | Ownership |\n",
+            );
+            md.push_str("| --- | --- | --- | --- | --- | --- | --- | --- | --- |\n");
+            for t in transactions.iter() {
+                md.push_str(&format!(
+                    "| {} | {} | {} | {} | {} | {} | {} | {} | {} |\n",
+                    t.date,
+                    t.security_title,
+                    t.code,
+                    t.code.description(),
+                    match t.acquired_disposed {
+                        AcquiredDisposed::Acquired => "A",
+                        AcquiredDisposed::Disposed => "D",
+                    },
+                    t.shares,
+                    format_number(t.price_per_share),
+                    format_number(t.shares_owned_following),
+                    t.direct_or_indirect.as_deref().unwrap_or("-"),
+                ));
+            }
+        }
+
+        if !self.footnotes.is_empty() {
+            md.push_str("\n## Footnotes\n\n");
+            let mut footnotes = self.footnotes.iter().collect::<Vec<_>>();
+            footnotes.sort();
+            for (id, text) in footnotes {
+                md.push_str(&format!("- {}: {}\n", id, text));
+            }
+        }
+
+        md
+    }
+}
+
+/// Open market activity of one insider over a date range
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct InsiderActivity {
+    pub owner_cik: String,
+    pub owner_name: String,
+    pub relationship: String,
+    pub shares_bought: f64,
+    pub shares_sold: f64,
+    pub value_bought: f64,
+    pub value_sold: f64,
+    /// Grants, exercises, tax withholdings, gifts and other non open market transactions
+    pub other_transactions: usize,
+    pub last_transaction: Option<NaiveDate>,
+    pub shares_owned: Option<f64>,
+}
+
+impl InsiderActivity {
+    pub fn net_shares(&self) -> f64 {
+        self.shares_bought - self.shares_sold
+    }
+
+    pub fn net_value(&self) -> f64 {
+        self.value_bought - self.value_sold
+    }
+}
+
+/// The documents still in effect: an amendment replaces the document of the same form,
+/// reporting owners and period of report that was filed on its date of original submission,
+/// and a later amendment replaces an earlier one. Documents without the dates to match them
+/// are kept as they are.
+pub fn current_documents(documents: &[OwnershipDocument]) -> Vec<&OwnershipDocument> {
+    let mut current: Vec<&OwnershipDocument> = Vec::new();
+    let mut by_original: HashMap<_, usize> = HashMap::new();
+
+    for document in documents {
+        let submitted = if document.is_amendment() {
+            document.date_of_original_submission
+        } else {
+            document.filing_date
+        };
+        let (Some(submitted), Some(period)) = (submitted, document.period_of_report) else {
+            current.push(document);
+            continue;
+        };
+        let key = (
+            document.document_type.trim_end_matches("/A").to_string(),
+            document.owner_ciks(),
+            period,
+            submitted,
+        );
+        match by_original.get(&key) {
+            Some(&i) => {
+                let kept = current[i];
+                if (document.is_amendment(), document.filing_date)
+                    > (kept.is_amendment(), kept.filing_date)
+                {
+                    current[i] = document;
+                }
+            }
+            None => {
+                by_original.insert(key, current.len());
+                current.push(document);
+            }
+        }
+    }
+    current
+}
+
+/// Aggregate the non-derivative transactions of the given documents per insider, after
+/// amendments replaced the documents they amend (see [`current_documents`]).
+///
+/// Only open market purchases (P) and sales (S) count towards buys and sells, since grants,
+/// option exercises and tax withholdings say little about an insider's view of the stock.
+/// Insiders are sorted by the absolute net value traded.
+pub fn summarize_insider_activity(
+    documents: &[OwnershipDocument],
+    start_date: NaiveDate,
+    end_date: NaiveDate,
+) -> Vec<InsiderActivity> {
+    let mut by_owner: HashMap<String, InsiderActivity> = HashMap::new();
+
+    for document in current_documents(documents) {
+        let owner_cik = document.owner_ciks();
+        let activity = by_owner
+            .entry(owner_cik.clone())
+            .or_insert_with(|| InsiderActivity {
+                owner_cik,
+                owner_name: document.owner_names(),
+                relationship: document.reporting_owners[0].relationship.describe(),
+                ..Default::default()
+            });
+
+        let mut transactions = document
+            .non_derivative_transactions
+            .iter()
+            .filter(|t| t.date >= start_date && t.date <= end_date)
+            .collect::<Vec<_>>();
+        transactions.sort_by_key(|t| t.date);
+
+        for t in transactions {
+            let value = t.shares * t.price_per_share.unwrap_or(0.0);
+            match t.code {
+                TransactionCode::Purchase => {
+                    activity.shares_bought += t.shares;
+                    activity.value_bought += value;
+                }
+                TransactionCode::Sale => {
+                    activity.shares_sold += t.shares;
+                    activity.value_sold += value;
+                }
+                _ => activity.other_transactions += 1,
+            }
+            if activity.last_transaction.is_none_or(|last| t.date >= last) {
+                activity.last_transaction = Some(t.date);
+                if t.shares_owned_following.is_some() {
+                    activity.shares_owned = t.shares_owned_following;
+                }
+            }
+        }
+    }
+
+    let mut activity = by_owner
+        .into_values()
+        .filter(|a| a.last_transaction.is_some())
+        .collect::<Vec<_>>();
+    activity.sort_by(|a, b| b.net_value().abs().total_cmp(&a.net_value().abs()));
+    activity
+}
+
+pub fn insider_activity_markdown(
+    symbol: &str,
+    start_date: NaiveDate,
+    end_date: NaiveDate,
+    activity: &[InsiderActivity],
+) -> String {
+    let mut md = format!(
+        "# {} Insider Activity {} to {}\n\n",
+        symbol, start_date, end_date
+    );
+    if activity.is_empty() {
+        md.push_str("No insider transactions reported in this period.\n");
+        return md;
+    }
+
+    md.push_str("| Insider | Relationship | Bought | Sold | Net Shares | Net Value | Other Transactions | Owned After | Last Transaction |\n");
+    md.push_str("| --- | --- | --- | --- | --- | --- | --- | --- | --- |\n");
+    for a in activity {
+        md.push_str(&format!(
+            "| {} | {} | {} | {} | {} | {:.2} | {} | {} | {} |\n",
+            a.owner_name,
+            a.relationship,
+            a.shares_bought,
+            a.shares_sold,
+            a.net_shares(),
+            a.net_value(),
+            a.other_transactions,
+            format_number(a.shares_owned),
+            a.last_transaction
+                .map_or("-".to_string(), |d| d.to_string()),
+        ));
+    }
+
+    let net_value: f64 = activity.iter().map(|a| a.net_value()).sum();
+    md.push_str(&format!(
+        "\nNet open market value across insiders: {:.2}\n",
+        net_value
+    ));
+    md
+}
+
+/// Load every parsed ownership document of a company from the parsed filings directory
+pub fn load_ownership_documents(client: &EdgarClient, cik: &str) -> Result<Vec<OwnershipDocument>> {
+    let company_dir = client.parsed_dir().join(cik);
+    if !company_dir.exists() {
+        return Ok(Vec::new());
+    }
+
+    let mut documents = Vec::new();
+    for entry in fs::read_dir(company_dir)? {
+        let path = entry?.path().join(OWNERSHIP_JSON);
+        if !path.exists() {
+            continue;
+        }
+        match serde_json::from_str(&fs::read_to_string(&path)?) {
+            Ok(document) => documents.push(document),
+            Err(e) => log::warn!("Failed to load {}: {}", path.display(), e),
+        }
+    }
+    Ok(documents)
+}
+
+/// Insider activity summary for a ticker, from the ownership filings fetched so far
+pub async fn insider_activity_for_ticker(
+    client: &EdgarClient,
+    ticker: &str,
+    start_date: NaiveDate,
+    end_date: NaiveDate,
+) -> Result<String> {
+    let cik = super::tickers::resolve_cik(client, ticker).await?;
+    let documents = load_ownership_documents(client, &cik)?;
+    let activity = summarize_insider_activity(&documents, start_date, end_date);
+    Ok(insider_activity_markdown(
+        ticker, start_date, end_date, &activity,
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const FORM4: &str = r#"<?xml version="1.0"?>
+<ownershipDocument>
+    <schemaVersion>X0508</schemaVersion>
+    <documentType>4</documentType>
+    <periodOfReport>2024-10-01</periodOfReport>
+    <issuer>
+        <issuerCik>0000320193</issuerCik>
+        <issuerName>Apple Inc.</issuerName>
+        <issuerTradingSymbol>AAPL</issuerTradingSymbol>
+    </issuer>
+    <reportingOwner>
+        <reportingOwnerId>
+            <rptOwnerCik>0001631982</rptOwnerCik>
+            <rptOwnerName>Adams Katherine L.</rptOwnerName>
+        </reportingOwnerId>
+        <reportingOwnerRelationship>
+            <isDirector>0</isDirector>
+            <isOfficer>1</isOfficer>
+            <officerTitle>SVP, GC and Secretary</officerTitle>
+        </reportingOwnerRelationship>
+    </reportingOwner>
+    <nonDerivativeTable>
+        <nonDerivativeTransaction>
+            <securityTitle><value>Common Stock</value></securityTitle>
+            <transactionDate><value>2024-10-01</value></transactionDate>
+            <transactionCoding><transactionFormType>4</transactionFormType><transactionCode>M</transactionCode></transactionCoding>
+            <transactionAmounts>
+                <transactionShares><value>1000</value></transactionShares>
+                <transactionPricePerShare><footnoteId id="F1"/></transactionPricePerShare>
+                <transactionAcquiredDisposedCode><value>A</value></transactionAcquiredDisposedCode>
+            </transactionAmounts>
+            <postTransactionAmounts><sharesOwnedFollowingTransaction><value>5000</value></sharesOwnedFollowingTransaction></postTransactionAmounts>
+            <ownershipNature><directOrIndirectOwnership><value>D</value></directOrIndirectOwnership></ownershipNature>
+        </nonDerivativeTransaction>
+        <nonDerivativeTransaction>
+            <securityTitle><value>Common Stock</value></securityTitle>
+            <transactionDate><value>2024-10-02</value></transactionDate>
+            <transactionCoding><transactionFormType>4</transactionFormType><transactionCode>S</transactionCode></transactionCoding>
+            <transactionAmounts>
+                <transactionShares><value>400</value></transactionShares>
+                <transactionPricePerShare><value>226.5</value></transactionPricePerShare>
+                <transactionAcquiredDisposedCode><value>D</value></transactionAcquiredDisposedCode>
+            </transactionAmounts>
+            <postTransactionAmounts><sharesOwnedFollowingTransaction><value>4600</value></sharesOwnedFollowingTransaction></postTransactionAmounts>
+            <ownershipNature><directOrIndirectOwnership><value>D</value></directOrIndirectOwnership></ownershipNature>
+        </nonDerivativeTransaction>
+    </nonDerivativeTable>
+    <derivativeTable>
+        <derivativeTransaction>
+            <securityTitle><value>Restricted Stock Unit</value></securityTitle>
+            <conversionOrExercisePrice><footnoteId id="F2"/></conversionOrExercisePrice>
+            <transactionDate><value>2024-10-01</value></transactionDate>
+            <transactionCoding><transactionFormType>4</transactionFormType><transactionCode>M</transactionCode></transactionCoding>
+            <transactionAmounts>
+                <transactionShares><value>1000</value></transactionShares>
+                <transactionPricePerShare><value>0</value></transactionPricePerShare>
+                <transactionAcquiredDisposedCode><value>D</value></transactionAcquiredDisposedCode>
+            </transactionAmounts>
+            <underlyingSecurity>
+                <underlyingSecurityTitle><value>Common Stock</value></underlyingSecurityTitle>
+                <underlyingSecurityShares><value>1000</value></underlyingSecurityShares>
+            </underlyingSecurity>
+            <postTransactionAmounts><sharesOwnedFollowingTransaction><value>2000</value></sharesOwnedFollowingTransaction></postTransactionAmounts>
+            <ownershipNature><directOrIndirectOwnership><value>D</value></directOrIndirectOwnership></ownershipNature>
+        </derivativeTransaction>
+    </derivativeTable>
+    <footnotes>
+        <footnote id="F1">Shares issued upon vesting of restricted stock units.</footnote>
+    </footnotes>
+</ownershipDocument>"#;
+
+    #[test]
+    fn test_parse_ownership_xml() {
+        let document = parse_ownership_xml(FORM4).unwrap();
+
+        assert_eq!(document.document_type, "4");
+        assert_eq!(document.issuer.trading_symbol, "AAPL");
+        assert_eq!(document.reporting_owners[0].name, "Adams Katherine L.");
+        assert_eq!(
+            document.reporting_owners[0].relationship.describe(),
+            "SVP, GC and Secretary"
+        );
+
+        let sale = &document.non_derivative_transactions[1];
+        assert_eq!(sale.code, TransactionCode::Sale);
+        assert_eq!(sale.acquired_disposed, AcquiredDisposed::Disposed);
+        assert_eq!(sale.price_per_share, Some(226.5));
+        assert_eq!(sale.shares_owned_following, Some(4600.0));
+        assert_eq!(
+            document.non_derivative_transactions[0].price_per_share,
+            None
+        );
+
+        let rsu = &document.derivative_transactions[0];
+        assert_eq!(rsu.code, TransactionCode::DerivativeExercise);
+        assert_eq!(rsu.underlying_shares, Some(1000.0));
+        assert!(document
+            .to_markdown()
+            .contains("| 2024-10-02 | Common Stock | S |"));
+    }
+
+    #[test]
+    fn test_summarize_insider_activity() {
+        let document = parse_ownership_xml(FORM4).unwrap();
+        let activity = summarize_insider_activity(
+            &[document],
+            NaiveDate::from_ymd_opt(2024, 10, 1).unwrap(),
+            NaiveDate::from_ymd_opt(2024, 10, 31).unwrap(),
+        );
+
+        assert_eq!(activity.len(), 1);
+        assert_eq!(activity[0].shares_sold, 400.0);
+        assert_eq!(activity[0].net_value(), -90600.0);
+        assert_eq!(activity[0].other_transactions, 1);
+        assert_eq!(activity[0].shares_owned, Some(4600.0));
+    }
+
+    #[test]
+    fn test_amendment_replaces_original() {
+        let mut original = parse_ownership_xml(FORM4).unwrap();
+        original.filing_date = NaiveDate::from_ymd_opt(2024, 10, 3);
+        let amended = FORM4
+            .replace(
+                "<documentType>4</documentType>",
+                "<documentType>4/A</documentType>\
+                 <dateOfOriginalSubmission>2024-10-03</dateOfOriginalSubmission>",
+            )
+            .replace("<value>400</value>", "<value>450</value>");
+        let mut amendment = parse_ownership_xml(&amended).unwrap();
+        amendment.filing_date = NaiveDate::from_ymd_opt(2024, 10, 10);
+        assert_eq!(
+            amendment.date_of_original_submission,
+            NaiveDate::from_ymd_opt(2024, 10, 3)
+        );
+
+        let activity = summarize_insider_activity(
+            &[amendment, original],
+            NaiveDate::from_ymd_opt(2024, 10, 1).unwrap(),
+            NaiveDate::from_ymd_opt(2024, 10, 31).unwrap(),
+        );
+        assert_eq!(activity.len(), 1);
+        assert_eq!(activity[0].shares_sold, 450.0);
+        assert_eq!(activity[0].other_transactions, 1);
+    }
+}
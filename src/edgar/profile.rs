@@ -0,0 +1,330 @@
+use anyhow::{anyhow, Result};
+use chrono::Month;
+use mime::APPLICATION_JSON;
+use serde::{Deserialize, Deserializer, Serialize};
+use sqlx::{Pool, Postgres};
+use std::fs;
+
+use super::client::EdgarClient;
+use super::fiscal::parse_month_day;
+use super::ticker_index::normalize_cik;
+use super::tickers;
+use crate::db;
+
+/// Company information from the header of the submissions JSON
+/// (`/submissions/CIK##########.json`), everything but the filing list
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CompanyProfile {
+    pub cik: String,
+    pub name: String,
+    /// `operating`, `other` for funds and trusts, ...
+    #[serde(default)]
+    pub entity_type: Option<String>,
+    #[serde(default)]
+    pub sic: Option<String>,
+    #[serde(default)]
+    pub sic_description: Option<String>,
+    /// Filer status, e.g. `Large accelerated filer`
+    #[serde(default)]
+    pub category: Option<String>,
+    /// Month and day the fiscal year ends, `MMDD` (`0928` for Apple)
+    #[serde(default)]
+    pub fiscal_year_end: Option<String>,
+    #[serde(default)]
+    pub state_of_incorporation: Option<String>,
+    #[serde(default)]
+    pub state_of_incorporation_description: Option<String>,
+    #[serde(default)]
+    pub ein: Option<String>,
+    #[serde(default)]
+    pub phone: Option<String>,
+    #[serde(default)]
+    pub website: Option<String>,
+    /// Tickers and the exchanges they trade on, paired by position; either can be null
+    #[serde(default, deserialize_with = "nullable_list")]
+    pub tickers: Vec<Option<String>>,
+    #[serde(default, deserialize_with = "nullable_list")]
+    pub exchanges: Vec<Option<String>>,
+    #[serde(default)]
+    pub addresses: Addresses,
+    #[serde(default)]
+    pub former_names: Vec<FormerName>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Addresses {
+    pub mailing: Option<Address>,
+    pub business: Option<Address>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Address {
+    pub street1: Option<String>,
+    pub street2: Option<String>,
+    pub city: Option<String>,
+    /// State code for US addresses, country code otherwise
+    pub state_or_country: Option<String>,
+    pub state_or_country_description: Option<String>,
+    pub zip_code: Option<String>,
+}
+
+/// An earlier name of the company, dated by the EDGAR timestamps of the change
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FormerName {
+    pub name: String,
+    pub from: Option<String>,
+    pub to: Option<String>,
+}
+
+/// Submissions lists can be null or contain nulls, e.g. an exchange for a ticker that is not
+/// listed. Nulls are kept so the positions of parallel lists still line up.
+fn nullable_list<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> Result<Vec<Option<String>>, D::Error> {
+    Ok(Option::<Vec<Option<String>>>::deserialize(deserializer)?.unwrap_or_default())
+}
+
+/// SEC leaves unknown fields as empty strings
+fn non_empty(value: &mut Option<String>) {
+    if value.as_deref().is_some_and(|v| v.trim().is_empty()) {
+        *value = None;
+    }
+}
+
+impl Address {
+    /// One line, `ONE APPLE PARK WAY, CUPERTINO, CA 95014`
+    pub fn one_line(&self) -> String {
+        let region = [&self.state_or_country, &self.zip_code]
+            .into_iter()
+            .flatten()
+            .map(String::as_str)
+            .collect::<Vec<_>>()
+            .join(" ");
+        [&self.street1, &self.street2, &self.city]
+            .into_iter()
+            .flatten()
+            .map(String::as_str)
+            .chain((!region.is_empty()).then_some(region.as_str()))
+            .filter(|part| !part.trim().is_empty())
+            .collect::<Vec<_>>()
+            .join(", ")
+    }
+}
+
+impl CompanyProfile {
+    /// Parse the submissions JSON of a company; its `filings` are ignored
+    pub fn from_submissions(json: &str) -> Result<Self> {
+        let mut profile: CompanyProfile = serde_json::from_str(json)?;
+        profile.cik = normalize_cik(&profile.cik)
+            .ok_or_else(|| anyhow!("Invalid CIK in submissions: {}", profile.cik))?;
+        for field in [
+            &mut profile.entity_type,
+            &mut profile.sic,
+            &mut profile.sic_description,
+            &mut profile.category,
+            &mut profile.fiscal_year_end,
+            &mut profile.state_of_incorporation,
+            &mut profile.state_of_incorporation_description,
+            &mut profile.ein,
+            &mut profile.phone,
+            &mut profile.website,
+        ] {
+            non_empty(field);
+        }
+        // Several statuses are separated by line breaks,
+        // `Non-accelerated filer<br>Smaller reporting company`
+        profile.category = profile
+            .category
+            .map(|category| category.replace("<br>", ", "));
+        Ok(profile)
+    }
+
+    /// Month and day the fiscal year ends, `(9, 28)` for `0928`
+    pub fn fiscal_year_end_month_day(&self) -> Option<(u32, u32)> {
+        self.fiscal_year_end.as_deref().and_then(parse_month_day)
+    }
+
+    pub fn to_markdown(&self) -> String {
+        let mut md = format!("# {} (CIK {}) Company Profile\n\n", self.name, self.cik);
+        let mut line = |label: &str, value: String| {
+            if !value.is_empty() {
+                md.push_str(&format!("- {}: {}\n", label, value));
+            }
+        };
+
+        line(
+            "Tickers",
+            self.tickers
+                .iter()
+                .enumerate()
+                .filter_map(|(i, ticker)| {
+                    let ticker = ticker.as_ref()?;
+                    Some(match self.exchanges.get(i).cloned().flatten() {
+                        Some(exchange) => format!("{} ({})", ticker, exchange),
+                        None => ticker.clone(),
+                    })
+                })
+                .collect::<Vec<_>>()
+                .join(", "),
+        );
+        if let Some(sic) = &self.sic {
+            line(
+                "Industry",
+                format!(
+                    "SIC {} {}",
+                    sic,
+                    self.sic_description.as_deref().unwrap_or_default()
+                )
+                .trim()
+                .to_string(),
+            );
+        }
+        if let Some((month, day)) = self.fiscal_year_end_month_day() {
+            let month = Month::try_from(month as u8).map_or(String::new(), |m| m.name().into());
+            line("Fiscal year end", format!("{} {}", month, day));
+        }
+        line("Filer category", self.category.clone().unwrap_or_default());
+        line("Entity type", self.entity_type.clone().unwrap_or_default());
+        line(
+            "Incorporated in",
+            self.state_of_incorporation_description
+                .clone()
+                .or_else(|| self.state_of_incorporation.clone())
+                .unwrap_or_default(),
+        );
+        if let Some(business) = &self.addresses.business {
+            line("Business address", business.one_line());
+        }
+        if let Some(mailing) = &self.addresses.mailing {
+            if Some(mailing) != self.addresses.business.as_ref() {
+                line("Mailing address", mailing.one_line());
+            }
+        }
+        line(
+            "Former names",
+            self.former_names
+                .iter()
+                .map(|former| {
+                    let date = |d: &Option<String>| {
+                        d.as_deref()
+                            .map_or("?", |d| d.get(..10).unwrap_or(d))
+                            .to_string()
+                    };
+                    format!(
+                        "{} ({} to {})",
+                        former.name,
+                        date(&former.from),
+                        date(&former.to)
+                    )
+                })
+                .collect::<Vec<_>>()
+                .join(", "),
+        );
+        md
+    }
+}
+
+/// Profile of a company from its submissions, refreshed once the cached copy is stale and
+/// stored in Postgres. The stored profile is used when EDGAR can't be reached.
+pub async fn fetch_company_profile(
+    client: &EdgarClient,
+    pool: &Pool<Postgres>,
+    cik: &str,
+) -> Result<CompanyProfile> {
+    let cik = normalize_cik(cik).ok_or_else(|| anyhow!("Invalid CIK: {}", cik))?;
+    // Same file as the first page of the filing list
+    let path = client.filings_dir().join(format!("CIK{}_0.json", cik));
+    let url = format!("{}/submissions/CIK{}.json", client.data_url(), cik);
+
+    match client.fetch_if_stale(&url, &path, APPLICATION_JSON).await {
+        Ok(_) => {}
+        Err(e) if path.exists() => {
+            log::warn!("Failed to refresh submissions of CIK {}: {}", cik, e)
+        }
+        Err(e) => {
+            return match db::get_company_profile(pool, &cik).await? {
+                Some(profile) => {
+                    log::warn!("Using stored profile of CIK {}: {}", cik, e);
+                    Ok(profile)
+                }
+                None => Err(e),
+            };
+        }
+    }
+
+    let profile = CompanyProfile::from_submissions(&fs::read_to_string(&path)?)?;
+    db::upsert_company_profile(pool, &profile).await?;
+    Ok(profile)
+}
+
+/// Profile of the company behind a ticker or CIK reference
+pub async fn company_profile_for_ticker(
+    client: &EdgarClient,
+    pool: &Pool<Postgres>,
+    ticker: &str,
+) -> Result<CompanyProfile> {
+    let cik = tickers::resolve_cik(client, ticker).await?;
+    fetch_company_profile(client, pool, &cik).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SUBMISSIONS: &str = r#"{
+        "cik": "320193",
+        "entityType": "operating",
+        "sic": "3571",
+        "sicDescription": "Electronic Computers",
+        "name": "Apple Inc.",
+        "tickers": ["AAPL"],
+        "exchanges": ["Nasdaq"],
+        "ein": "942404110",
+        "website": "",
+        "category": "Large accelerated filer<br>Well-known Seasoned Issuer",
+        "fiscalYearEnd": "0928",
+        "stateOfIncorporation": "CA",
+        "stateOfIncorporationDescription": "CA",
+        "addresses": {
+            "mailing": {"street1": "ONE APPLE PARK WAY", "street2": null, "city": "CUPERTINO", "stateOrCountry": "CA", "zipCode": "95014", "stateOrCountryDescription": "CA"},
+            "business": {"street1": "ONE APPLE PARK WAY", "street2": null, "city": "CUPERTINO", "stateOrCountry": "CA", "zipCode": "95014", "stateOrCountryDescription": "CA"}
+        },
+        "phone": "(408) 996-1010",
+        "formerNames": [
+            {"name": "APPLE INC", "from": "2007-01-10T05:00:00.000Z", "to": "2019-08-05T04:00:00.000Z"},
+            {"name": "APPLE COMPUTER INC", "from": "1994-01-26T05:00:00.000Z", "to": "2007-01-04T05:00:00.000Z"}
+        ],
+        "filings": {"recent": {}, "files": []}
+    }"#;
+
+    #[test]
+    fn test_company_profile_from_submissions() {
+        let profile = CompanyProfile::from_submissions(SUBMISSIONS).unwrap();
+
+        assert_eq!(profile.cik, "0000320193");
+        assert_eq!(profile.fiscal_year_end_month_day(), Some((9, 28)));
+        assert_eq!(profile.website, None);
+        assert_eq!(
+            profile.category.as_deref(),
+            Some("Large accelerated filer, Well-known Seasoned Issuer")
+        );
+        assert_eq!(profile.former_names.len(), 2);
+
+        let md = profile.to_markdown();
+        assert!(md.contains("- Tickers: AAPL (Nasdaq)\n"));
+        assert!(md.contains("- Industry: SIC 3571 Electronic Computers\n"));
+        assert!(md.contains("- Fiscal year end: September 28\n"));
+        assert!(md.contains("- Business address: ONE APPLE PARK WAY, CUPERTINO, CA 95014\n"));
+        assert!(!md.contains("Mailing address"));
+        assert!(md.contains("APPLE COMPUTER INC (1994-01-26 to 2007-01-04)"));
+
+        // An unlisted ticker keeps its place next to the exchanges of the others
+        let submissions = SUBMISSIONS
+            .replace(r#"["AAPL"]"#, r#"["X", "Y", null]"#)
+            .replace(r#"["Nasdaq"]"#, r#"[null, "NYSE", "OTC"]"#);
+        let profile = CompanyProfile::from_submissions(&submissions).unwrap();
+        assert!(profile.to_markdown().contains("- Tickers: X, Y (NYSE)\n"));
+    }
+}
@@ -1,3 +1,5 @@
+use crate::edgar::exhibits::ExhibitSelection;
+use crate::edgar::items::Form8KItem;
 use crate::edgar::report::ReportType;
 use anyhow::{anyhow, Result};
 use chrono::NaiveDate;
@@ -56,6 +58,13 @@ use super::report;
 /// - `start_date`: Start date in YYYY-MM-DD format
 /// - `end_date`: End date in YYYY-MM-DD format  
 /// - `report_types`: Array of SEC filing types to fetch
+/// - `items`: Optional array of 8-K item codes (e.g. `["2.02", "7.01"]`); when present only
+///   8-Ks reporting at least one of them are fetched, other report types are unaffected
+/// - `exhibits`: Optional map of report type to the exhibit types downloaded alongside the
+///   primary document (e.g. `{"8-K": ["EX-99.1"]}`); defaults to press releases of 8-Ks,
+///   subsidiaries and material contracts of 10-Ks and material contracts of 10-Qs
+/// - `include_amendments`: Optional, when true a requested form also matches its amendments
+///   (`10-K` fetches `10-K/A` too); amendments can always be requested explicitly
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Query {
     pub tickers: Vec<String>,
@@ -64,6 +73,12 @@ pub struct Query {
     #[serde(with = "date_format")]
     pub end_date: NaiveDate,
     pub report_types: Vec<ReportType>,
+    #[serde(default)]
+    pub items: Vec<Form8KItem>,
+    #[serde(default)]
+    pub exhibits: ExhibitSelection,
+    #[serde(default)]
+    pub include_amendments: bool,
     pub is_adr: bool,
 }
 
@@ -102,6 +117,9 @@ impl Query {
             start_date,
             end_date,
             report_types,
+            items: Vec::new(),
+            exhibits: ExhibitSelection::default(),
+            include_amendments: false,
             is_adr,
         };
         query.validate()?;
@@ -144,8 +162,8 @@ impl Query {
     /// - end_date: The end date for the query in YYYY-MM-DD format
     /// - report_types: An array of report types (strings)
     ///
-    /// Valid report types are: "10-K", "10-Q", "8-K", "4", "5", "S-1", "S-3", "S-4",
-    /// "DEF 14A", "13F", "13G", "13D", "SD", "6-K", "20-F", "N-1A", "N-CSR", "N-PORT", "N-Q"
+    /// Valid report types are the codes of the form registry, see
+    /// [`report::ReportType::list_types`] and [`report::REPORT_TYPES_BY_FAMILY`]
     pub fn from_json(json_str: &str) -> Result<Self> {
         serde_json::from_str(json_str).map_err(|e| anyhow!("Failed to parse JSON: {}", e))
     }
@@ -157,6 +175,9 @@ pub struct QueryBuilder {
     start_date: Option<NaiveDate>,
     end_date: Option<NaiveDate>,
     report_types: Option<Vec<report::ReportType>>,
+    items: Option<Vec<Form8KItem>>,
+    exhibits: Option<ExhibitSelection>,
+    include_amendments: Option<bool>,
     is_adr: Option<bool>,
 }
 
@@ -181,6 +202,21 @@ impl QueryBuilder {
         self
     }
 
+    pub fn items(mut self, items: Vec<Form8KItem>) -> Self {
+        self.items = Some(items);
+        self
+    }
+
+    pub fn exhibits(mut self, exhibits: ExhibitSelection) -> Self {
+        self.exhibits = Some(exhibits);
+        self
+    }
+
+    pub fn include_amendments(mut self, include_amendments: bool) -> Self {
+        self.include_amendments = Some(include_amendments);
+        self
+    }
+
     pub fn is_adr(mut self, is_adr: bool) -> Self {
         self.is_adr = Some(is_adr);
         self
@@ -201,7 +237,11 @@ impl QueryBuilder {
             .ok_or_else(|| anyhow!("Report types must be specified"))?;
         let is_adr = self.is_adr.unwrap_or(false);
 
-        Query::new(tickers, start_date, end_date, report_types, is_adr)
+        let mut query = Query::new(tickers, start_date, end_date, report_types, is_adr)?;
+        query.items = self.items.unwrap_or_default();
+        query.exhibits = self.exhibits.unwrap_or_default();
+        query.include_amendments = self.include_amendments.unwrap_or(false);
+        Ok(query)
     }
 }
 
@@ -250,6 +290,28 @@ mod tests {
 
         assert_eq!(query.tickers, vec!["AAPL"]);
         assert_eq!(query.report_types, vec![report::ReportType::Form10K]);
+        assert!(query.items.is_empty());
+    }
+
+    #[test]
+    fn test_query_item_filters() {
+        let json_str = r#"
+        {
+            "tickers": ["AAPL"],
+            "start_date": "2024-01-01",
+            "end_date": "2024-12-31",
+            "report_types": ["8-K"],
+            "items": ["2.02", "7.01"],
+            "is_adr": false
+        }
+        "#;
+
+        let query = Query::from_json(json_str).unwrap();
+
+        assert_eq!(
+            query.items,
+            vec![Form8KItem::ResultsOfOperations, Form8KItem::RegulationFD]
+        );
     }
 
     #[test]
@@ -1,16 +1,15 @@
 use once_cell::sync::Lazy;
 use serde::{Deserialize, Serialize};
 use std::{fmt, str::FromStr};
-use strum::{EnumIter, IntoEnumIterator};
 
-#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, EnumIter)]
-#[serde(try_from = "String")]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
 pub enum ReportType {
     Form10K,
     Form6K,
     Form10Q,
-    Form20K,
     Form8K,
+    Form3,
     Form4,
     Form5,
     FormS1,
@@ -20,6 +19,12 @@ pub enum ReportType {
     Form13F,
     Form13G,
     Form13D,
+    Form3A,
+    Form4A,
+    Form5A,
+    Form13FA,
+    Form13GA,
+    Form13DA,
     FormSD,
     Form20F,
     FormN1A,
@@ -27,9 +32,411 @@ pub enum ReportType {
     FormNPORT,
     FormNQ,
     Form144,
+    Form10KA,
+    Form10QA,
+    Form8KA,
+    Form40F,
+    Form10KT,
+    Form424B,
+    FormDEFA14A,
+    Form11K,
+    FormNCSRS,
     Other(String),
 }
 
+/// Broad category of a form, used to group them in prompts and listings
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FormFamily {
+    /// Annual and quarterly reports
+    Periodic,
+    /// Reports of material events between periodic reports
+    Current,
+    /// Insider, beneficial owner and institutional holdings reports
+    Ownership,
+    /// Securities registrations and prospectuses
+    Registration,
+    /// Proxy statements and soliciting material
+    Proxy,
+    /// Investment company filings
+    Fund,
+}
+
+impl fmt::Display for FormFamily {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FormFamily::Periodic => write!(f, "Periodic reports"),
+            FormFamily::Current => write!(f, "Current reports"),
+            FormFamily::Ownership => write!(f, "Ownership reports"),
+            FormFamily::Registration => write!(f, "Registrations and prospectuses"),
+            FormFamily::Proxy => write!(f, "Proxy materials"),
+            FormFamily::Fund => write!(f, "Fund filings"),
+        }
+    }
+}
+
+/// How the documents of a filing are downloaded and ingested
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FormParser {
+    /// XBRL instance with the financial statements, plus the narrative text of the primary
+    /// document and selected exhibits
+    Xbrl,
+    /// Primary HTML document converted to markdown
+    Text,
+    /// Ownership XML of insider transactions (Forms 3, 4 and 5)
+    Ownership,
+    /// 13F information table of institutional holdings
+    Holdings,
+    /// Structured XML forms without a parser yet, listed but not ingested
+    Unsupported,
+}
+
+/// Registry entry describing a form
+#[derive(Debug)]
+pub struct FormSpec {
+    pub report_type: ReportType,
+    /// Form type as it appears in EDGAR submissions
+    pub code: &'static str,
+    /// Other spellings accepted when parsing, e.g. the numbered variants of 424B prospectuses
+    pub aliases: &'static [&'static str],
+    pub description: &'static str,
+    pub family: FormFamily,
+    pub parser: FormParser,
+    /// The form an amendment amends
+    pub amends: Option<ReportType>,
+}
+
+fn form(
+    report_type: ReportType,
+    code: &'static str,
+    description: &'static str,
+    family: FormFamily,
+    parser: FormParser,
+) -> FormSpec {
+    FormSpec {
+        report_type,
+        code,
+        aliases: &[],
+        description,
+        family,
+        parser,
+        amends: None,
+    }
+}
+
+fn amendment(
+    report_type: ReportType,
+    code: &'static str,
+    description: &'static str,
+    family: FormFamily,
+    parser: FormParser,
+    amends: ReportType,
+) -> FormSpec {
+    FormSpec {
+        report_type,
+        code,
+        aliases: &[],
+        description,
+        family,
+        parser,
+        amends: Some(amends),
+    }
+}
+
+fn with_aliases(mut spec: FormSpec, aliases: &'static [&'static str]) -> FormSpec {
+    spec.aliases = aliases;
+    spec
+}
+
+/// Every form known by name, in the order they are listed to the LLM
+pub static FORMS: Lazy<Vec<FormSpec>> = Lazy::new(|| {
+    vec![
+        form(
+            ReportType::Form10K,
+            "10-K",
+            "Annual report",
+            FormFamily::Periodic,
+            FormParser::Xbrl,
+        ),
+        form(
+            ReportType::Form10Q,
+            "10-Q",
+            "Quarterly report",
+            FormFamily::Periodic,
+            FormParser::Xbrl,
+        ),
+        form(
+            ReportType::Form10KT,
+            "10-KT",
+            "Transition report after a fiscal year change",
+            FormFamily::Periodic,
+            FormParser::Xbrl,
+        ),
+        form(
+            ReportType::Form20F,
+            "20-F",
+            "Annual report of a foreign private issuer",
+            FormFamily::Periodic,
+            FormParser::Xbrl,
+        ),
+        form(
+            ReportType::Form40F,
+            "40-F",
+            "Annual report of a Canadian issuer",
+            FormFamily::Periodic,
+            FormParser::Xbrl,
+        ),
+        form(
+            ReportType::Form11K,
+            "11-K",
+            "Annual report of an employee stock plan",
+            FormFamily::Periodic,
+            FormParser::Text,
+        ),
+        amendment(
+            ReportType::Form10KA,
+            "10-K/A",
+            "Amended annual report",
+            FormFamily::Periodic,
+            FormParser::Xbrl,
+            ReportType::Form10K,
+        ),
+        amendment(
+            ReportType::Form10QA,
+            "10-Q/A",
+            "Amended quarterly report",
+            FormFamily::Periodic,
+            FormParser::Xbrl,
+            ReportType::Form10Q,
+        ),
+        form(
+            ReportType::Form8K,
+            "8-K",
+            "Current report of material events",
+            FormFamily::Current,
+            FormParser::Xbrl,
+        ),
+        amendment(
+            ReportType::Form8KA,
+            "8-K/A",
+            "Amended current report",
+            FormFamily::Current,
+            FormParser::Xbrl,
+            ReportType::Form8K,
+        ),
+        form(
+            ReportType::Form6K,
+            "6-K",
+            "Current report of a foreign private issuer",
+            FormFamily::Current,
+            FormParser::Text,
+        ),
+        form(
+            ReportType::FormSD,
+            "SD",
+            "Specialized disclosure (conflict minerals)",
+            FormFamily::Current,
+            FormParser::Text,
+        ),
+        form(
+            ReportType::Form3,
+            "3",
+            "Initial statement of insider ownership",
+            FormFamily::Ownership,
+            FormParser::Ownership,
+        ),
+        form(
+            ReportType::Form4,
+            "4",
+            "Changes in insider ownership",
+            FormFamily::Ownership,
+            FormParser::Ownership,
+        ),
+        form(
+            ReportType::Form5,
+            "5",
+            "Annual statement of insider ownership changes",
+            FormFamily::Ownership,
+            FormParser::Ownership,
+        ),
+        amendment(
+            ReportType::Form3A,
+            "3/A",
+            "Amended initial statement of insider ownership",
+            FormFamily::Ownership,
+            FormParser::Ownership,
+            ReportType::Form3,
+        ),
+        amendment(
+            ReportType::Form4A,
+            "4/A",
+            "Amended changes in insider ownership",
+            FormFamily::Ownership,
+            FormParser::Ownership,
+            ReportType::Form4,
+        ),
+        amendment(
+            ReportType::Form5A,
+            "5/A",
+            "Amended annual statement of insider ownership changes",
+            FormFamily::Ownership,
+            FormParser::Ownership,
+            ReportType::Form5,
+        ),
+        form(
+            ReportType::Form144,
+            "144",
+            "Notice of proposed sale of restricted securities",
+            FormFamily::Ownership,
+            FormParser::Unsupported,
+        ),
+        with_aliases(
+            form(
+                ReportType::Form13F,
+                "13F",
+                "Institutional holdings report",
+                FormFamily::Ownership,
+                FormParser::Holdings,
+            ),
+            &["13F-HR"],
+        ),
+        with_aliases(
+            form(
+                ReportType::Form13D,
+                "SC 13D",
+                "Beneficial ownership above 5% by an active investor",
+                FormFamily::Ownership,
+                FormParser::Text,
+            ),
+            &["13D", "SCHEDULE 13D"],
+        ),
+        with_aliases(
+            form(
+                ReportType::Form13G,
+                "SC 13G",
+                "Beneficial ownership above 5% by a passive investor",
+                FormFamily::Ownership,
+                FormParser::Text,
+            ),
+            &["13G", "SCHEDULE 13G"],
+        ),
+        with_aliases(
+            amendment(
+                ReportType::Form13FA,
+                "13F-HR/A",
+                "Amended institutional holdings report",
+                FormFamily::Ownership,
+                FormParser::Holdings,
+                ReportType::Form13F,
+            ),
+            &["13F/A"],
+        ),
+        with_aliases(
+            amendment(
+                ReportType::Form13DA,
+                "SC 13D/A",
+                "Amended beneficial ownership report of an active investor",
+                FormFamily::Ownership,
+                FormParser::Text,
+                ReportType::Form13D,
+            ),
+            &["13D/A", "SCHEDULE 13D/A"],
+        ),
+        with_aliases(
+            amendment(
+                ReportType::Form13GA,
+                "SC 13G/A",
+                "Amended beneficial ownership report of a passive investor",
+                FormFamily::Ownership,
+                FormParser::Text,
+                ReportType::Form13G,
+            ),
+            &["13G/A", "SCHEDULE 13G/A"],
+        ),
+        form(
+            ReportType::FormS1,
+            "S-1",
+            "IPO registration statement",
+            FormFamily::Registration,
+            FormParser::Text,
+        ),
+        form(
+            ReportType::FormS3,
+            "S-3",
+            "Simplified registration statement",
+            FormFamily::Registration,
+            FormParser::Text,
+        ),
+        form(
+            ReportType::FormS4,
+            "S-4",
+            "Registration for mergers and acquisitions",
+            FormFamily::Registration,
+            FormParser::Text,
+        ),
+        with_aliases(
+            form(
+                ReportType::Form424B,
+                "424B",
+                "Prospectus",
+                FormFamily::Registration,
+                FormParser::Text,
+            ),
+            &[
+                "424B1", "424B2", "424B3", "424B4", "424B5", "424B7", "424B8",
+            ],
+        ),
+        form(
+            ReportType::FormDEF14A,
+            "DEF 14A",
+            "Definitive proxy statement",
+            FormFamily::Proxy,
+            FormParser::Text,
+        ),
+        form(
+            ReportType::FormDEFA14A,
+            "DEFA14A",
+            "Additional proxy soliciting material",
+            FormFamily::Proxy,
+            FormParser::Text,
+        ),
+        form(
+            ReportType::FormN1A,
+            "N-1A",
+            "Mutual fund registration statement",
+            FormFamily::Fund,
+            FormParser::Text,
+        ),
+        form(
+            ReportType::FormNCSR,
+            "N-CSR",
+            "Annual shareholder report of a fund",
+            FormFamily::Fund,
+            FormParser::Text,
+        ),
+        form(
+            ReportType::FormNCSRS,
+            "N-CSRS",
+            "Semi-annual shareholder report of a fund",
+            FormFamily::Fund,
+            FormParser::Text,
+        ),
+        form(
+            ReportType::FormNPORT,
+            "N-PORT",
+            "Monthly portfolio holdings of a fund",
+            FormFamily::Fund,
+            FormParser::Unsupported,
+        ),
+        form(
+            ReportType::FormNQ,
+            "N-Q",
+            "Quarterly portfolio holdings of a fund (before 2019)",
+            FormFamily::Fund,
+            FormParser::Text,
+        ),
+    ]
+});
+
 impl TryFrom<String> for ReportType {
     type Error = String;
 
@@ -38,75 +445,138 @@ impl TryFrom<String> for ReportType {
     }
 }
 
+impl From<ReportType> for String {
+    fn from(report_type: ReportType) -> Self {
+        report_type.to_string()
+    }
+}
+
 impl fmt::Display for ReportType {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        match self {
-            ReportType::Form10K => write!(f, "10-K"),
-            ReportType::Form6K => write!(f, "6-K"),
-            ReportType::Form10Q => write!(f, "10-Q"),
-            ReportType::Form20K => write!(f, "20-K"),
-            ReportType::Form8K => write!(f, "8-K"),
-            ReportType::Form4 => write!(f, "4"),
-            ReportType::Form5 => write!(f, "5"),
-            ReportType::FormS1 => write!(f, "S-1"),
-            ReportType::FormS3 => write!(f, "S-3"),
-            ReportType::FormS4 => write!(f, "S-4"),
-            ReportType::FormDEF14A => write!(f, "DEF 14A"),
-            ReportType::Form13F => write!(f, "13F"),
-            ReportType::Form13G => write!(f, "13G"),
-            ReportType::Form13D => write!(f, "13D"),
-            ReportType::FormSD => write!(f, "SD"),
-            ReportType::Form20F => write!(f, "20-F"),
-            ReportType::FormN1A => write!(f, "N-1A"),
-            ReportType::FormNCSR => write!(f, "N-CSR"),
-            ReportType::FormNPORT => write!(f, "N-PORT"),
-            ReportType::FormNQ => write!(f, "N-Q"),
-            ReportType::Form144 => write!(f, "144"),
-            ReportType::Other(s) => write!(f, "{}", s),
+        match (self, self.spec()) {
+            (ReportType::Other(s), _) => write!(f, "{}", s),
+            (_, Some(spec)) => write!(f, "{}", spec.code),
+            (_, None) => write!(f, "{:?}", self),
         }
     }
 }
 
+/// Comma separated codes of the known forms
 pub static REPORT_TYPES: Lazy<String> = Lazy::new(|| {
-    ReportType::iter()
-        .filter(|t| !matches!(t, ReportType::Other(_)))
-        .map(|t| t.to_string())
+    FORMS
+        .iter()
+        .map(|spec| spec.code)
         .collect::<Vec<_>>()
         .join(", ")
 });
 
+/// Known forms grouped by family as "code (description)" lists, one family per line, for
+/// LLM prompts
+pub static REPORT_TYPES_BY_FAMILY: Lazy<String> = Lazy::new(|| {
+    let mut families: Vec<FormFamily> = Vec::new();
+    for spec in FORMS.iter() {
+        if !families.contains(&spec.family) {
+            families.push(spec.family);
+        }
+    }
+    families
+        .into_iter()
+        .map(|family| {
+            let forms = FORMS
+                .iter()
+                .filter(|spec| spec.family == family)
+                .map(|spec| format!("{} ({})", spec.code, spec.description))
+                .collect::<Vec<_>>()
+                .join(", ");
+            format!("- {}: {}", family, forms)
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+});
+
 impl ReportType {
     pub fn list_types() -> &'static str {
         &REPORT_TYPES
     }
+
+    /// Registry entry of the form, `None` for forms not in the registry
+    pub fn spec(&self) -> Option<&'static FormSpec> {
+        FORMS.iter().find(|spec| &spec.report_type == self)
+    }
+
+    pub fn description(&self) -> Option<&'static str> {
+        self.spec().map(|spec| spec.description)
+    }
+
+    pub fn family(&self) -> Option<FormFamily> {
+        self.spec().map(|spec| spec.family)
+    }
+
+    /// Forms outside the registry are ingested as text
+    pub fn parser(&self) -> FormParser {
+        self.spec().map_or(FormParser::Text, |spec| spec.parser)
+    }
+
+    pub fn is_amendment(&self) -> bool {
+        self.spec().is_some_and(|spec| spec.amends.is_some())
+    }
+
+    /// The form an amendment amends (`10-K/A` -> `10-K`), every other form is its own original
+    pub fn original(&self) -> ReportType {
+        self.spec()
+            .and_then(|spec| spec.amends.clone())
+            .unwrap_or_else(|| self.clone())
+    }
 }
 
 impl FromStr for ReportType {
     type Err = String;
     fn from_str(s: &str) -> std::result::Result<ReportType, std::string::String> {
-        match s.to_uppercase().as_str() {
-            "10-K" => Ok(ReportType::Form10K),
-            "6-K" => Ok(ReportType::Form6K),
-            "10-Q" => Ok(ReportType::Form10Q),
-            "20-Q" => Ok(ReportType::Form20K),
-            "8-K" => Ok(ReportType::Form8K),
-            "4" => Ok(ReportType::Form4),
-            "5" => Ok(ReportType::Form5),
-            "S-1" => Ok(ReportType::FormS1),
-            "S-3" => Ok(ReportType::FormS3),
-            "S-4" => Ok(ReportType::FormS4),
-            "DEF 14A" => Ok(ReportType::FormDEF14A),
-            "13F" => Ok(ReportType::Form13F),
-            "13G" => Ok(ReportType::Form13G),
-            "13D" => Ok(ReportType::Form13D),
-            "SD" => Ok(ReportType::FormSD),
-            "20-F" => Ok(ReportType::Form20F),
-            "N-1A" => Ok(ReportType::FormN1A),
-            "N-CSR" => Ok(ReportType::FormNCSR),
-            "N-PORT" => Ok(ReportType::FormNPORT),
-            "N-Q" => Ok(ReportType::FormNQ),
-            "144" => Ok(ReportType::Form144),
-            _ => Ok(ReportType::Other(s.to_string())),
+        let code = s.trim();
+        Ok(FORMS
+            .iter()
+            .find(|spec| {
+                spec.code.eq_ignore_ascii_case(code)
+                    || spec
+                        .aliases
+                        .iter()
+                        .any(|alias| alias.eq_ignore_ascii_case(code))
+            })
+            .map(|spec| spec.report_type.clone())
+            .unwrap_or_else(|| ReportType::Other(s.to_string())))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_form_registry() {
+        for spec in FORMS.iter() {
+            assert_eq!(spec.code.parse::<ReportType>().unwrap(), spec.report_type);
+            assert_eq!(spec.report_type.to_string(), spec.code);
         }
+        assert_eq!("424B5".parse::<ReportType>().unwrap(), ReportType::Form424B);
+        assert_eq!(
+            "20-Q".parse::<ReportType>().unwrap(),
+            ReportType::Other("20-Q".to_string())
+        );
+        assert_eq!(ReportType::Form10KA.original(), ReportType::Form10K);
+        assert_eq!(
+            "SCHEDULE 13D/A".parse::<ReportType>().unwrap().original(),
+            ReportType::Form13D
+        );
+        assert_eq!(ReportType::Form13FA.parser(), FormParser::Holdings);
+        assert_eq!(
+            "4/A".parse::<ReportType>().unwrap().parser(),
+            FormParser::Ownership
+        );
+        assert_eq!(ReportType::Form3.parser(), FormParser::Ownership);
+        assert_eq!(ReportType::Form40F.family(), Some(FormFamily::Periodic));
+        assert_eq!(
+            serde_json::to_string(&ReportType::Form13D).unwrap(),
+            "\"SC 13D\""
+        );
     }
 }
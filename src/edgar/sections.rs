@@ -0,0 +1,382 @@
+// Segmentation of 10-K and 10-Q text into the standard Regulation S-K items, so retrieval
+// can target "Item 1A Risk Factors" or "Item 7 MD&A" instead of the whole document.
+use once_cell::sync::Lazy;
+use regex::Regex;
+
+use super::report::ReportType;
+
+/// A standard item of a periodic report.
+///
+/// 10-Q item numbers repeat between Part I and Part II, so their codes carry the part as a
+/// roman numeral prefix ("I-2" is MD&A, "II-1A" is Risk Factors). 10-K codes are the plain
+/// item numbers ("1A", "7").
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct StandardItem {
+    pub code: &'static str,
+    pub title: &'static str,
+}
+
+const fn item(code: &'static str, title: &'static str) -> StandardItem {
+    StandardItem { code, title }
+}
+
+pub const FORM_10K_ITEMS: &[StandardItem] = &[
+    item("1", "Business"),
+    item("1A", "Risk Factors"),
+    item("1B", "Unresolved Staff Comments"),
+    item("1C", "Cybersecurity"),
+    item("2", "Properties"),
+    item("3", "Legal Proceedings"),
+    item("4", "Mine Safety Disclosures"),
+    item("5", "Market for Registrant's Common Equity, Related Stockholder Matters and Issuer Purchases of Equity Securities"),
+    item("6", "[Reserved]"),
+    item("7", "Management's Discussion and Analysis of Financial Condition and Results of Operations"),
+    item("7A", "Quantitative and Qualitative Disclosures About Market Risk"),
+    item("8", "Financial Statements and Supplementary Data"),
+    item("9", "Changes in and Disagreements with Accountants on Accounting and Financial Disclosure"),
+    item("9A", "Controls and Procedures"),
+    item("9B", "Other Information"),
+    item("9C", "Disclosure Regarding Foreign Jurisdictions that Prevent Inspections"),
+    item("10", "Directors, Executive Officers and Corporate Governance"),
+    item("11", "Executive Compensation"),
+    item("12", "Security Ownership of Certain Beneficial Owners and Management and Related Stockholder Matters"),
+    item("13", "Certain Relationships and Related Transactions, and Director Independence"),
+    item("14", "Principal Accountant Fees and Services"),
+    item("15", "Exhibits and Financial Statement Schedules"),
+    item("16", "Form 10-K Summary"),
+];
+
+pub const FORM_10Q_ITEMS: &[StandardItem] = &[
+    item("I-1", "Financial Statements"),
+    item(
+        "I-2",
+        "Management's Discussion and Analysis of Financial Condition and Results of Operations",
+    ),
+    item(
+        "I-3",
+        "Quantitative and Qualitative Disclosures About Market Risk",
+    ),
+    item("I-4", "Controls and Procedures"),
+    item("II-1", "Legal Proceedings"),
+    item("II-1A", "Risk Factors"),
+    item(
+        "II-2",
+        "Unregistered Sales of Equity Securities and Use of Proceeds",
+    ),
+    item("II-3", "Defaults Upon Senior Securities"),
+    item("II-4", "Mine Safety Disclosures"),
+    item("II-5", "Other Information"),
+    item("II-6", "Exhibits"),
+];
+
+/// Question keywords and the 10-K / 10-Q item codes that answer them. Keywords are specific
+/// enough not to match unrelated questions ("interest rate risks", "investment properties").
+const TOPICS: &[(&[&str], &[&str])] = &[
+    (&["risk factor"], &["1A", "II-1A"]),
+    (
+        &[
+            "md&a",
+            "management's discussion",
+            "management discussion",
+            "results of operations",
+            "liquidity",
+        ],
+        &["7", "I-2"],
+    ),
+    (
+        &["market risk", "interest rate risk", "currency risk"],
+        &["7A", "I-3"],
+    ),
+    (
+        &["legal proceeding", "litigation", "lawsuit"],
+        &["3", "II-1"],
+    ),
+    (&["cybersecurity"], &["1C"]),
+    (
+        &["controls and procedures", "internal control"],
+        &["9A", "I-4"],
+    ),
+    (&["executive compensation"], &["11"]),
+    (&["physical properties", "owned or leased"], &["2"]),
+    (&["share repurchase", "buyback"], &["5", "II-2"]),
+];
+
+/// A contiguous section of a filing document
+#[derive(Debug, Clone, PartialEq)]
+pub struct FilingSection {
+    /// Standard item code, `None` for the cover page and anything before the first item
+    pub item: Option<String>,
+    pub section_title: String,
+    pub content: String,
+}
+
+static PART_HEADING: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"(?i)^(?:#+\s*)?PART\s+(I{1,2})\b").unwrap());
+static ITEM_HEADING: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"(?i)^(?:#+\s*)?(?:PART\s+(I{1,2})\b[^a-z]*)?ITEM\s+(\d{1,2}[A-C]?)\b[.:]?")
+        .unwrap()
+});
+static ITEM_REFERENCE: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"(?i)(?:\bpart\s+(i{1,2})\b[^a-z0-9]*)?\bitem\s+(\d{1,2}[a-c]?)\b").unwrap()
+});
+
+/// Minimum body length for a section to count as the real item rather than a table of
+/// contents entry pointing at it
+const MIN_SECTION_LENGTH: usize = 200;
+const MAX_HEADING_LENGTH: usize = 200;
+
+pub fn standard_items(report_type: &ReportType) -> &'static [StandardItem] {
+    match report_type.original() {
+        ReportType::Form10K => FORM_10K_ITEMS,
+        ReportType::Form10Q => FORM_10Q_ITEMS,
+        _ => &[],
+    }
+}
+
+pub fn supports_sections(report_type: &ReportType) -> bool {
+    !standard_items(report_type).is_empty()
+}
+
+/// Split a markdown filing document into its standard items.
+///
+/// Headings are recognized on block lines only (not inside markdown tables), which keeps
+/// table-of-contents rows from opening sections. When an item is seen more than once, short
+/// occurrences (table-of-contents paragraphs, cross references) are dropped in favour of the
+/// one holding the actual text.
+pub fn split_sections(markdown: &str, report_type: &ReportType) -> Vec<FilingSection> {
+    let items = standard_items(report_type);
+    if items.is_empty() {
+        return vec![FilingSection {
+            item: None,
+            section_title: report_type.to_string(),
+            content: markdown.to_string(),
+        }];
+    }
+    let is_10q = matches!(report_type.original(), ReportType::Form10Q);
+
+    let mut sections: Vec<FilingSection> = vec![FilingSection {
+        item: None,
+        section_title: "Cover Page".to_string(),
+        content: String::new(),
+    }];
+    let mut current_part = "I".to_string();
+
+    for line in markdown.lines() {
+        let trimmed = line.trim();
+        let is_heading_candidate = !trimmed.starts_with('|') && trimmed.len() <= MAX_HEADING_LENGTH;
+
+        if is_heading_candidate {
+            if let Some(caps) = ITEM_HEADING.captures(trimmed) {
+                if let Some(part) = caps.get(1) {
+                    current_part = part.as_str().to_uppercase();
+                }
+                let number = caps[2].to_uppercase();
+                let code = if is_10q {
+                    format!("{}-{}", current_part, number)
+                } else {
+                    number
+                };
+
+                if let Some(standard) = items.iter().find(|i| i.code == code) {
+                    sections.push(FilingSection {
+                        item: Some(standard.code.to_string()),
+                        section_title: standard.title.to_string(),
+                        content: String::new(),
+                    });
+                }
+            } else if let Some(caps) = PART_HEADING.captures(trimmed) {
+                current_part = caps[1].to_uppercase();
+            }
+        }
+
+        let section = sections
+            .last_mut()
+            .expect("sections always has the cover page");
+        section.content.push_str(line);
+        section.content.push('\n');
+    }
+
+    let mut result: Vec<FilingSection> = Vec::new();
+    for section in sections {
+        let duplicate = result
+            .iter()
+            .position(|s| s.item.is_some() && s.item == section.item);
+        match duplicate {
+            Some(i) if result[i].content.trim().len() < MIN_SECTION_LENGTH => {
+                // Table of contents entries come first; keep the body in document order
+                result.remove(i);
+                result.push(section);
+            }
+            Some(_) if section.content.trim().len() < MIN_SECTION_LENGTH => {}
+            Some(i) => result[i].content.push_str(&section.content),
+            None => result.push(section),
+        }
+    }
+
+    result.retain(|s| !s.content.trim().is_empty());
+    result
+}
+
+/// Item codes a question is asking about in filings of `report_types`, from explicit "Item 7"
+/// references and topic keywords. Item numbers are read per form: in a 10-Q, "Item 2" is MD&A
+/// (I-2) unless the question says "Part II, Item 2", and "Item 1A" is Risk Factors (II-1A).
+/// Empty when none of `report_types` is split into sections.
+pub fn items_for_question(question: &str, report_types: &[ReportType]) -> Vec<String> {
+    let lowercase = question.to_lowercase();
+    let forms = report_types
+        .iter()
+        .map(standard_items)
+        .filter(|items| !items.is_empty())
+        .collect::<Vec<_>>();
+    if forms.is_empty() {
+        return Vec::new();
+    }
+
+    let mut codes = Vec::new();
+    for caps in ITEM_REFERENCE.captures_iter(&lowercase) {
+        // 8-K items ("Item 2.02") are not sections of a 10-K or 10-Q
+        let rest = &lowercase[caps.get(0).map_or(0, |m| m.end())..];
+        if rest.starts_with('.') && rest[1..].starts_with(|c: char| c.is_ascii_digit()) {
+            continue;
+        }
+        let part = caps.get(1).map(|part| part.as_str().to_uppercase());
+        let number = caps[2].to_uppercase();
+        codes.extend(
+            forms
+                .iter()
+                .filter_map(|items| item_code(items, part.as_deref(), &number)),
+        );
+    }
+
+    for (keywords, items) in TOPICS {
+        if keywords.iter().any(|k| lowercase.contains(k)) {
+            codes.extend(
+                items
+                    .iter()
+                    .filter(|code| forms.iter().any(|items| has_item(items, code)))
+                    .map(|code| code.to_string()),
+            );
+        }
+    }
+
+    codes.sort();
+    codes.dedup();
+    codes
+}
+
+fn has_item(items: &[StandardItem], code: &str) -> bool {
+    items.iter().any(|item| item.code == code)
+}
+
+/// Code of item `number` of a form, in `part` if the question named one. 10-Q items are looked
+/// up in Part I first, where the financial statements and MD&A are.
+fn item_code(items: &[StandardItem], part: Option<&str>, number: &str) -> Option<String> {
+    let candidates = match part {
+        Some(part) => vec![number.to_string(), format!("{}-{}", part, number)],
+        None => vec![
+            number.to_string(),
+            format!("I-{}", number),
+            format!("II-{}", number),
+        ],
+    };
+    candidates.into_iter().find(|code| has_item(items, code))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn body(text: &str) -> String {
+        format!("{}\n", text.repeat(30))
+    }
+
+    #[test]
+    fn test_split_10k_sections() {
+        let markdown = format!(
+            "Apple Inc.\n\n| Item 1. | Business | 1 |\n| Item 1A. | Risk Factors | 5 |\n\n\
+             Item 1A. Risk Factors\n\nPART I\n\n## Item 1. Business\n\n{}\n\
+             ITEM 1A. RISK FACTORS\n\n{}\nItem 7. Management's Discussion\n\n{}",
+            body("The Company designs smartphones. "),
+            body("Supply chain disruption may harm results. "),
+            body("Net sales increased 2% year over year. "),
+        );
+
+        let sections = split_sections(&markdown, &ReportType::Form10K);
+        let items: Vec<Option<&str>> = sections.iter().map(|s| s.item.as_deref()).collect();
+
+        assert_eq!(items, vec![None, Some("1"), Some("1A"), Some("7")]);
+        assert_eq!(sections[2].section_title, "Risk Factors");
+        assert!(sections[2].content.contains("Supply chain disruption"));
+        assert!(!sections[1].content.contains("Supply chain disruption"));
+    }
+
+    #[test]
+    fn test_split_10q_sections_by_part() {
+        let markdown = format!(
+            "PART I — FINANCIAL INFORMATION\n\nItem 2. Management's Discussion\n\n{}\n\
+             PART II — OTHER INFORMATION\n\nItem 1A. Risk Factors\n\n{}",
+            body("Revenue grew. "),
+            body("There have been no material changes. "),
+        );
+
+        let sections = split_sections(&markdown, &ReportType::Form10Q);
+        let items: Vec<Option<&str>> = sections.iter().map(|s| s.item.as_deref()).collect();
+
+        assert_eq!(items, vec![None, Some("I-2"), Some("II-1A")]);
+    }
+
+    #[test]
+    fn test_items_for_question() {
+        let periodic = [ReportType::Form10K, ReportType::Form10Q];
+        assert_eq!(
+            items_for_question("What are the key risk factors for @TSLA?", &periodic),
+            vec!["1A", "II-1A"]
+        );
+        assert_eq!(
+            items_for_question("Summarize Item 7 of the 10-K", &[ReportType::Form10K]),
+            vec!["7"]
+        );
+        assert!(items_for_question("What was revenue last quarter?", &periodic).is_empty());
+        assert!(
+            items_for_question("What risks do its investment properties carry?", &periodic)
+                .is_empty()
+        );
+        // 8-Ks are not split into sections, and their item numbers are not 10-K items
+        assert!(
+            items_for_question("What did the 8-K Item 2.02 say?", &[ReportType::Form8K]).is_empty()
+        );
+        assert!(items_for_question("What did Item 2.02 say?", &periodic).is_empty());
+        assert_eq!(
+            items_for_question("Summarize Item 7.", &[ReportType::Form10K]),
+            vec!["7"]
+        );
+    }
+
+    #[test]
+    fn test_items_for_10q_question() {
+        let form_10q = [ReportType::Form10Q];
+        assert_eq!(
+            items_for_question("What does Item 2 of the latest 10-Q say?", &form_10q),
+            vec!["I-2"]
+        );
+        assert_eq!(
+            items_for_question("Anything new in Item 1A?", &form_10q),
+            vec!["II-1A"]
+        );
+        assert_eq!(
+            items_for_question("Summarize Part II, Item 2", &form_10q),
+            vec!["II-2"]
+        );
+        assert_eq!(
+            items_for_question(
+                "Compare Item 2",
+                &[ReportType::Form10K, ReportType::Form10Q]
+            ),
+            vec!["2", "I-2"]
+        );
+        assert_eq!(
+            items_for_question("Any share buybacks?", &form_10q),
+            vec!["II-2"]
+        );
+    }
+}
@@ -0,0 +1,333 @@
+use anyhow::{anyhow, Result};
+use mime::APPLICATION_JSON;
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+use std::sync::Arc;
+use tokio::sync::RwLock;
+
+use super::client::EdgarClient;
+use super::tickers::normalize_ticker;
+
+/// A listed security from `company_tickers_exchange.json`. Companies with several share
+/// classes have one entry per ticker.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CompanyEntry {
+    /// Zero padded to 10 digits, like the rest of the EDGAR code
+    pub cik: String,
+    pub name: String,
+    pub ticker: String,
+    pub exchange: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SearchHit {
+    #[serde(flatten)]
+    pub company: CompanyEntry,
+    pub score: u32,
+}
+
+/// Ticker, CIK and company name lookups over every listed security. SEC orders the list
+/// roughly by market capitalization, which breaks ties between equally good matches.
+#[derive(Debug, Clone, Default)]
+pub struct TickerIndex {
+    entries: Vec<CompanyEntry>,
+    by_ticker: HashMap<String, usize>,
+    by_cik: HashMap<String, Vec<usize>>,
+    /// Upper-cased alphanumeric words of each company name, joined by single spaces
+    normalized_names: Vec<String>,
+}
+
+static TICKER_INDEX: Lazy<RwLock<Option<Arc<TickerIndex>>>> = Lazy::new(|| RwLock::new(None));
+
+/// `320193`, `0000320193` and `CIK0000320193` all become `0000320193`
+pub fn normalize_cik(cik: &str) -> Option<String> {
+    let digits = cik.trim();
+    let digits = digits
+        .strip_prefix("CIK")
+        .or_else(|| digits.strip_prefix("cik"))
+        .unwrap_or(digits);
+    if digits.is_empty() || digits.len() > 10 || !digits.chars().all(|c| c.is_ascii_digit()) {
+        return None;
+    }
+    Some(format!("{:0>10}", digits))
+}
+
+fn normalize_name(name: &str) -> String {
+    name.split(|c: char| !c.is_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .map(|word| word.to_uppercase())
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Whether the characters of `query` appear in order in `name`, starting at a word. Returns
+/// the number of skipped characters, fewer means a tighter match.
+fn subsequence_gaps(name: &str, query: &str) -> Option<usize> {
+    let mut query_chars = query.chars().filter(|c| !c.is_whitespace()).peekable();
+    let first = *query_chars.peek()?;
+    let start = name
+        .split(' ')
+        .scan(0, |offset, word| {
+            let start = *offset;
+            *offset += word.len() + 1;
+            Some((start, word))
+        })
+        .find(|(_, word)| word.starts_with(first))
+        .map(|(start, _)| start)?;
+
+    let mut gaps = 0;
+    for c in name[start..].chars() {
+        match query_chars.peek() {
+            Some(&q) if q == c => {
+                query_chars.next();
+            }
+            Some(_) => gaps += 1,
+            None => break,
+        }
+    }
+    query_chars.peek().is_none().then_some(gaps)
+}
+
+impl TickerIndex {
+    pub fn from_entries(entries: Vec<CompanyEntry>) -> Self {
+        let mut index = TickerIndex {
+            normalized_names: entries.iter().map(|e| normalize_name(&e.name)).collect(),
+            ..Default::default()
+        };
+        for (i, entry) in entries.iter().enumerate() {
+            index.by_ticker.entry(entry.ticker.clone()).or_insert(i);
+            index.by_cik.entry(entry.cik.clone()).or_default().push(i);
+        }
+        index.entries = entries;
+        index
+    }
+
+    /// Parse `company_tickers_exchange.json`:
+    /// `{"fields": ["cik", "name", "ticker", "exchange"], "data": [[320193, "Apple Inc.", "AAPL", "Nasdaq"], ...]}`
+    pub fn from_json(json: &str) -> Result<Self> {
+        let value: Value = serde_json::from_str(json)?;
+        let fields = value["fields"]
+            .as_array()
+            .ok_or_else(|| anyhow!("Missing fields in ticker exchange list"))?;
+        let column = |name: &str| {
+            fields
+                .iter()
+                .position(|field| field.as_str() == Some(name))
+                .ok_or_else(|| anyhow!("Missing {} column in ticker exchange list", name))
+        };
+        let (cik, name, ticker, exchange) = (
+            column("cik")?,
+            column("name")?,
+            column("ticker")?,
+            column("exchange")?,
+        );
+
+        let entries = value["data"]
+            .as_array()
+            .ok_or_else(|| anyhow!("Missing data in ticker exchange list"))?
+            .iter()
+            .filter_map(|row| {
+                let cik = match &row[cik] {
+                    Value::Number(n) => n.as_u64().map(|n| format!("{:010}", n)),
+                    Value::String(s) => normalize_cik(s),
+                    _ => None,
+                }?;
+                Some(CompanyEntry {
+                    cik,
+                    name: row[name].as_str()?.trim().to_string(),
+                    ticker: normalize_ticker(row[ticker].as_str()?).ok()?,
+                    exchange: row[exchange].as_str().map(str::to_string),
+                })
+            })
+            .collect();
+        Ok(TickerIndex::from_entries(entries))
+    }
+
+    pub fn load(path: &Path) -> Result<Self> {
+        TickerIndex::from_json(&fs::read_to_string(path)?)
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    pub fn entries(&self) -> &[CompanyEntry] {
+        &self.entries
+    }
+
+    /// Any share class spelling works, `BRK.B` finds `BRK-B`
+    pub fn by_ticker(&self, ticker: &str) -> Option<&CompanyEntry> {
+        self.by_ticker
+            .get(&normalize_ticker(ticker).ok()?)
+            .map(|&i| &self.entries[i])
+    }
+
+    /// Every listed security of a company, e.g. both share classes of Alphabet
+    pub fn by_cik(&self, cik: &str) -> Vec<&CompanyEntry> {
+        normalize_cik(cik)
+            .and_then(|cik| self.by_cik.get(&cik))
+            .map(|indices| indices.iter().map(|&i| &self.entries[i]).collect())
+            .unwrap_or_default()
+    }
+
+    /// How well an entry matches the upper-cased query, compared as is to tickers and
+    /// normalized to words to names. `None` if it does not match at all.
+    fn score(&self, i: usize, ticker_query: &str, query: &str) -> Option<u32> {
+        let ticker = &self.entries[i].ticker;
+        let name = &self.normalized_names[i];
+
+        if ticker == ticker_query {
+            Some(1000)
+        } else if name == query {
+            Some(900)
+        } else if ticker.starts_with(ticker_query) {
+            Some(800 - (ticker.len() - ticker_query.len()).min(100) as u32)
+        } else if name.starts_with(query) {
+            Some(700)
+        } else if name.split(' ').any(|word| word.starts_with(query)) {
+            Some(600)
+        } else if name.contains(query) {
+            Some(400)
+        } else {
+            subsequence_gaps(name, query).map(|gaps| 300u32.saturating_sub(gaps as u32 * 5))
+        }
+    }
+
+    /// Fuzzy search over tickers and company names (`apple` finds AAPL), optionally limited
+    /// to one exchange (`Nasdaq`, `NYSE`, `OTC`, ...). A CIK (`320193`, `CIK320193`) finds the
+    /// company directly.
+    pub fn search(&self, query: &str, exchange: Option<&str>, limit: usize) -> Vec<SearchHit> {
+        let on_exchange = |entry: &CompanyEntry| {
+            exchange.is_none_or(|exchange| {
+                entry
+                    .exchange
+                    .as_deref()
+                    .is_some_and(|e| e.eq_ignore_ascii_case(exchange))
+            })
+        };
+
+        let ticker_query = normalize_ticker(query).unwrap_or_else(|_| query.trim().to_uppercase());
+        let query = normalize_name(query);
+        if query.is_empty() {
+            return Vec::new();
+        }
+
+        let mut hits = if let Some(cik) = normalize_cik(&query) {
+            self.by_cik(&cik)
+                .into_iter()
+                .map(|company| (1000, company))
+                .collect::<Vec<_>>()
+        } else {
+            // Scored in list order, so the stable sort keeps larger companies first on ties
+            (0..self.entries.len())
+                .filter_map(|i| {
+                    self.score(i, &ticker_query, &query)
+                        .map(|score| (score, &self.entries[i]))
+                })
+                .collect::<Vec<_>>()
+        };
+        hits.retain(|(_, company)| on_exchange(company));
+        hits.sort_by_key(|(score, _)| std::cmp::Reverse(*score));
+
+        hits.into_iter()
+            .take(limit)
+            .map(|(score, company)| SearchHit {
+                company: company.clone(),
+                score,
+            })
+            .collect()
+    }
+}
+
+/// Download (or revalidate) `company_tickers_exchange.json` and return the index built from it
+pub async fn fetch_ticker_index(client: &EdgarClient) -> Result<Arc<TickerIndex>> {
+    let path = client.tickers_exchange_path();
+    let downloaded = match client
+        .fetch_if_stale(&client.tickers_exchange_url(), &path, APPLICATION_JSON)
+        .await
+    {
+        Ok(downloaded) => downloaded,
+        Err(e) if path.exists() => {
+            log::warn!(
+                "Failed to refresh ticker exchange list, using existing file: {}",
+                e
+            );
+            false
+        }
+        Err(e) => return Err(e),
+    };
+
+    if !downloaded {
+        if let Some(index) = TICKER_INDEX.read().await.as_ref() {
+            return Ok(index.clone());
+        }
+    }
+
+    let index = Arc::new(TickerIndex::load(&path)?);
+    log::debug!("Loaded ticker index with {} securities", index.len());
+    *TICKER_INDEX.write().await = Some(index.clone());
+    Ok(index)
+}
+
+/// Download the ticker exchange list even if the cached copy is still fresh
+pub async fn refresh_ticker_index(client: &EdgarClient) -> Result<Arc<TickerIndex>> {
+    crate::utils::cache::invalidate(&client.tickers_exchange_path())?;
+    fetch_ticker_index(client).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TICKERS: &str = r#"{
+        "fields": ["cik", "name", "ticker", "exchange"],
+        "data": [
+            [320193, "Apple Inc.", "AAPL", "Nasdaq"],
+            [1652044, "Alphabet Inc.", "GOOGL", "Nasdaq"],
+            [1652044, "Alphabet Inc.", "GOOG", "Nasdaq"],
+            [1067983, "BERKSHIRE HATHAWAY INC", "BRK-B", "NYSE"],
+            [1418121, "Apple Hospitality REIT, Inc.", "APLE", "NYSE"],
+            [1234567, "Pineapple Energy Inc.", "PEGY", null]
+        ]
+    }"#;
+
+    #[test]
+    fn test_search_ticker_index() {
+        let index = TickerIndex::from_json(TICKERS).unwrap();
+        assert_eq!(index.len(), 6);
+        assert_eq!(index.by_ticker("brk-b").unwrap().cik, "0001067983");
+        assert_eq!(index.by_cik("CIK0001652044").len(), 2);
+
+        let tickers = |hits: Vec<SearchHit>| {
+            hits.into_iter()
+                .map(|hit| hit.company.ticker)
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(
+            tickers(index.search("apple", None, 10)),
+            vec!["AAPL", "APLE", "PEGY"]
+        );
+        assert_eq!(
+            tickers(index.search("apple", Some("nyse"), 10)),
+            vec!["APLE"]
+        );
+        assert_eq!(
+            tickers(index.search("berkshire hath", None, 10)),
+            vec!["BRK-B"]
+        );
+        assert_eq!(tickers(index.search("alph inc", None, 1)), vec!["GOOGL"]);
+        assert_eq!(tickers(index.search("320193", None, 10)), vec!["AAPL"]);
+        assert_eq!(tickers(index.search("cik320193", None, 10)), vec!["AAPL"]);
+        assert_eq!(tickers(index.search("brk-b", None, 10)), vec!["BRK-B"]);
+        assert_eq!(tickers(index.search("BRK.B", None, 1)), vec!["BRK-B"]);
+        assert_eq!(index.by_ticker("BRK/B").unwrap().ticker, "BRK-B");
+    }
+}
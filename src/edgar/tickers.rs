@@ -1,45 +1,83 @@
 use anyhow::{anyhow, Result};
 use mime::APPLICATION_JSON;
 use once_cell::sync::Lazy;
-use reqwest::{Client, Url};
 use serde_json::Value;
 use std::collections::HashMap;
 use std::fs;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
 use tokio::sync::RwLock;
 
-const TICKER_URL: &str = "https://www.sec.gov/files/company_tickers.json";
+use super::client::EdgarClient;
+use super::ticker_index::normalize_cik;
 
 pub type TickerData = (Ticker, String, String); // (ticker, company name, CIK)
 
 #[derive(Debug, Clone)]
 pub struct TickerMaps {
-    ticker_to_cik: HashMap<String, (String, String)>,  // Ticker -> (CIK, Name)
-    cik_to_ticker: HashMap<String, (String, String)>,  // CIK -> (Ticker, Name)
+    ticker_to_cik: HashMap<String, (String, String)>, // Ticker -> (CIK, Name)
+    cik_to_ticker: HashMap<String, (String, String)>, // CIK -> (Ticker, Name)
 }
 
-static TICKER_MAPS: Lazy<RwLock<Option<Arc<TickerMaps>>>> = Lazy::new(|| RwLock::new(None));
+/// Ticker maps loaded so far, keyed by the ticker file they were loaded from, so clients with
+/// different cache directories (a mock EDGAR in tests) do not share a ticker list
+static TICKER_MAPS: Lazy<RwLock<HashMap<PathBuf, Arc<TickerMaps>>>> =
+    Lazy::new(|| RwLock::new(HashMap::new()));
 
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub struct Ticker(String);
 
+/// Characters written between a ticker and its share class: `BRK.B` on exchanges and most
+/// data providers, `BRK/B` and `BRK B` on terminals, `BRK_B` in file names and `BRK-B` at SEC
+const SHARE_CLASS_SEPARATORS: [char; 5] = ['-', '.', '/', ' ', '_'];
+
+/// The padded CIK of an entity referenced by CIK instead of ticker (`CIK320193`, `@CIK0000320193`),
+/// for delisted issuers, private debt issuers and funds that have no ticker
+pub fn cik_reference(reference: &str) -> Option<String> {
+    let trimmed = reference.trim();
+    let trimmed = trimmed.strip_prefix('@').unwrap_or(trimmed);
+    let digits = trimmed
+        .get(..3)?
+        .eq_ignore_ascii_case("CIK")
+        .then(|| &trimmed[3..])?;
+    normalize_cik(digits)
+}
+
+/// Canonical spelling of a ticker: upper case, without the REPL's `@` prefix and with the share
+/// class separated by `-` like SEC's ticker lists, so `brk.b`, `BRK/B` and `@BRK-B` are all `BRK-B`.
+/// CIK references are padded, `@cik320193` becomes `CIK0000320193`.
+pub fn normalize_ticker(ticker: &str) -> Result<String> {
+    if let Some(cik) = cik_reference(ticker) {
+        return Ok(format!("CIK{}", cik));
+    }
+    let trimmed = ticker.trim();
+    let trimmed = trimmed.strip_prefix('@').unwrap_or(trimmed);
+    if trimmed.is_empty() {
+        return Err(anyhow!("Ticker cannot be empty"));
+    }
+
+    let parts = trimmed.split(SHARE_CLASS_SEPARATORS).collect::<Vec<_>>();
+    if parts
+        .iter()
+        .any(|part| part.is_empty() || !part.chars().all(|c| c.is_ascii_alphanumeric()))
+    {
+        return Err(anyhow!(
+            "Ticker must contain only alphanumeric characters and share class separators: {}",
+            ticker
+        ));
+    }
+    Ok(parts.join("-").to_uppercase())
+}
+
 impl Ticker {
     pub fn new(ticker: String) -> Result<Self> {
-        let uppercase_ticker = ticker.to_uppercase();
-        if uppercase_ticker.is_empty() {
-            return Err(anyhow!("Ticker cannot be empty"));
-        }
-        if !uppercase_ticker
-            .chars()
-            .all(|c| c.is_ascii_alphanumeric() || c == '-')
-        {
-            return Err(anyhow!(
-                "Ticker must contain only alphanumeric characters or hyphens: {}",
-                ticker
-            ));
-        }
-        Ok(Ticker(uppercase_ticker))
+        Ok(Ticker(normalize_ticker(&ticker)?))
+    }
+
+    /// The ticker as spelled by a provider that separates share classes with `separator`,
+    /// e.g. `BRK.B` for `'.'`
+    pub fn with_separator(&self, separator: char) -> String {
+        self.0.replace('-', &separator.to_string())
     }
 
     pub fn as_str(&self) -> &str {
@@ -59,18 +97,8 @@ impl std::fmt::Display for Ticker {
     }
 }
 
-static USER_AGENT: &str = "software@example.com";
-
-pub async fn get_ticker_maps() -> Result<Arc<TickerMaps>> {
-    // First try to read from cache
-    if let Some(cache) = TICKER_MAPS.read().await.as_ref() {
-        return Ok(cache.clone());
-    }
-
-    // If not in cache, load it
-    let mut write_guard = TICKER_MAPS.write().await;
-    if write_guard.is_none() {
-        let tickers = load_tickers()?;
+impl TickerMaps {
+    fn from_tickers(tickers: Vec<TickerData>) -> Self {
         let mut maps = TickerMaps {
             ticker_to_cik: HashMap::new(),
             cik_to_ticker: HashMap::new(),
@@ -78,60 +106,111 @@ pub async fn get_ticker_maps() -> Result<Arc<TickerMaps>> {
 
         for (ticker, name, cik) in tickers {
             let ticker_str = ticker.as_str().to_string();
-            maps.ticker_to_cik.insert(ticker_str.clone(), (cik.clone(), name.clone()));
+            maps.ticker_to_cik
+                .insert(ticker_str.clone(), (cik.clone(), name.clone()));
             maps.cik_to_ticker.insert(cik, (ticker_str, name));
         }
-        
-        *write_guard = Some(Arc::new(maps));
+        maps
+    }
+}
+
+pub async fn get_ticker_maps(client: &EdgarClient) -> Result<Arc<TickerMaps>> {
+    let path = client.tickers_path();
+    // First try to read from cache
+    if let Some(cache) = TICKER_MAPS.read().await.get(&path) {
+        return Ok(cache.clone());
+    }
+
+    // If not in cache, load it
+    let mut write_guard = TICKER_MAPS.write().await;
+    if let Some(cache) = write_guard.get(&path) {
+        return Ok(cache.clone());
     }
-    
-    Ok(write_guard.as_ref().unwrap().clone())
+    let maps = Arc::new(TickerMaps::from_tickers(load_tickers(client)?));
+    write_guard.insert(path, maps.clone());
+    Ok(maps)
 }
 
-pub async fn get_cik_for_ticker(ticker: &str) -> Result<String> {
-    let mapping = get_ticker_maps().await?;
-    mapping.ticker_to_cik
-        .get(ticker)
+pub async fn get_cik_for_ticker(client: &EdgarClient, ticker: &str) -> Result<String> {
+    let mapping = get_ticker_maps(client).await?;
+    mapping
+        .ticker_to_cik
+        .get(&normalize_ticker(ticker)?)
         .map(|(cik, _)| cik.clone())
         .ok_or_else(|| anyhow!("No CIK found for ticker: {}", ticker))
 }
 
-pub async fn get_ticker_for_cik(cik: &str) -> Result<String> {
-    let mapping = get_ticker_maps().await?;
-    mapping.cik_to_ticker
+/// CIK of a ticker or of a CIK reference, which needs no ticker list lookup
+pub async fn resolve_cik(client: &EdgarClient, ticker: &str) -> Result<String> {
+    match cik_reference(ticker) {
+        Some(cik) => Ok(cik),
+        None => get_cik_for_ticker(client, ticker).await,
+    }
+}
+
+pub async fn get_company_name_for_ticker(client: &EdgarClient, ticker: &str) -> Result<String> {
+    let mapping = get_ticker_maps(client).await?;
+    mapping
+        .ticker_to_cik
+        .get(&normalize_ticker(ticker)?)
+        .map(|(_, name)| name.clone())
+        .ok_or_else(|| anyhow!("No company found for ticker: {}", ticker))
+}
+
+pub async fn get_ticker_for_cik(client: &EdgarClient, cik: &str) -> Result<String> {
+    let mapping = get_ticker_maps(client).await?;
+    mapping
+        .cik_to_ticker
         .get(cik)
         .map(|(ticker, _)| ticker.clone())
         .ok_or_else(|| anyhow!("No ticker found for CIK: {}", cik))
 }
 
-pub async fn fetch_tickers() -> Result<Vec<TickerData>> {
+pub async fn fetch_tickers(client: &EdgarClient) -> Result<Vec<TickerData>> {
     log::debug!("Fetching tickers from SEC");
-    let client = Client::new();
-    let url = Url::parse(TICKER_URL)?;
-    let path = Path::new("data/edgar/tickers.json");
+    let path = client.tickers_path();
     log::debug!("Checking for existing tickers file at {:?}", path);
 
-    if !path.exists() {
-        log::debug!("Tickers file not found, downloading from SEC");
-        crate::utils::http::fetch_and_save(
-            &client,
-            &url,
-            path,
-            USER_AGENT,
-            APPLICATION_JSON,
-            crate::edgar::rate_limiter(),
-        )
-        .await?;
-        log::debug!("Successfully downloaded tickers file");
-    } else {
-        log::debug!("Using existing tickers file");
+    let downloaded = match client
+        .fetch_if_stale(&client.tickers_url(), &path, APPLICATION_JSON)
+        .await
+    {
+        Ok(true) => {
+            log::debug!("Successfully downloaded tickers file");
+            true
+        }
+        Ok(false) => {
+            log::debug!("Using existing tickers file");
+            false
+        }
+        Err(e) if path.exists() => {
+            log::warn!("Failed to refresh tickers, using existing file: {}", e);
+            false
+        }
+        Err(e) => return Err(e),
+    };
+
+    let tickers = load_tickers_from(&path)?;
+    // Lookups go through the in-memory maps, rebuild them from the list we just loaded
+    let mut maps = TICKER_MAPS.write().await;
+    if downloaded || !maps.contains_key(&path) {
+        maps.insert(path, Arc::new(TickerMaps::from_tickers(tickers.clone())));
     }
+    Ok(tickers)
+}
 
-    load_tickers()
+/// Download the ticker list even if the cached copy is still fresh
+pub async fn refresh_tickers(client: &EdgarClient) -> Result<Vec<TickerData>> {
+    crate::utils::cache::invalidate(&client.tickers_path())?;
+    fetch_tickers(client).await
 }
 
-pub fn load_tickers() -> Result<Vec<TickerData>> {
-    let path = Path::new("data/edgar/tickers.json");
+/// Ticker list cached in the client's cache directory by `fetch_tickers`
+pub fn load_tickers(client: &EdgarClient) -> Result<Vec<TickerData>> {
+    load_tickers_from(&client.tickers_path())
+}
+
+fn load_tickers_from(path: &Path) -> Result<Vec<TickerData>> {
     log::debug!("Loading tickers from {:?}", path);
     if path.exists() {
         log::debug!("Reading tickers file");
@@ -160,3 +239,70 @@ pub fn load_tickers() -> Result<Vec<TickerData>> {
         ))
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::config::AdvisorConfig;
+    use crate::edgar::client::{DEFAULT_CACHE_TTL, DEFAULT_REQUESTS_PER_SECOND};
+
+    #[test]
+    fn test_normalize_share_class_tickers() {
+        for spelling in ["BRK.B", "brk-b", "BRK/B", "BRK B", "BRK_B", "@BRK.B"] {
+            assert_eq!(normalize_ticker(spelling).unwrap(), "BRK-B");
+        }
+        assert_eq!(normalize_ticker(" aapl ").unwrap(), "AAPL");
+        assert!(normalize_ticker("BRK..B").is_err());
+        assert!(normalize_ticker("BRK.").is_err());
+        assert!(normalize_ticker("@").is_err());
+        assert_eq!(normalize_ticker("@cik320193").unwrap(), "CIK0000320193");
+        assert_eq!(
+            cik_reference("CIK0000320193").as_deref(),
+            Some("0000320193")
+        );
+        assert_eq!(cik_reference("CIEN"), None);
+
+        let ticker = Ticker::new("brk/a".to_string()).unwrap();
+        assert_eq!(ticker.as_str(), "BRK-A");
+        assert_eq!(ticker.with_separator('.'), "BRK.A");
+    }
+
+    #[tokio::test]
+    async fn test_ticker_maps_per_cache_dir() {
+        let config = AdvisorConfig {
+            openai_key: String::new(),
+            database_url: String::new(),
+            user_agent: "Advisor Tests tests@example.com".to_string(),
+            data_dir: PathBuf::from("data"),
+            edgar_requests_per_second: DEFAULT_REQUESTS_PER_SECOND,
+            edgar_cache_ttl: DEFAULT_CACHE_TTL,
+        };
+        let mut dirs = Vec::new();
+        let mut clients = Vec::new();
+        for cik in [320193, 1067983] {
+            let dir = tempfile::tempdir().unwrap();
+            let client = EdgarClient::new(&config)
+                .unwrap()
+                .with_cache_dir(dir.path());
+            fs::write(
+                client.tickers_path(),
+                format!(
+                    r#"{{"0": {{"cik_str": {}, "ticker": "BRK-B", "title": "Test"}}}}"#,
+                    cik
+                ),
+            )
+            .unwrap();
+            dirs.push(dir);
+            clients.push(client);
+        }
+
+        assert_eq!(
+            resolve_cik(&clients[0], "brk.b").await.unwrap(),
+            "0000320193"
+        );
+        assert_eq!(
+            resolve_cik(&clients[1], "brk.b").await.unwrap(),
+            "0001067983"
+        );
+    }
+}
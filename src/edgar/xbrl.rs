@@ -4,6 +4,11 @@ use scraper::Html;
 use serde::{Deserialize, Serialize};
 use std::collections::{HashMap, HashSet};
 
+use super::linkbase::{Calculation, Inconsistency, Statement};
+use super::numeric::{NumericUnit, NumericValue, Precision};
+
+pub(crate) const XSI_NAMESPACE: &str = "http://www.w3.org/2001/XMLSchema-instance";
+
 // Define structs
 
 #[derive(Clone, Debug, Deserialize, Serialize)]
@@ -22,6 +27,26 @@ pub struct Dimension {
     pub axis_name: String, // was key_value
     pub member_ns: String,
     pub member_name: String, // was member_value
+    /// Labels from the filing's label linkbase, see [`super::linkbase::Labels`]
+    #[serde(default)]
+    pub axis_label: Option<String>,
+    #[serde(default)]
+    pub member_label: Option<String>,
+}
+
+impl Dimension {
+    /// Label of the axis, or its QName when the filing has no label for it
+    pub fn axis_display(&self) -> String {
+        self.axis_label
+            .clone()
+            .unwrap_or_else(|| format!("{}:{}", self.axis_ns, self.axis_name))
+    }
+
+    pub fn member_display(&self) -> String {
+        self.member_label
+            .clone()
+            .unwrap_or_else(|| format!("{}:{}", self.member_ns, self.member_name))
+    }
 }
 
 #[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
@@ -54,6 +79,46 @@ pub struct FactItem {
     pub dimensions: Vec<Dimension>,
     pub units: Vec<Unit>,
     pub periods: Vec<Period>,
+    /// Standard and terse label of the concept from the filing's label linkbase
+    #[serde(default)]
+    pub label: Option<String>,
+    #[serde(default)]
+    pub terse_label: Option<String>,
+    /// Typed value of a numeric fact, one with a `unitRef`
+    #[serde(default)]
+    pub numeric: Option<NumericValue>,
+}
+
+impl FactItem {
+    /// `Label (prefix:Concept)`, or just the QName when the concept has no label
+    pub fn display_name(&self) -> String {
+        match &self.label {
+            Some(label) => format!("{} ({}:{})", label, self.prefix, self.name),
+            None => format!("{}:{}", self.prefix, self.name),
+        }
+    }
+
+    /// `2023-09-30` for instants, `2022-09-25/2023-09-30` for durations
+    pub fn period_key(&self) -> Option<String> {
+        let mut start_date = None;
+        let mut end_date = None;
+
+        for period in &self.periods {
+            match period.period_type.as_str() {
+                "startDate" => start_date = Some(period.period_value.clone()),
+                "endDate" => end_date = Some(period.period_value.clone()),
+                "instant" => start_date = Some(period.period_value.clone()),
+                _ => {}
+            }
+        }
+
+        match (start_date, end_date) {
+            (Some(start), Some(end)) if start == end => Some(start),
+            (Some(start), Some(end)) => Some(format!("{}/{}", start, end)),
+            (Some(date), None) | (None, Some(date)) => Some(date),
+            (None, None) => None,
+        }
+    }
 }
 
 // Logic for dimensions table
@@ -67,6 +132,8 @@ pub struct DimensionTableRow {
     pub axis_tag: String,
     pub member_prefix: String,
     pub member_tag: String,
+    pub axis_label: Option<String>,
+    pub member_label: Option<String>,
 }
 
 pub fn dimensions_to_table(facts: Vec<FactItem>) -> Vec<DimensionTableRow> {
@@ -86,6 +153,8 @@ pub fn dimensions_to_table(facts: Vec<FactItem>) -> Vec<DimensionTableRow> {
                         axis_prefix: dimension.axis_ns.clone(), // was key_ns
                         member_tag: dimension.member_name.clone(), // was member_value
                         member_prefix: dimension.member_ns.clone(),
+                        axis_label: dimension.axis_label.clone(),
+                        member_label: dimension.member_label.clone(),
                     };
                     table_rows.push(row);
                     context_ref_tracker.push(fact.context_ref.clone().expect("No context ref"));
@@ -105,12 +174,14 @@ pub struct FactTableRow {
     // pub accession_number: String,
     pub context_ref: Option<String>,
     pub tag: String,
+    pub label: Option<String>,
     pub value: String,
     pub prefix: String,
     pub period_start: Option<String>,
     pub period_end: Option<String>,
     pub point_in_time: Option<String>,
     pub unit: Option<String>,
+    pub numeric: Option<NumericValue>,
     pub num_dim: u32,
 }
 
@@ -124,6 +195,7 @@ pub fn facts_to_table(facts: Vec<FactItem>) -> Vec<FactTableRow> {
         let mut row = FactTableRow {
             context_ref: fact.context_ref.clone(),
             tag: fact.name.clone(),
+            label: fact.label.clone(),
             prefix: fact.prefix.clone(),
             num_dim: fact.dimensions.len() as u32,
             value: fact.value.clone(),
@@ -131,6 +203,7 @@ pub fn facts_to_table(facts: Vec<FactItem>) -> Vec<FactTableRow> {
             period_end: None,
             point_in_time: None,
             unit: None,
+            numeric: fact.numeric.clone(),
         };
 
         // Periods are processed into three different columns
@@ -162,184 +235,254 @@ pub fn facts_to_table(facts: Vec<FactItem>) -> Vec<FactTableRow> {
     table_rows
 }
 
-pub fn parse_xml_to_facts(raw_xml: String) -> Vec<FactItem> {
-    // -- Parse the XML --
-    let re = Regex::new(r"\s+").unwrap();
-    let raw_xml = re.replace_all(raw_xml.as_str(), " ").to_string();
-
-    let xml_tree = roxmltree::Document::parse(raw_xml.as_str()).expect("Error parsing XML"); // Error handling?
+/// Errors of [`parse_xml_to_facts`] and [`super::ixbrl::parse_ixbrl_to_facts`]. Only XML
+/// syntax errors fail the whole instance, other bad facts are skipped with a warning.
+#[derive(Debug)]
+pub enum XbrlError {
+    /// The instance is not well-formed XML
+    Xml(roxmltree::Error),
+    /// A fact refers to a context the instance does not define (or that could not be parsed)
+    MissingContext { fact: String, context: String },
+    /// A dimension or explicit member that is not a `prefix:name` QName
+    InvalidQName(String),
+    /// An inline XBRL value its `format` transform can't read
+    InvalidValue { fact: String, value: String },
+}
 
-    // -- Get elements out of XML --
+impl std::fmt::Display for XbrlError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            XbrlError::Xml(e) => write!(f, "Invalid XBRL XML: {}", e),
+            XbrlError::MissingContext { fact, context } => {
+                write!(f, "Fact {} refers to unknown context {}", fact, context)
+            }
+            XbrlError::InvalidQName(qname) => write!(f, "Invalid QName {:?}", qname),
+            XbrlError::InvalidValue { fact, value } => {
+                write!(f, "Invalid value {:?} of fact {}", value, fact)
+            }
+        }
+    }
+}
 
-    let elem = xml_tree
-        .root_element()
-        .children()
-        .filter(|e| e.node_type() == roxmltree::NodeType::Element);
+impl std::error::Error for XbrlError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            XbrlError::Xml(e) => Some(e),
+            _ => None,
+        }
+    }
+}
 
-    // -- Process the context elements --
+impl From<roxmltree::Error> for XbrlError {
+    fn from(e: roxmltree::Error) -> Self {
+        XbrlError::Xml(e)
+    }
+}
 
-    let mut units: HashMap<String, Vec<Unit>> = HashMap::new();
-    let mut periods: HashMap<String, Vec<Period>> = HashMap::new();
-    let mut dimensions: HashMap<String, Vec<Dimension>> = HashMap::new();
-
-    // --- Process the unit elements ---
-
-    let unit_ele = elem.clone().filter(|e| e.tag_name().name() == "unit");
-    '_unit_loop: for child in unit_ele.into_iter() {
-        let id = child.attribute("id").unwrap_or("");
-        let measure_nodes = child
-            .descendants()
-            .filter(|e| e.tag_name().name() == "measure");
-
-        for m_ele in measure_nodes.into_iter() {
-            let name = m_ele.parent().unwrap().tag_name().name();
-            let value = m_ele.text().unwrap_or("");
-            units.entry(id.to_string()).or_default().push(Unit {
-                unit_type: name.to_string(),
-                unit_value: value.to_string(),
-            });
+/// Split a QName (`us-gaap:StatementBusinessSegmentsAxis`) into prefix and local name
+pub(crate) fn split_qname(qname: &str) -> Result<(&str, &str), XbrlError> {
+    match qname.trim().split_once(':') {
+        Some((prefix, name)) if !prefix.is_empty() && !name.is_empty() && !name.contains(':') => {
+            Ok((prefix, name))
         }
+        _ => Err(XbrlError::InvalidQName(qname.to_string())),
     }
+}
 
-    // --- Process the context elements ---
+/// Dimension of an `explicitMember` or `typedMember` segment element. Typed members have
+/// no QName value, their member is the content of the typed domain element.
+fn segment_dimension(segment: roxmltree::Node) -> Result<Dimension, XbrlError> {
+    let (axis_ns, axis_name) = split_qname(segment.attribute("dimension").unwrap_or(""))?;
+    let (member_ns, member_name) = if segment.tag_name().name() == "typedMember" {
+        match segment.children().find(|e| e.is_element()) {
+            Some(domain) => {
+                let namespace = domain.tag_name().namespace().unwrap_or("");
+                (
+                    domain.lookup_prefix(namespace).unwrap_or("").to_string(),
+                    domain.text().unwrap_or("").trim().to_string(),
+                )
+            }
+            None => (
+                String::new(),
+                segment.text().unwrap_or("").trim().to_string(),
+            ),
+        }
+    } else {
+        let (ns, name) = split_qname(segment.text().unwrap_or(""))?;
+        (ns.to_string(), name.to_string())
+    };
+    Ok(Dimension {
+        axis_ns: axis_ns.to_string(),     // was key_ns in fast_xbrl_parser
+        axis_name: axis_name.to_string(), // was key_value in fast_xbrl_parser
+        member_ns,
+        member_name, // was member_value in fast_xbrl_parser
+        axis_label: None,
+        member_label: None,
+    })
+}
 
-    let context_ele = elem.clone().filter(|e| e.tag_name().name() == "context");
-    '_context_loop: for child in context_ele.into_iter() {
-        let id = child.attribute("id").unwrap_or("");
-        log::debug!("ID {}", id);
+/// Units, periods and dimensions of the contexts of an instance, keyed by their ids
+#[derive(Debug, Default)]
+pub(crate) struct Contexts {
+    units: HashMap<String, Vec<Unit>>,
+    periods: HashMap<String, Vec<Period>>,
+    dimensions: HashMap<String, Vec<Dimension>>,
+    // Contexts that were parsed successfully, facts referring to any other are skipped
+    ids: HashSet<String>,
+}
 
-        let node_desc = child
-            .children()
-            .filter(|e| e.node_type() == roxmltree::NodeType::Element);
+impl Contexts {
+    /// Collect the `unit` and `context` elements among `elements`
+    pub(crate) fn parse<'a, 'input: 'a>(
+        elements: impl Iterator<Item = roxmltree::Node<'a, 'input>> + Clone,
+    ) -> Self {
+        let mut contexts = Contexts::default();
+
+        // --- Process the unit elements ---
+
+        let unit_ele = elements.clone().filter(|e| e.tag_name().name() == "unit");
+        '_unit_loop: for child in unit_ele.into_iter() {
+            let id = child.attribute("id").unwrap_or("");
+            let measure_nodes = child
+                .descendants()
+                .filter(|e| e.tag_name().name() == "measure");
+
+            for m_ele in measure_nodes.into_iter() {
+                let name = m_ele.parent().map_or("", |p| p.tag_name().name());
+                let value = m_ele.text().unwrap_or("").trim();
+                contexts
+                    .units
+                    .entry(id.to_string())
+                    .or_default()
+                    .push(Unit {
+                        unit_type: name.to_string(),
+                        unit_value: value.to_string(),
+                    });
+            }
+        }
 
-        // loop over descendants and process the different types of elements
-        for child_ele in node_desc.into_iter() {
-            match child_ele.tag_name().name() {
-                "period" => {
-                    log::debug!("Found period");
+        // --- Process the context elements ---
 
-                    let to_keep = ["instant", "startDate", "endDate"];
-                    let node_desc_filtered = child_ele
-                        .descendants()
-                        .filter(|e| to_keep.contains(&e.tag_name().name()));
+        let context_ele = elements.filter(|e| e.tag_name().name() == "context");
+        '_context_loop: for child in context_ele.into_iter() {
+            let id = child.attribute("id").unwrap_or("");
+            log::debug!("ID {}", id);
 
-                    for child_ele_filtered in node_desc_filtered.into_iter() {
-                        let value = child_ele_filtered.text().unwrap_or("");
-                        let name = child_ele_filtered.tag_name().name();
-                        let _namespace = child_ele_filtered.tag_name().namespace().unwrap_or("");
+            let node_desc = child
+                .children()
+                .filter(|e| e.node_type() == roxmltree::NodeType::Element);
 
-                        periods.entry(id.to_string()).or_default().push(Period {
-                            period_type: name.to_string(),
-                            period_value: value.to_string(),
-                        });
+            // loop over descendants and process the different types of elements
+            for child_ele in node_desc.into_iter() {
+                match child_ele.tag_name().name() {
+                    "period" => {
+                        log::debug!("Found period");
 
-                        log::debug!("Period: {} {}", name, value);
-                    }
-                }
-                "entity" => {
-                    log::debug!("Found entity");
-
-                    let to_keep = ["explicitMember"];
-                    let node_desc_filtered = child_ele
-                        .descendants()
-                        .filter(|e| to_keep.contains(&e.tag_name().name()));
-
-                    for child_ele_filtered in node_desc_filtered.into_iter() {
-                        let value = child_ele_filtered.text().unwrap_or("");
-                        let _name = child_ele_filtered.tag_name().name();
-                        let _namespace = child_ele_filtered.tag_name().namespace().unwrap_or("");
-                        if child_ele_filtered.has_attribute("dimension") {
-                            let dimension_raw = child_ele_filtered.attribute("dimension").unwrap();
-                            let dimension_split = dimension_raw.split(":").collect::<Vec<&str>>();
-                            let dimension_ns = dimension_split[0];
-                            let dimension_value = dimension_split[1];
-
-                            let value_split = value.split(":").collect::<Vec<&str>>();
-                            let key_ns = value_split[0];
-                            let key_value = value_split[1];
-
-                            dimensions
+                        let to_keep = ["instant", "startDate", "endDate"];
+                        let node_desc_filtered = child_ele
+                            .descendants()
+                            .filter(|e| to_keep.contains(&e.tag_name().name()));
+
+                        for child_ele_filtered in node_desc_filtered.into_iter() {
+                            let value = child_ele_filtered.text().unwrap_or("").trim();
+                            let name = child_ele_filtered.tag_name().name();
+
+                            contexts
+                                .periods
                                 .entry(id.to_string())
                                 .or_default()
-                                .push(Dimension {
-                                    axis_ns: dimension_ns.to_string(), // was key_ns in fast_xbrl_parser
-                                    axis_name: dimension_value.to_string(), // was key_value in fast_xbrl_parser
-                                    member_ns: key_ns.to_string(),
-                                    member_name: key_value.to_string(), // was member_value in fast_xbrl_parser
+                                .push(Period {
+                                    period_type: name.to_string(),
+                                    period_value: value.to_string(),
                                 });
 
-                            log::debug!(
-                                "Segment: {} {} {} {}",
-                                dimension_ns,
-                                dimension_value,
-                                key_ns,
-                                key_value
-                            );
+                            log::debug!("Period: {} {}", name, value);
                         }
                     }
-                }
-                _ => {}
-            }
-        }
-    }
-
-    // -- Process the fact elements --
+                    "entity" | "scenario" => {
+                        log::debug!("Found entity");
 
-    let mut facts: Vec<FactItem> = Vec::new();
-
-    let non_fact_ele = ["context", "unit", "xbrl", "schemaRef"];
-    let fact_ele = elem.clone().filter(|e| {
-        !&non_fact_ele.contains(&e.tag_name().name()) && e.tag_name().namespace().is_some()
-    });
-
-    // loop over fact_ele using enumerate
-    '_fact_loop: for child in fact_ele.into_iter() {
-        let id = child.attribute("id").unwrap_or(""); // Issue here
-        let name: String = child.tag_name().name().to_string();
-        let namespace: String = child.tag_name().namespace().unwrap_or("").to_string();
-        let prefix = child.lookup_prefix(namespace.as_str()).unwrap_or("");
-        let context_ref = &child.attribute("contextRef");
-        let unit_ref = &child.attribute("unitRef");
-        let decimals = child.attribute("decimals").unwrap_or("");
-        let value = child.text().unwrap_or("");
+                        let to_keep = ["explicitMember", "typedMember"];
+                        let node_desc_filtered = child_ele.descendants().filter(|e| {
+                            to_keep.contains(&e.tag_name().name()) && e.has_attribute("dimension")
+                        });
 
-        // Sanitize the value
-        let clean_value = sanitize_html(value.to_string().clone());
-
-        let mut fact_dimensions: Vec<Dimension> = Vec::new();
-        let mut fact_units: Vec<Unit> = Vec::new();
-        let mut fact_periods: Vec<Period> = Vec::new();
-
-        // Look up the units
-        if unit_ref.is_some() {
-            let unit_ref_value = unit_ref.unwrap().to_string();
-            // if unit_ref in units
-            if units.contains_key(&unit_ref_value) {
-                fact_units = units.get(&unit_ref_value).expect("Unit not found").clone();
+                        for child_ele_filtered in node_desc_filtered.into_iter() {
+                            match segment_dimension(child_ele_filtered) {
+                                Ok(dimension) => {
+                                    log::debug!(
+                                        "Segment: {} {} {} {}",
+                                        dimension.axis_ns,
+                                        dimension.axis_name,
+                                        dimension.member_ns,
+                                        dimension.member_name
+                                    );
+                                    contexts
+                                        .dimensions
+                                        .entry(id.to_string())
+                                        .or_default()
+                                        .push(dimension);
+                                }
+                                Err(e) => {
+                                    log::warn!("Skipping context {}: {}", id, e);
+                                    continue '_context_loop;
+                                }
+                            }
+                        }
+                    }
+                    _ => {}
+                }
             }
+            contexts.ids.insert(id.to_string());
         }
 
-        // Look up the dimensions
-        if context_ref.is_some() {
-            let context_ref_value = context_ref.unwrap().to_string();
-            if dimensions.contains_key(&context_ref_value) {
-                fact_dimensions = dimensions
-                    .get(&context_ref_value)
-                    .expect("Dimension not found")
-                    .clone();
-            }
+        contexts
+    }
+
+    /// Fact of the `prefix:name` concept reported by `node`, with the units, dimensions and
+    /// periods its `unitRef` and `contextRef` attributes point at
+    pub(crate) fn fact(
+        &self,
+        node: roxmltree::Node,
+        prefix: &str,
+        name: &str,
+        value: String,
+    ) -> Result<FactItem, XbrlError> {
+        let id = node.attribute("id").unwrap_or("");
+        let context_ref = node.attribute("contextRef");
+        let unit_ref = node.attribute("unitRef");
+        let decimals = node.attribute("decimals").unwrap_or("");
+
+        if let Some(context) = context_ref.filter(|context| !self.ids.contains(*context)) {
+            return Err(XbrlError::MissingContext {
+                fact: format!("{}:{}", prefix, name),
+                context: context.to_string(),
+            });
         }
 
-        // Look up the periods
-        if context_ref.is_some() {
-            let context_ref_value = context_ref.unwrap().to_string();
-            if periods.contains_key(&context_ref_value) {
-                fact_periods = periods
-                    .get(&context_ref_value)
-                    .expect("Period not found")
-                    .clone();
+        let units = unit_ref
+            .and_then(|unit_ref| self.units.get(unit_ref))
+            .cloned()
+            .unwrap_or_default();
+        let numeric = match unit_ref {
+            Some(_) => {
+                let nil = node.attribute((XSI_NAMESPACE, "nil")) == Some("true");
+                let value = if nil {
+                    None
+                } else {
+                    Some(value.trim().parse().map_err(|_| XbrlError::InvalidValue {
+                        fact: format!("{}:{}", prefix, name),
+                        value: value.trim().to_string(),
+                    })?)
+                };
+                Some(NumericValue {
+                    value,
+                    precision: Precision::parse(decimals),
+                    unit: NumericUnit::from_units(&units),
+                })
             }
-        }
+            None => None,
+        };
 
         log::debug!(
             "Fact: {} {} {} {} {} {}",
@@ -351,23 +494,73 @@ pub fn parse_xml_to_facts(raw_xml: String) -> Vec<FactItem> {
             unit_ref.unwrap_or("no unit")
         );
 
-        // Push to vector
-
-        facts.push(FactItem {
+        Ok(FactItem {
             id: id.to_string(),
             prefix: prefix.to_string(),
             name: name.to_string(),
-            value: clean_value,
+            value,
             decimals: decimals.to_string(),
             context_ref: context_ref.map(str::to_string),
             unit_ref: unit_ref.map(str::to_string),
-            units: fact_units,
-            dimensions: fact_dimensions,
-            periods: fact_periods,
-        });
+            units,
+            dimensions: context_ref
+                .and_then(|context_ref| self.dimensions.get(context_ref))
+                .cloned()
+                .unwrap_or_default(),
+            periods: context_ref
+                .and_then(|context_ref| self.periods.get(context_ref))
+                .cloned()
+                .unwrap_or_default(),
+            label: None,
+            terse_label: None,
+            numeric,
+        })
+    }
+}
+
+pub fn parse_xml_to_facts(raw_xml: String) -> Result<Vec<FactItem>, XbrlError> {
+    // -- Parse the XML --
+    let re = Regex::new(r"\s+").unwrap();
+    let raw_xml = re.replace_all(raw_xml.as_str(), " ").to_string();
+
+    let xml_tree = roxmltree::Document::parse(raw_xml.as_str())?;
+
+    // -- Get elements out of XML --
+
+    let elem = xml_tree
+        .root_element()
+        .children()
+        .filter(|e| e.node_type() == roxmltree::NodeType::Element);
+
+    // -- Process the context elements --
+
+    let contexts = Contexts::parse(elem.clone());
+
+    // -- Process the fact elements --
+
+    let mut facts: Vec<FactItem> = Vec::new();
+
+    let non_fact_ele = ["context", "unit", "xbrl", "schemaRef"];
+    let fact_ele = elem.clone().filter(|e| {
+        !&non_fact_ele.contains(&e.tag_name().name()) && e.tag_name().namespace().is_some()
+    });
+
+    '_fact_loop: for child in fact_ele.into_iter() {
+        let name = child.tag_name().name();
+        let namespace = child.tag_name().namespace().unwrap_or("");
+        let prefix = child.lookup_prefix(namespace).unwrap_or("");
+        let value = child.text().unwrap_or("");
+
+        // Sanitize the value
+        let clean_value = sanitize_html(value.to_string());
+
+        match contexts.fact(child, prefix, name, clean_value) {
+            Ok(fact) => facts.push(fact),
+            Err(e) => log::warn!("Skipping fact: {}", e),
+        }
     }
 
-    facts
+    Ok(facts)
 }
 
 #[derive(Clone, Debug, Deserialize, Serialize)]
@@ -375,6 +568,13 @@ pub struct XBRLFiling {
     pub raw_facts: Option<Vec<FactItem>>,
     pub fact_table: Option<Vec<FactTableRow>>,
     pub dimensions: Option<Vec<DimensionTableRow>>,
+    /// Financial statements of the presentation linkbase, see
+    /// [`super::linkbase::Presentation::statements`]
+    #[serde(default)]
+    pub statements: Option<Vec<Statement>>,
+    /// Summations of the calculation linkbase the facts are checked against
+    #[serde(default)]
+    pub calculation: Option<Calculation>,
 }
 
 impl XBRLFiling {
@@ -382,10 +582,34 @@ impl XBRLFiling {
         let mut md = String::new();
 
         if let Some(facts) = &self.raw_facts {
+            // Facts without dimensions of the concepts on a statement are shown in the statement
+            let mut presented: HashSet<&str> = HashSet::new();
+            let mut statements_md = String::new();
+            for statement in self.statements.iter().flatten() {
+                let table = self.generate_statement(statement, facts);
+                if !table.is_empty() {
+                    statements_md.push_str(&table);
+                    statements_md.push_str("\n\n");
+                    presented.extend(statement.lines.iter().map(|line| line.concept.as_str()));
+                }
+            }
+            if !statements_md.is_empty() {
+                md.push_str("# Financial Statements\n\n");
+                md.push_str(&statements_md);
+            }
+            let remaining: Vec<&FactItem> = facts
+                .iter()
+                .filter(|f| {
+                    !f.dimensions.is_empty()
+                        || !presented.contains(format!("{}:{}", f.prefix, f.name).as_str())
+                })
+                .collect();
+
             // Group facts by concept and dimensions signature
-            let tables = self.detect_tables(facts);
-            let standalone_facts: Vec<&FactItem> = facts
+            let tables = self.detect_tables(&remaining);
+            let standalone_facts: Vec<&FactItem> = remaining
                 .iter()
+                .copied()
                 .filter(|f| {
                     f.dimensions.is_empty()
                         && !tables.values().any(|table_facts| table_facts.contains(f))
@@ -409,15 +633,26 @@ impl XBRLFiling {
                     md.push('\n');
                 }
             }
+
+            if let Some(calculation) = &self.calculation {
+                let inconsistencies = calculation.check(facts);
+                if !inconsistencies.is_empty() {
+                    md.push_str("\n# Calculation Inconsistencies\n\n");
+                    for inconsistency in inconsistencies {
+                        md.push_str(&self.format_inconsistency(&inconsistency, facts));
+                        md.push('\n');
+                    }
+                }
+            }
         }
         md
     }
 
-    fn detect_tables<'a>(&self, facts: &'a [FactItem]) -> HashMap<String, Vec<&'a FactItem>> {
+    fn detect_tables<'a>(&self, facts: &[&'a FactItem]) -> HashMap<String, Vec<&'a FactItem>> {
         let mut tables: HashMap<String, Vec<&FactItem>> = HashMap::new();
 
         // Group facts by name and dimension structure
-        for fact in facts {
+        for &fact in facts {
             // Create signature combining concept name and dimension axes
             let mut dim_axes: Vec<String> = fact
                 .dimensions
@@ -450,11 +685,12 @@ impl XBRLFiling {
         let sample_fact = facts[0];
 
         // Table title
-        md.push_str(&format!("## {}\n\n", sample_fact.name));
+        md.push_str(&format!("## {}\n\n", sample_fact.display_name()));
 
         // Collect all unique dimensions and periods
         let mut all_periods = HashSet::new();
         let mut dim_members: HashMap<String, HashSet<String>> = HashMap::new();
+        let mut axis_labels: HashMap<String, String> = HashMap::new();
 
         for fact in facts {
             // Collect periods
@@ -464,8 +700,10 @@ impl XBRLFiling {
 
             // Collect dimension members
             for dim in &fact.dimensions {
+                let axis = format!("{}:{}", dim.axis_ns, dim.axis_name);
+                axis_labels.insert(axis.clone(), dim.axis_display());
                 dim_members
-                    .entry(format!("{}:{}", dim.axis_ns, dim.axis_name))
+                    .entry(axis)
                     .or_default()
                     .insert(format!("{}:{}", dim.member_ns, dim.member_name));
             }
@@ -474,7 +712,7 @@ impl XBRLFiling {
         // Generate table headers
         let mut headers = vec!["Period".to_string()];
         let dimensions: Vec<String> = dim_members.keys().cloned().collect();
-        headers.extend(dimensions.iter().cloned());
+        headers.extend(dimensions.iter().map(|axis| axis_labels[axis].clone()));
         headers.push("Value".to_string());
 
         // Header row
@@ -507,7 +745,7 @@ impl XBRLFiling {
                             .dimensions
                             .iter()
                             .find(|d| format!("{}:{}", d.axis_ns, d.axis_name) == *dim_axis)
-                            .map(|d| format!("{}:{}", d.member_ns, d.member_name))
+                            .map(|d| d.member_display())
                             .unwrap_or_else(|| "-".to_string());
                         md.push_str(&format!("| {} ", member));
                     }
@@ -521,43 +759,143 @@ impl XBRLFiling {
         md
     }
 
-    fn format_compact_fact(&self, fact: &FactItem) -> String {
-        let mut parts = vec![format!("{}:{}", fact.prefix, fact.name)];
+    /// Statement table with the line items as rows and the reported periods as columns,
+    /// latest first. Empty when none of the line items were reported without dimensions.
+    fn generate_statement(&self, statement: &Statement, facts: &[FactItem]) -> String {
+        let concepts: HashSet<&str> = statement
+            .lines
+            .iter()
+            .map(|line| line.concept.as_str())
+            .collect();
+        let mut periods: Vec<String> = Vec::new();
+        let mut values: HashMap<(String, String), &str> = HashMap::new();
+        for fact in facts.iter().filter(|f| f.dimensions.is_empty()) {
+            let concept = format!("{}:{}", fact.prefix, fact.name);
+            let Some(period) = fact.period_key() else {
+                continue;
+            };
+            if !concepts.contains(concept.as_str()) {
+                continue;
+            }
+            if !periods.contains(&period) {
+                periods.push(period.clone());
+            }
+            values
+                .entry((concept, period))
+                .or_insert(fact.value.as_str());
+        }
+        if periods.is_empty() {
+            return String::new();
+        }
 
-        // Add value
-        parts.push(fact.value.clone());
+        // Latest end date first, instants before the durations ending on the same day
+        let period_order = |period: &String| match period.split_once('/') {
+            Some((start, end)) => (end.to_string(), start.to_string()),
+            None => (period.clone(), period.clone()),
+        };
+        periods.sort_by_key(|period| std::cmp::Reverse(period_order(period)));
 
-        // Add period(s) with "/" separator for ranges
-        if !fact.periods.is_empty() {
-            let mut start_date = None;
-            let mut end_date = None;
+        let rows: Vec<Vec<String>> = statement
+            .lines
+            .iter()
+            .map(|line| {
+                periods
+                    .iter()
+                    .map(|period| {
+                        let value = values
+                            .get(&(line.concept.clone(), period.clone()))
+                            .copied()
+                            .unwrap_or("");
+                        // Line items with a negated label are shown with the opposite sign
+                        match value.strip_prefix('-') {
+                            _ if !line.negated
+                                || !value.parse::<f64>().is_ok_and(|number| number != 0.0) =>
+                            {
+                                value.to_string()
+                            }
+                            Some(magnitude) => magnitude.to_string(),
+                            None => format!("-{}", value),
+                        }
+                    })
+                    .collect()
+            })
+            .collect();
+        let has_values: Vec<bool> = rows
+            .iter()
+            .map(|row| row.iter().any(|value| !value.is_empty()))
+            .collect();
+
+        let mut md = format!("## {}\n\n| Line item |", statement.title);
+        for period in &periods {
+            md.push_str(&format!(" {} |", period));
+        }
+        md.push_str("\n| --- |");
+        for _ in &periods {
+            md.push_str(" --- |");
+        }
+        md.push('\n');
 
-            for period in &fact.periods {
-                match period.period_type.as_str() {
-                    "startDate" => start_date = Some(period.period_value.clone()),
-                    "endDate" => end_date = Some(period.period_value.clone()),
-                    "instant" => start_date = Some(period.period_value.clone()),
-                    _ => {}
-                }
+        for (i, line) in statement.lines.iter().enumerate() {
+            // Headings are kept when a line item under them has values
+            let keep = has_values[i]
+                || statement.lines[i + 1..]
+                    .iter()
+                    .zip(&has_values[i + 1..])
+                    .take_while(|(next, _)| next.depth > line.depth)
+                    .any(|(_, has_values)| *has_values);
+            if !keep {
+                continue;
+            }
+            md.push_str(&format!(
+                "| {}{} |",
+                "&nbsp;&nbsp;".repeat(line.depth),
+                line.label
+            ));
+            for value in &rows[i] {
+                md.push_str(&format!(" {} |", value));
             }
+            md.push('\n');
+        }
 
-            let period_str = match (start_date, end_date) {
-                (Some(start), Some(end)) if start == end => format!("({})", start),
-                (Some(start), Some(end)) => format!("({}/{})", start, end),
-                (Some(date), None) | (None, Some(date)) => format!("({})", date),
-                (None, None) => String::new(),
-            };
+        md
+    }
 
-            if !period_str.is_empty() {
-                parts.push(period_str);
+    fn format_inconsistency(&self, inconsistency: &Inconsistency, facts: &[FactItem]) -> String {
+        let total = facts.iter().find(|f| {
+            format!("{}:{}", f.prefix, f.name) == inconsistency.total
+                && f.context_ref.as_deref() == Some(inconsistency.context_ref.as_str())
+        });
+        let name = total.map_or_else(|| inconsistency.total.clone(), |f| f.display_name());
+        let period = total
+            .and_then(|f| f.period_key())
+            .unwrap_or_else(|| inconsistency.context_ref.clone());
+        format!(
+            "{} ({}): reported {}, line items add up to {}",
+            name, period, inconsistency.reported, inconsistency.computed
+        )
+    }
+
+    fn format_compact_fact(&self, fact: &FactItem) -> String {
+        let mut parts = vec![fact.display_name()];
+
+        // Add value, with the unit of numeric facts
+        match &fact.numeric {
+            Some(numeric) if !numeric.is_nil() && !numeric.unit.numerator.is_empty() => {
+                parts.push(format!("{} {}", fact.value, numeric.unit))
             }
+            _ => parts.push(fact.value.clone()),
+        }
+
+        // Add period(s) with "/" separator for ranges
+        if let Some(period) = fact.period_key() {
+            parts.push(format!("({})", period));
         }
 
         parts.join(" ")
     }
 }
 
-fn sanitize_html(input: String) -> String {
+pub(crate) fn sanitize_html(input: String) -> String {
     let mut output = input.clone();
 
     // Remove non ascii characters and replace them with a space
@@ -576,3 +914,68 @@ fn sanitize_html(input: String) -> String {
     let re = Regex::new(r"\s+").unwrap();
     re.replace_all(output.as_str(), " ").to_string()
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const INSTANCE: &str = r#"<?xml version="1.0" encoding="utf-8"?>
+<xbrli:xbrl xmlns:xbrli="http://www.xbrl.org/2003/instance" xmlns:xbrldi="http://xbrl.org/2006/xbrldi"
+    xmlns:us-gaap="http://fasb.org/us-gaap/2023" xmlns:aapl="http://www.apple.com/20230930" xmlns:iso4217="http://www.xbrl.org/2003/iso4217">
+  <xbrli:context id="FY2023">
+    <xbrli:entity><xbrli:identifier scheme="http://www.sec.gov/CIK">0000320193</xbrli:identifier></xbrli:entity>
+    <xbrli:period><xbrli:startDate>2022-09-25</xbrli:startDate><xbrli:endDate>2023-09-30</xbrli:endDate></xbrli:period>
+  </xbrli:context>
+  <xbrli:context id="FY2023_Americas">
+    <xbrli:entity>
+      <xbrli:identifier scheme="http://www.sec.gov/CIK">0000320193</xbrli:identifier>
+      <xbrli:segment><xbrldi:explicitMember dimension="us-gaap:StatementBusinessSegmentsAxis">aapl:AmericasSegmentMember</xbrldi:explicitMember></xbrli:segment>
+    </xbrli:entity>
+    <xbrli:period><xbrli:startDate>2022-09-25</xbrli:startDate><xbrli:endDate>2023-09-30</xbrli:endDate></xbrli:period>
+  </xbrli:context>
+  <xbrli:context id="FY2023_Tranche">
+    <xbrli:entity>
+      <xbrli:identifier scheme="http://www.sec.gov/CIK">0000320193</xbrli:identifier>
+      <xbrli:segment><xbrldi:typedMember dimension="us-gaap:DebtInstrumentAxis"><aapl:TrancheDomain>2030 Notes</aapl:TrancheDomain></xbrldi:typedMember></xbrli:segment>
+    </xbrli:entity>
+    <xbrli:period><xbrli:instant>2023-09-30</xbrli:instant></xbrli:period>
+  </xbrli:context>
+  <xbrli:context id="FY2023_Broken">
+    <xbrli:entity>
+      <xbrli:identifier scheme="http://www.sec.gov/CIK">0000320193</xbrli:identifier>
+      <xbrli:segment><xbrldi:explicitMember dimension="StatementBusinessSegmentsAxis">EuropeSegmentMember</xbrldi:explicitMember></xbrli:segment>
+    </xbrli:entity>
+    <xbrli:period><xbrli:startDate>2022-09-25</xbrli:startDate><xbrli:endDate>2023-09-30</xbrli:endDate></xbrli:period>
+  </xbrli:context>
+  <xbrli:unit id="usd"><xbrli:measure>iso4217:USD</xbrli:measure></xbrli:unit>
+  <us-gaap:Revenues contextRef="FY2023" unitRef="usd" decimals="-6">383285000000</us-gaap:Revenues>
+  <us-gaap:Revenues contextRef="FY2023_Americas" unitRef="usd" decimals="-6">162560000000</us-gaap:Revenues>
+  <us-gaap:Revenues contextRef="FY2023_Broken" unitRef="usd" decimals="-6">94294000000</us-gaap:Revenues>
+  <us-gaap:LongTermDebt contextRef="FY2023_Tranche" unitRef="usd" decimals="-6">1000000000</us-gaap:LongTermDebt>
+  <us-gaap:NetIncomeLoss contextRef="FY2022" unitRef="usd" decimals="-6">99803000000</us-gaap:NetIncomeLoss>
+</xbrli:xbrl>"#;
+
+    #[test]
+    fn test_parse_xml_to_facts_skips_bad_facts() {
+        let facts = parse_xml_to_facts(INSTANCE.to_string()).unwrap();
+        // The fact in the context with unprefixed QNames and the one in an undefined context are dropped
+        assert_eq!(facts.len(), 3);
+        assert_eq!(facts[0].units[0].unit_value, "iso4217:USD");
+        let revenue = facts[0].numeric.as_ref().unwrap();
+        assert_eq!(revenue.value, Some("383285000000".parse().unwrap()));
+        assert_eq!(revenue.precision, Some(Precision::Decimals(-6)));
+        assert_eq!(revenue.unit.currency(), Some("USD"));
+        assert_eq!(facts[1].dimensions[0].member_name, "AmericasSegmentMember");
+        assert_eq!(facts[2].dimensions[0].axis_name, "DebtInstrumentAxis");
+        assert_eq!(facts[2].dimensions[0].member_name, "2030 Notes");
+
+        assert!(matches!(
+            split_qname("EuropeSegmentMember"),
+            Err(XbrlError::InvalidQName(_))
+        ));
+        assert!(matches!(
+            parse_xml_to_facts("<xbrl><unclosed></xbrl>".to_string()),
+            Err(XbrlError::Xml(_))
+        ));
+    }
+}
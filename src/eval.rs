@@ -1,5 +1,5 @@
 use crate::document::DocType;
-use crate::edgar::{self, filing};
+use crate::edgar::{self, client::EdgarClient, filing};
 use crate::memory::{Conversation, ConversationManager, DatabaseMemory, MessageRole};
 use crate::query::Query;
 use crate::{earnings, ProgressTracker, TokenUsage};
@@ -35,8 +35,8 @@ use uuid::Uuid;
 /// 2. Initialize a vector to store processing futures
 /// 3. If filings are requested in query parameters:
 ///    - Convert query to EDGAR format
-///    - For each ticker:
-///      - Fetch matching filings
+///    - Fetch matching filings for every ticker
+///    - For each company (CIK):
 ///      - Process EDGAR filings with progress tracking
 /// 4. If earnings data is requested:
 ///    - Convert query to earnings format
@@ -46,8 +46,9 @@ use uuid::Uuid;
 /// 6. Clear progress bars if present
 async fn process_documents(
     query: &Query,
-    http_client: &reqwest::Client,
+    edgar_client: &EdgarClient,
     store: Arc<Store>,
+    pg_pool: &Pool<Postgres>,
     progress: Option<&Arc<MultiProgress>>,
 ) -> Result<()> {
     let progress_tracker = Arc::new(ProgressTracker::new(
@@ -64,24 +65,27 @@ async fn process_documents(
         let edgar_future = async {
             match query.to_edgar_query() {
                 Ok(edgar_query) => {
-                    for ticker in &edgar_query.tickers {
-                        log::debug!(
-                            "Fetching EDGAR filings ({}) for ticker: {} in date range {} to {}",
-                            edgar_query
-                                .report_types
-                                .iter()
-                                .map(|rt| rt.to_string())
-                                .join(", "),
-                            ticker,
-                            edgar_query.start_date,
-                            edgar_query.end_date
-                        );
-                        let filings =
-                            filing::fetch_matching_filings(http_client, &edgar_query, progress)
-                                .await?;
+                    log::debug!(
+                        "Fetching EDGAR filings ({}) for tickers: {} in date range {} to {}",
+                        edgar_query
+                            .report_types
+                            .iter()
+                            .map(|rt| rt.to_string())
+                            .join(", "),
+                        edgar_query.tickers.join(", "),
+                        edgar_query.start_date,
+                        edgar_query.end_date
+                    );
+                    let filings_by_cik =
+                        filing::fetch_matching_filings(edgar_client, &edgar_query, progress)
+                            .await?;
+                    for (cik, filings) in filings_by_cik {
+                        log::debug!("Processing {} filings for CIK {}", filings.len(), cik);
                         process_edgar_filings(
+                            edgar_client,
                             filings,
                             Arc::clone(&store),
+                            pg_pool.clone(),
                             Some(Arc::clone(&progress_tracker)),
                         )
                         .await?;
@@ -110,8 +114,10 @@ async fn process_documents(
         let progress_tracker = progress_tracker.clone();
 
         let earnings_future = async move {
+            let calendar = edgar::fiscal::fiscal_calendar_for_ticker(edgar_client, &ticker).await;
             let transcripts =
-                earnings::fetch_transcripts(http_client, &ticker, start_date, end_date).await?;
+                earnings::fetch_transcripts(edgar_client, &ticker, &calendar, start_date, end_date)
+                    .await?;
             process_earnings_transcripts(transcripts, store, Some(progress_tracker)).await?;
             Ok::<_, anyhow::Error>(())
         };
@@ -129,23 +135,73 @@ async fn process_documents(
     Ok(())
 }
 
+/// Drop the XBRL facts of filings whose amendment was retrieved as well, so restated numbers
+/// replace the original ones. Narrative text of the original is kept, amendments often only
+/// restate part of it.
+fn prefer_amended_facts(docs: Vec<Document>) -> Vec<Document> {
+    let is_facts = |doc: &Document| {
+        doc.metadata.get("doc_type").and_then(|v| v.as_str()) == Some("edgar_filing")
+    };
+    let amended = docs
+        .iter()
+        .filter(|doc| is_facts(doc))
+        .filter_map(|doc| doc.metadata.get("amends").and_then(|v| v.as_str()))
+        .map(str::to_string)
+        .collect::<std::collections::HashSet<_>>();
+    if amended.is_empty() {
+        return docs;
+    }
+
+    docs.into_iter()
+        .filter(|doc| {
+            !is_facts(doc)
+                || !doc
+                    .metadata
+                    .get("accession_number")
+                    .and_then(|v| v.as_str())
+                    .is_some_and(|accession| amended.contains(accession))
+        })
+        .collect()
+}
+
 fn count_tokens(doc: &Document) -> usize {
     doc.page_content.to_string().split_whitespace().count() * 4
 }
 
+/// CIKs of the conversation companies, whether referenced by ticker or by CIK
+async fn conversation_ciks(edgar_client: &EdgarClient, tickers: &[String]) -> Vec<String> {
+    let mut ciks = Vec::new();
+    for ticker in tickers {
+        match edgar::tickers::resolve_cik(edgar_client, ticker).await {
+            Ok(cik) => ciks.push(cik),
+            Err(e) => log::warn!("Leaving {} out of the document search: {}", ticker, e),
+        }
+    }
+    ciks
+}
+
+/// How a filing is labelled in the context: its ticker, or the filer name and then the CIK for
+/// entities without one
+fn entity_label(metadata: &HashMap<String, serde_json::Value>) -> &str {
+    ["symbol", "entity_name", "cik"]
+        .iter()
+        .find_map(|key| metadata.get(*key).and_then(|v| v.as_str()))
+        .unwrap_or("Unknown")
+}
+
 /// Build context for LLM from relevant documents
 ///
 /// Logical steps:
 /// 1. Initialize empty vectors for required and all documents
 /// 2. If filings requested:
-///    - Create filter for doc_type and symbol
+///    - Create filter for doc_type and the CIKs of the conversation companies
 ///    - Perform similarity search for each ticker
 ///    - Add matching docs to collection
 /// 3. If earnings requested:
 ///    - Extract year from start date
 ///    - Perform similarity search with earnings filters
 ///    - Add matching docs to required docs
-/// 4. Filter docs to match conversation tickers
+/// 4. Filter docs to match conversation companies
 /// 5. Filter chunks based on conversation tracking
 /// 6. Calculate total tokens from documents
 /// 7. If over token limit:
@@ -157,41 +213,93 @@ async fn build_document_context(
     query: &Query,
     input: &str,
     store: Arc<Store>,
+    edgar_client: &EdgarClient,
+    pg_pool: &Pool<Postgres>,
     conversation: &Conversation,
     conversation_manager: Arc<RwLock<ConversationManager>>,
 ) -> Result<String> {
     // 1. Get all documents specified by the query
     let mut required_docs = Vec::new();
-    let all_docs = Vec::new();
+    let mut all_docs = Vec::new();
+
+    // Filings are matched by CIK, which entities referenced as `@CIK...` have even without a ticker
+    let ciks = conversation_ciks(edgar_client, &conversation.tickers).await;
 
     if let Some(filings) = query.parameters.get("filings") {
-        if let Some(_types) = filings.get("report_types").and_then(|t| t.as_array()) {
-            let mut all_docs = Vec::new();
-            let filter = And(vec![
-                Eq(
-                    JsonField(vec!["doc_type".to_string()]),
-                    LitStr("edgar_filing".to_string()),
-                ),
-                In(
-                    JsonField(vec!["symbol".to_string()]),
-                    conversation.tickers.clone(),
-                ),
-            ]);
-
-            log::info!("Using filter for similarity search: {:?}", filter);
-            let docs = store
-                .similarity_search(
-                    input,
-                    20,
-                    &langchain_rust::vectorstore::VecStoreOptions {
-                        filters: Some(filter),
-                        ..Default::default()
-                    },
-                )
-                .await
-                .map_err(|e| anyhow!("Failed to retrieve documents from vector store: {}", e))?;
+        if let Some(types) = filings.get("report_types").and_then(|t| t.as_array()) {
+            let symbol_filter = || In(JsonField(vec!["cik".to_string()]), ciks.clone());
+            let report_types = types
+                .iter()
+                .filter_map(|t| t.as_str()?.parse::<edgar::report::ReportType>().ok())
+                .collect::<Vec<_>>();
+            let items = edgar::sections::items_for_question(input, &report_types);
+
+            // Questions about specific items ("risk factors", "Item 7") only search the text
+            // of those sections, alongside the XBRL financial data. Documents that are not
+            // split into sections (8-Ks, exhibits) are searched without the item filter; the
+            // flag marks the searches whose sectioned documents are dropped.
+            let filters = if items.is_empty() {
+                vec![(
+                    And(vec![
+                        In(
+                            JsonField(vec!["doc_type".to_string()]),
+                            vec!["edgar_filing".to_string(), "edgar_filing_text".to_string()],
+                        ),
+                        symbol_filter(),
+                    ]),
+                    false,
+                )]
+            } else {
+                let text_filter = || {
+                    Eq(
+                        JsonField(vec!["doc_type".to_string()]),
+                        LitStr("edgar_filing_text".to_string()),
+                    )
+                };
+                vec![
+                    (
+                        And(vec![
+                            Eq(
+                                JsonField(vec!["doc_type".to_string()]),
+                                LitStr("edgar_filing".to_string()),
+                            ),
+                            symbol_filter(),
+                        ]),
+                        false,
+                    ),
+                    (
+                        And(vec![
+                            text_filter(),
+                            In(JsonField(vec!["item".to_string()]), items),
+                            symbol_filter(),
+                        ]),
+                        false,
+                    ),
+                    (And(vec![text_filter(), symbol_filter()]), true),
+                ]
+            };
 
-            all_docs.extend(docs);
+            for (filter, unsectioned_only) in filters {
+                log::info!("Using filter for similarity search: {:?}", filter);
+                let docs = store
+                    .similarity_search(
+                        input,
+                        20,
+                        &langchain_rust::vectorstore::VecStoreOptions {
+                            filters: Some(filter),
+                            ..Default::default()
+                        },
+                    )
+                    .await
+                    .map_err(|e| {
+                        anyhow!("Failed to retrieve documents from vector store: {}", e)
+                    })?;
+
+                all_docs.extend(
+                    docs.into_iter()
+                        .filter(|doc| !unsectioned_only || !doc.metadata.contains_key("item")),
+                );
+            }
         }
     }
 
@@ -229,12 +337,12 @@ async fn build_document_context(
         required_docs.extend(docs);
     }
 
-    // Filter docs to only include those matching conversation tickers
-    let filtered_docs: Vec<_> = all_docs
+    // Filter docs to only include those matching conversation companies
+    let filtered_docs: Vec<_> = prefer_amended_facts(all_docs)
         .into_iter()
         .filter(|doc: &langchain_rust::schemas::Document| {
-            if let Some(symbol) = doc.metadata.get("symbol").and_then(|v| v.as_str()) {
-                conversation.tickers.contains(&symbol.to_string())
+            if let Some(cik) = doc.metadata.get("cik").and_then(|v| v.as_str()) {
+                ciks.contains(&cik.to_string())
             } else {
                 false
             }
@@ -254,7 +362,17 @@ async fn build_document_context(
         "Query-based search returned {} documents",
         required_docs.len()
     );
-    if required_docs.is_empty() {
+
+    // Summaries computed from parsed filings rather than retrieved chunks
+    let structured_context = [
+        insider_activity_context(query, edgar_client).await,
+        institutional_activity_context(query, edgar_client, pg_pool).await,
+    ]
+    .into_iter()
+    .filter(|section| !section.is_empty())
+    .join("\n\n");
+
+    if required_docs.is_empty() && structured_context.is_empty() {
         return Err(anyhow!("No relevant documents found in vector store"));
     }
 
@@ -303,7 +421,7 @@ async fn build_document_context(
     for doc in &final_docs {
         if let Some(doc_type) = doc.metadata.get("type").and_then(|v| v.as_str()) {
             match doc_type {
-                "edgar_filing" => {
+                "edgar_filing" | "edgar_filing_text" => {
                     if let Some(filing_type) =
                         doc.metadata.get("filing_type").and_then(|v| v.as_str())
                     {
@@ -368,14 +486,21 @@ async fn build_document_context(
 
             // Format document header based on type
             let doc_header = match doc_type.and_then(DocType::from_str) {
-                Ok(DocType::EdgarFiling) => {
+                Ok(doc_type @ (DocType::EdgarFiling | DocType::EdgarFilingText)) => {
                     format!(
-                        "[{} {} Filing - {} - Score: {:.3}]",
-                        symbol.unwrap(),
+                        "[{} {} Filing{} - {} - Score: {:.3}]",
+                        entity_label(&doc.metadata),
                         doc.metadata
                             .get("filing_type")
                             .and_then(|v| v.as_str())
                             .unwrap_or("Unknown"),
+                        match doc.metadata.get("exhibit_type").and_then(|v| v.as_str()) {
+                            Some(exhibit_type) => format!(" {}", exhibit_type),
+                            None if matches!(doc_type, DocType::EdgarFilingText) => {
+                                " Text".to_string()
+                            }
+                            None => String::new(),
+                        },
                         doc.metadata
                             .get("filing_date")
                             .and_then(|v| v.as_str())
@@ -408,6 +533,20 @@ async fn build_document_context(
         .collect::<Vec<_>>()
         .join("\n\n");
 
+    let context = if structured_context.is_empty() {
+        context
+    } else {
+        format!("{}\n\n{}", structured_context, context)
+    };
+
+    // Company profiles go first, so answers know each company's fiscal calendar and industry
+    let profiles = company_profile_context(query, edgar_client, pg_pool).await;
+    let context = if profiles.is_empty() {
+        context
+    } else {
+        format!("{}\n\n{}", profiles, context)
+    };
+
     log::info!(
         "=== Complete LLM Context ===\n{}\n=== End Context ===",
         summary
@@ -416,6 +555,90 @@ async fn build_document_context(
     Ok(context)
 }
 
+/// Profiles of the companies in the query: industry, fiscal year end, filer category
+async fn company_profile_context(
+    query: &Query,
+    edgar_client: &EdgarClient,
+    pg_pool: &Pool<Postgres>,
+) -> String {
+    let mut profiles = Vec::new();
+    for ticker in query.tickers.iter().unique() {
+        match edgar::profile::company_profile_for_ticker(edgar_client, pg_pool, ticker).await {
+            Ok(profile) => profiles.push(profile.to_markdown()),
+            Err(e) => log::warn!("Failed to load company profile for {}: {}", ticker, e),
+        }
+    }
+    profiles.join("\n")
+}
+
+/// Per-ticker insider activity summaries, when the query asked for ownership reports
+async fn insider_activity_context(query: &Query, edgar_client: &EdgarClient) -> String {
+    let Ok(edgar_query) = query.to_edgar_query() else {
+        return String::new();
+    };
+    let has_ownership_reports = edgar_query
+        .report_types
+        .iter()
+        .any(|report_type| report_type.parser() == edgar::report::FormParser::Ownership);
+    if !has_ownership_reports {
+        return String::new();
+    }
+
+    let mut summaries = Vec::new();
+    for ticker in &edgar_query.tickers {
+        match edgar::ownership::insider_activity_for_ticker(
+            edgar_client,
+            ticker,
+            edgar_query.start_date,
+            edgar_query.end_date,
+        )
+        .await
+        {
+            Ok(summary) => summaries.push(summary),
+            Err(e) => log::warn!("Failed to summarize insider activity for {}: {}", ticker, e),
+        }
+    }
+    summaries.join("\n\n")
+}
+
+/// Top institutional holders and their quarter over quarter changes for each ticker, when the
+/// query asked for 13F reports
+async fn institutional_activity_context(
+    query: &Query,
+    edgar_client: &EdgarClient,
+    pg_pool: &Pool<Postgres>,
+) -> String {
+    let Ok(edgar_query) = query.to_edgar_query() else {
+        return String::new();
+    };
+    if !edgar_query
+        .report_types
+        .contains(&edgar::report::ReportType::Form13F)
+    {
+        return String::new();
+    }
+
+    let mut summaries = Vec::new();
+    for ticker in &edgar_query.tickers {
+        match edgar::holdings::institutional_activity_for_ticker(
+            edgar_client,
+            pg_pool,
+            ticker,
+            None,
+        )
+        .await
+        {
+            Ok(summary) => summaries.push(summary),
+            Err(e) => log::warn!(
+                "Failed to summarize institutional holdings for {}: {}",
+                ticker,
+                e
+            ),
+        }
+    }
+    summaries.join("\n\n")
+}
+
 /// Filter document chunks and track them in conversation
 ///
 /// Logical steps:
@@ -508,10 +731,18 @@ fn build_metadata_summary(
             doc.metadata.get("symbol").and_then(|v| v.as_str()),
             doc.metadata.get("filing_date").and_then(|v| v.as_str()),
         ) {
-            (Some("edgar_filing"), Some(filing_type), _, _, total, Some(symbol), Some(date)) => {
+            (
+                Some("edgar_filing" | "edgar_filing_text"),
+                Some(filing_type),
+                _,
+                _,
+                total,
+                _,
+                Some(date),
+            ) => {
                 format!(
                     "{} {} Filing {} ({} chunks)",
-                    symbol,
+                    entity_label(&doc.metadata),
                     filing_type,
                     date,
                     total.unwrap_or(1)
@@ -705,8 +936,10 @@ async fn generate_response(
 }
 
 async fn process_edgar_filings(
+    edgar_client: &EdgarClient,
     filings: HashMap<String, filing::Filing>,
     store: Arc<Store>,
+    pg_pool: Pool<Postgres>,
     progress_tracker: Option<Arc<ProgressTracker>>,
 ) -> Result<()> {
     let mut success_count = 0;
@@ -717,7 +950,9 @@ async fn process_edgar_filings(
     // Launch tasks concurrently
     for (filepath, filing) in filings {
         let tx = tx.clone();
+        let edgar_client = edgar_client.clone();
         let store = store.clone();
+        let pg_pool = pg_pool.clone();
         let mut progress_tracker = progress_tracker.clone();
         if let Some(tracker) = progress_tracker.as_ref() {
             let task_tracker = Arc::new(ProgressTracker::new(
@@ -752,10 +987,12 @@ async fn process_edgar_filings(
             ));
             task_tracker.start_progress(100, "Processing filing");
 
-            match filing::extract_complete_submission_filing(
+            match filing::extract_filing_document(
+                &edgar_client,
                 &filepath,
-                filing.report_type,
+                &filing,
                 store,
+                &pg_pool,
                 Some(task_tracker),
             )
             .await
@@ -789,9 +1026,9 @@ async fn process_edgar_filings(
         }
     }
 
-    // Wait for all tasks
+    // Wait for all tasks. Failed filings were counted above and don't abort the others
     for handle in handles {
-        handle.await??;
+        let _ = handle.await?;
     }
 
     log::info!(
@@ -833,19 +1070,28 @@ async fn extract_query_params(llm: &OpenAI<OpenAIConfig>, input: &str) -> Result
         r#"Extract query parameters from the input text to build a comprehensive financial analysis query.
     
     Format the parameters as a JSON object with these fields:
-    - 'tickers': array of company ticker symbols
+    - 'tickers': array of company ticker symbols; entities given by CIK (e.g. @CIK0000320193) are kept as CIK0000320193
     - 'is_adr': boolean indicating if the security is an ADR (American Depositary Receipt)
     - 'parameters': object containing query parameters:
         - 'filings': optional object for SEC filings:
             - 'start_date': ISO date (YYYY-MM-DD)
             - 'end_date': ISO date (YYYY-MM-DD) 
             - 'report_types': array of SEC filing types:
-                - Required reports (10-K, 10-Q for US stocks, 20-F, 6-K, 8-K for ADRs)
+                - Required reports (10-K, 10-Q for US stocks, 20-F, 40-F, 6-K, 8-K for ADRs)
                 - Management discussion (8-K items 2.02, 7.01, 8.01)
                 - Strategic changes (8-K items 1.01, 1.02, 2.01)
                 - Guidance & projections (8-K item 7.01)
                 - Proxy statements (DEF 14A)
-                Possible values are: {} etc, use appropriate EDGAR report types even if not mentioned here.
+                - Institutional holders (13F, for questions about which funds hold, bought or sold a stock)
+                Possible values by family are:
+{}
+                Use appropriate EDGAR report types even if not mentioned here.
+            - 'items': optional array of 8-K item codes to narrow down 8-K filings, e.g. ["2.02", "7.01"]. Omit it to fetch all 8-Ks.
+                Possible values are: {}
+            - 'include_amendments': optional boolean, true to also fetch amendments (10-K/A, 10-Q/A, 8-K/A) of the requested report types, e.g. for questions about restatements or corrected filings
+        - 'fiscal_period': optional, the fiscal period the question is about, in the company's own fiscal calendar:
+            {{"year": 2024, "quarter": 3}} for Q3 FY2024, {{"year": 2024}} for fiscal year 2024, "latest_quarter" or "latest_year" for the latest reports.
+            Dates of 'filings' and 'earnings' are computed from it, still provide them as a calendar approximation.
 
     Examples:
     {{"tickers": ["AAPL"], "is_adr": false, "parameters": {{"filings": {{"start_date": "2024-01-01", "end_date": "2024-03-31", "report_types": ["10-K", "10-Q", "8-K"]}}, "earnings": {{"start_date": "2024-01-01", "end_date": "2024-03-31"}} }} }}
@@ -858,16 +1104,16 @@ async fn extract_query_params(llm: &OpenAI<OpenAIConfig>, input: &str) -> Result
     - Include both when the question spans multiple areas
     
     Use these defaults if values are missing:
-    - Latest report: date range from 'today - 90 days' to 'today'
-    - Latest quarterly report: include 10-Q, 8-F for US stocks. 20-F, 40-F, 6-K filings for ADRs. If no sure ask for both.
-    - Yearly reports include: 10-K for US stocks, and 20-K for ADRs. If not sure ask for both.
+    - Latest report: 'fiscal_period': "latest_quarter" (or "latest_year" for annual reports), with a date range from 'today - 90 days' to 'today'
+    - Latest quarterly report: include 10-Q, 8-K for US stocks. 20-F, 40-F, 6-K filings for ADRs. If no sure ask for both.
+    - Yearly reports include: 10-K for US stocks, and 20-F or 40-F for ADRs. If not sure ask for both.
     - Earnings analysis: automatically include earnings call transcripts and quarterly reports (10-Q for US stocks, 6-K for ADRs) or yearly reports(10-K for US stocks, 20-F, 40-F for ADRs) depending on the contex timeline.
     
     Current date is: {}.
     Return only a json document, as it's meant to be parsed by the software. No markdown formatting is allowed. No JSON formatting is allowed including pretty-printing and newlines.
     
     Parse this user input:
-    {input}"#, *edgar::report::REPORT_TYPES, now.format("%Y-%m-%d")
+    {input}"#, *edgar::report::REPORT_TYPES_BY_FAMILY, *edgar::items::FORM_8K_ITEMS, now.format("%Y-%m-%d")
     )
     .to_string();
 
@@ -881,12 +1127,18 @@ async fn extract_query_params(llm: &OpenAI<OpenAIConfig>, input: &str) -> Result
         Ok(result) => {
             let result = result.to_string();
             log::debug!("Result: {:?}", result);
-            let query: Query = match serde_json::from_str(&result) {
+            let mut query: Query = match serde_json::from_str(&result) {
                 Ok(query) => query,
                 Err(e) => {
                     return Err(anyhow!("LLM returned a malformed query, halting: {}", e));
                 }
             };
+            // One spelling per share class, so `BRK.B` and `BRK-B` name the same conversation ticker
+            query.tickers = query
+                .tickers
+                .iter()
+                .map(|ticker| edgar::tickers::normalize_ticker(ticker))
+                .collect::<Result<_>>()?;
             log::debug!("Parsed generated query: {:?}", query);
             Ok(query)
         }
@@ -997,7 +1249,7 @@ async fn process_earnings_transcripts(
 pub async fn eval(
     input: &str,
     conversation: &Conversation,
-    http_client: &reqwest::Client,
+    edgar_client: &EdgarClient,
     llm: &OpenAI<OpenAIConfig>,
     store: Arc<Store>,
     conversation_manager: Arc<RwLock<ConversationManager>>,
@@ -1021,7 +1273,16 @@ pub async fn eval(
         .await?;
 
     // Generate response
-    let (query, summary) = generate_query(llm, input, conversation).await?;
+    let (mut query, summary) = generate_query(llm, input, conversation).await?;
+
+    // Fiscal periods ("Q3 FY2024", "latest quarter") follow the first company's fiscal calendar
+    if query.fiscal_period().is_some() {
+        if let Some(ticker) = query.tickers.first() {
+            let calendar = edgar::fiscal::fiscal_calendar_for_ticker(edgar_client, ticker).await;
+            query.resolve_fiscal_period(&calendar, chrono::Local::now().date_naive());
+            log::info!("Resolved fiscal period: {:?}", query.parameters);
+        }
+    }
 
     // Update conversation tickers if new ones are found
     let existing_tickers: HashSet<_> = conversation.tickers.iter().cloned().collect();
@@ -1049,8 +1310,9 @@ pub async fn eval(
     let store = Arc::new(store);
     process_documents(
         &query,
-        http_client,
+        edgar_client,
         Arc::clone(&store),
+        &pg_pool,
         multi_progress.as_ref(),
     )
     .await?;
@@ -1063,6 +1325,8 @@ pub async fn eval(
         &query,
         input,
         Arc::clone(&store),
+        edgar_client,
+        &pg_pool,
         conversation,
         Arc::clone(&conversation_manager),
     )
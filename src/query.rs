@@ -1,9 +1,14 @@
 use crate::earnings;
-use crate::edgar::{query as edgar_query, report};
+use crate::edgar::fiscal::{FiscalCalendar, FiscalPeriod};
+use crate::edgar::{items::Form8KItem, query as edgar_query, report};
 use anyhow::{anyhow, Result};
+use chrono::{Days, NaiveDate};
 use serde::{self, Deserialize, Serialize};
 use serde_json::Value;
 
+/// Filings about a period are filed after it ends, annual reports up to 90 days later
+const FILING_DEADLINE_DAYS: u64 = 90;
+
 /// A high-level query type that can handle multiple data sources
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Query {
@@ -18,19 +23,19 @@ pub struct Query {
 impl Query {
     pub fn estimated_tasks(&self) -> usize {
         let mut count = 0;
-        
+
         // Estimate filing tasks if present
         if let Some(filings) = self.parameters.get("filings") {
             if let Some(types) = filings.get("report_types").and_then(|t| t.as_array()) {
                 count += types.len() * self.tickers.len();
             }
         }
-        
+
         // Estimate earnings tasks if present
         if self.parameters.get("earnings").is_some() {
             count += self.tickers.len();
         }
-        
+
         count
     }
     pub fn new(tickers: Vec<String>) -> Self {
@@ -71,6 +76,36 @@ impl Query {
         Ok(())
     }
 
+    /// Fiscal period named by the question, `parameters.fiscal_period`
+    pub fn fiscal_period(&self) -> Option<FiscalPeriod> {
+        let period = self.parameters.get("fiscal_period")?;
+        serde_json::from_value(period.clone())
+            .map_err(|e| log::warn!("Ignoring invalid fiscal period {}: {}", period, e))
+            .ok()
+    }
+
+    /// Replace the date ranges of the filings and earnings parameters with the dates of the
+    /// fiscal period, if the question named one. Reports about the period are filed from the end
+    /// of its first quarter until the filing deadline after it ends; transcripts are fetched for
+    /// the quarters ending in the period.
+    pub fn resolve_fiscal_period(&mut self, calendar: &FiscalCalendar, today: NaiveDate) {
+        let Some(period) = self.fiscal_period() else {
+            return;
+        };
+        let (start, end) = calendar.period_dates(&period, today);
+        let first_report = calendar.quarter_end(calendar.quarter_of(start));
+        let filed_by = end + Days::new(FILING_DEADLINE_DAYS);
+
+        let mut set_dates = |source: &str, start: NaiveDate, end: NaiveDate| {
+            if let Some(Value::Object(params)) = self.parameters.get_mut(source) {
+                params.insert("start_date".to_string(), Value::String(start.to_string()));
+                params.insert("end_date".to_string(), Value::String(end.to_string()));
+            }
+        };
+        set_dates("filings", first_report, filed_by);
+        set_dates("earnings", start, end);
+    }
+
     pub fn has_edgar_query(&self) -> bool {
         self.parameters.get("filings").is_some()
     }
@@ -104,13 +139,32 @@ impl Query {
                 .map(|r| r.map_err(|e| anyhow!(e)))
                 .collect();
 
-            Ok(edgar_query::Query::new(
-                self.tickers.clone(),
-                start,
-                end,
-                types?,
-                self.is_adr,
-            )?)
+            let items: Result<Vec<Form8KItem>> = filings
+                .get("items")
+                .and_then(|v| v.as_array())
+                .map(|items| {
+                    items
+                        .iter()
+                        .filter_map(|v| v.as_str())
+                        .map(|s| s.parse::<Form8KItem>().map_err(|e| anyhow!(e)))
+                        .collect()
+                })
+                .unwrap_or_else(|| Ok(Vec::new()));
+
+            edgar_query::Query::builder()
+                .tickers(self.tickers.clone())
+                .start_date(start)
+                .end_date(end)
+                .report_types(types?)
+                .items(items?)
+                .include_amendments(
+                    filings
+                        .get("include_amendments")
+                        .and_then(|v| v.as_bool())
+                        .unwrap_or(false),
+                )
+                .is_adr(self.is_adr)
+                .build()
         } else {
             Err(anyhow!("No filings parameters found"))
         }
@@ -130,8 +184,15 @@ impl Query {
             let start = chrono::NaiveDate::parse_from_str(start_date, "%Y-%m-%d")?;
             let end = chrono::NaiveDate::parse_from_str(end_date, "%Y-%m-%d")?;
 
+            // Use first ticker for earnings, entities referenced by CIK have no earnings calls
+            let ticker = self
+                .tickers
+                .iter()
+                .find(|ticker| crate::edgar::tickers::cik_reference(ticker).is_none())
+                .ok_or_else(|| anyhow!("No ticker with earnings calls in the query"))?;
+
             Ok(earnings::Query {
-                ticker: self.tickers[0].clone(), // Use first ticker for earnings
+                ticker: ticker.clone(),
                 start_date: start,
                 end_date: end,
             })
@@ -1,5 +1,12 @@
 use crate::memory::ConversationManager;
-use crate::{edgar::tickers::fetch_tickers, memory::ConversationChainManager};
+use crate::{
+    edgar::{
+        client::EdgarClient,
+        ticker_index::{fetch_ticker_index, TickerIndex},
+        tickers::cik_reference,
+    },
+    memory::ConversationChainManager,
+};
 use anyhow::Result as AnyhowResult;
 use crossterm::{
     event, execute,
@@ -18,7 +25,6 @@ use rustyline::{
     Editor, Event, EventContext, EventHandler, Helper, KeyEvent, RepeatCount, Result,
 };
 use std::borrow::Cow;
-use std::collections::HashMap;
 use std::env;
 use std::io::{stdout, Write};
 use std::sync::Arc;
@@ -27,7 +33,8 @@ use uuid::Uuid;
 
 static HELP_LABEL: &str = "Select a conversation (↑/↓ to navigate, Enter to select, DEL/Ctrl+D to delete, Esc/Ctrl+[ to cancel):\n\n";
 
-type TickerMap = Arc<HashMap<String, (crate::edgar::tickers::Ticker, String, String)>>;
+/// Completion candidates offered for a partial `@` reference
+const MAX_COMPLETIONS: usize = 50;
 
 static HISTORY_PATH: Lazy<String> = Lazy::new(|| {
     let home_dir = env::var("HOME").expect("HOME environment variable not set");
@@ -36,23 +43,19 @@ static HISTORY_PATH: Lazy<String> = Lazy::new(|| {
 
 #[derive(Clone)]
 pub struct ReplHelper {
-    ticker_map: TickerMap,
+    ticker_index: Arc<TickerIndex>,
 }
 
 impl ReplHelper {
-    pub async fn new() -> AnyhowResult<Self> {
-        let tickers = fetch_tickers().await?;
-
-        let mut map = HashMap::new();
-        for (ticker, company, cik) in tickers {
-            map.insert(
-                ticker.to_string(),
-                (ticker.clone(), company.clone(), cik.clone()),
-            );
-        }
-        let ticker_map = Arc::new(map);
-        // print_all_tickers(&ticker_map).await;
-        Ok(ReplHelper { ticker_map })
+    pub async fn new(edgar_client: &EdgarClient) -> AnyhowResult<Self> {
+        let ticker_index = fetch_ticker_index(edgar_client).await?;
+        Ok(ReplHelper { ticker_index })
+    }
+
+    /// A listed ticker, or any CIK reference (`CIK0000320193`): entities without a ticker are
+    /// not in the index, their submissions are only looked up when the question is asked
+    fn is_known_ticker(&self, ticker: &str) -> bool {
+        self.ticker_index.by_ticker(ticker).is_some() || cik_reference(ticker).is_some()
     }
 }
 
@@ -63,18 +66,16 @@ impl Completer for ReplHelper {
         // Find the word being completed
         let (word_start, word) = find_word_at_pos(line, pos);
 
-        // Check if we're completing a ticker (starts with @)
+        // Check if we're completing a ticker (starts with @), by ticker or company name
         if let Some(ticker_part) = word.strip_prefix('@') {
-            let prefix = ticker_part.to_uppercase();
-
             // Generate completion candidates
             let candidates: Vec<Pair> = self
-                .ticker_map
-                .iter()
-                .filter(|(key, _)| key.starts_with(&prefix))
-                .map(|(_, (ticker, company, _))| {
-                    let display = format!("{} ({})", ticker.as_str(), company);
-                    let replacement = format!("@{}", ticker.as_str());
+                .ticker_index
+                .search(ticker_part, None, MAX_COMPLETIONS)
+                .into_iter()
+                .map(|hit| {
+                    let display = format!("{} ({})", hit.company.ticker, hit.company.name);
+                    let replacement = format!("@{}", hit.company.ticker);
                     Pair {
                         display,
                         replacement,
@@ -99,21 +100,22 @@ impl Highlighter for ReplHelper {
             // Add unhighlighted text before the ticker
             highlighted.push_str(&line[last_pos..start]);
 
-            // Find the end of the ticker symbol
+            // Find the end of the ticker symbol, share classes included (`@BRK.B`) but not
+            // the punctuation after it
+            let rest = &line[start + part.len()..];
             let end = start
                 + part.len()
-                + line[start + part.len()..]
-                    .find(|c: char| !c.is_alphanumeric() && c != '-')
-                    .unwrap_or(line[start + part.len()..].len());
+                + rest[..rest
+                    .find(|c: char| !c.is_alphanumeric() && !is_share_class_separator(c))
+                    .unwrap_or(rest.len())]
+                    .trim_end_matches(is_share_class_separator)
+                    .len();
 
             let ticker = &line[start..end];
 
             // Check if it's a valid ticker
             if let Some(ticker_str) = ticker.strip_prefix('@') {
-                let is_valid = self
-                    .ticker_map
-                    .values()
-                    .any(|(t, _, _)| t.as_str() == ticker_str.to_uppercase());
+                let is_valid = self.is_known_ticker(ticker_str);
 
                 // Use different colors for valid/invalid tickers
                 if is_valid {
@@ -176,14 +178,9 @@ impl Validator for ReplHelper {
         for word in words {
             if word.starts_with('@') {
                 let ticker_with_punctuation = word.strip_prefix("@").unwrap(); // Remove the '@' prefix
-                let ticker = ticker_with_punctuation
-                    .trim_end_matches(|c: char| !c.is_alphanumeric() && c != '-');
-                let ticker = ticker.to_uppercase();
-                if !&self
-                    .ticker_map
-                    .values()
-                    .any(|(t, _, _)| t.as_str() == ticker)
-                {
+                let ticker =
+                    ticker_with_punctuation.trim_end_matches(|c: char| !c.is_alphanumeric());
+                if !self.is_known_ticker(ticker) {
                     return Ok(ValidationResult::Invalid(Some(format!(
                         "Invalid ticker: {}",
                         ticker
@@ -196,7 +193,8 @@ impl Validator for ReplHelper {
         // For non-command input, require at least one valid ticker
         if !found_valid_ticker {
             return Ok(ValidationResult::Invalid(Some(
-                "Please include at least one ticker symbol (e.g. @AAPL)".to_string(),
+                "Please include at least one ticker symbol (e.g. @AAPL) or CIK (e.g. @CIK0000320193)"
+                    .to_string(),
             )));
         }
 
@@ -219,13 +217,9 @@ impl Hinter for ReplHelper {
         // Show hint for partial ticker
         if let Some(partial) = word.strip_prefix('@') {
             if !partial.is_empty() {
-                // Find first matching ticker
-                if let Some((_, (ticker, company, _))) = self
-                    .ticker_map
-                    .iter()
-                    .find(|(key, _)| key.starts_with(&partial.to_uppercase()))
-                {
-                    return Some(format!(" → {} ({})", ticker.as_str(), company));
+                // Best matching ticker or company name
+                if let Some(hit) = self.ticker_index.search(partial, None, 1).pop() {
+                    return Some(format!(" → {} ({})", hit.company.ticker, hit.company.name));
                 }
             }
         }
@@ -233,8 +227,13 @@ impl Hinter for ReplHelper {
         // Show command hints
         if line.starts_with('/') {
             return Some(match line {
+                "/c" => " → /cusip @<ticker> <cusip> ...".into(),
                 "/d" => " → /delete <conversation_id>".into(),
                 "/l" => " → /list".into(),
+                "/ho" => " → /holders @<ticker> [quarter end]".into(),
+                "/i" => " → /import <submissions.zip|companyfacts.zip|2024q1.zip|2024q3_form13f.zip> ...".into(),
+                "/p" => " → /profile @<ticker>".into(),
+                "/r" => " → /refresh [@<ticker> ...]".into(),
                 "/h" => " → /help".into(),
                 "/q" => " → /quit".into(),
                 _ => "".into(),
@@ -245,6 +244,11 @@ impl Hinter for ReplHelper {
     }
 }
 
+/// Characters that may join a ticker and its share class inside a word, as in `@BRK.B`
+fn is_share_class_separator(c: char) -> bool {
+    matches!(c, '-' | '.' | '/' | '_')
+}
+
 // Helper function to find word boundaries
 fn find_word_at_pos(line: &str, pos: usize) -> (usize, &str) {
     let start = line[..pos]
@@ -285,15 +289,19 @@ pub async fn handle_list_command(
         // Draw all conversations
         for (i, conv) in conversations.iter().enumerate() {
             let helper = (*rl.helper().unwrap()).clone();
-            let ticker_map = &helper.ticker_map;
+            let ticker_index = &helper.ticker_index;
 
             // Get company names for all tickers
             let ticker_info: Vec<String> = conv
                 .tickers
                 .iter()
                 .map(|ticker| {
-                    if let Some((_, company_name, _)) = ticker_map.get(ticker) {
-                        format!("{}: {}", ticker, company_name)
+                    let company = ticker_index.by_ticker(ticker).or_else(|| {
+                        cik_reference(ticker)
+                            .and_then(|cik| ticker_index.by_cik(&cik).into_iter().next())
+                    });
+                    if let Some(company) = company {
+                        format!("{}: {}", ticker, company.name)
                     } else {
                         ticker.clone()
                     }
@@ -485,6 +493,7 @@ pub async fn create_editor(
     conversation_manager: ConversationManager,
     chain_manager: Arc<ConversationChainManager>,
     llm: OpenAI<OpenAIConfig>,
+    edgar_client: &EdgarClient,
 ) -> Result<EditorWithHistory> {
     log::debug!("Creating rustyline editor configuration");
     let rustyline_config = RustylineConfig::builder()
@@ -508,7 +517,7 @@ pub async fn create_editor(
     }
 
     log::debug!("Creating ReplHelper");
-    let helper = ReplHelper::new().await.map_err(|e| {
+    let helper = ReplHelper::new(edgar_client).await.map_err(|e| {
         log::error!("Failed to create ReplHelper: {}", e);
         ReadlineError::Io(std::io::Error::new(
             std::io::ErrorKind::Other,
@@ -0,0 +1,90 @@
+// Freshness metadata for downloaded files. Each cached file gets a `<file>.meta.json` sidecar
+// recording when it was fetched and the validators (ETag / Last-Modified) the server sent,
+// so stale entries can be revalidated with a conditional request instead of a full download.
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CacheEntry {
+    pub url: String,
+    pub fetched_at: DateTime<Utc>,
+    pub etag: Option<String>,
+    pub last_modified: Option<String>,
+}
+
+impl CacheEntry {
+    pub fn is_fresh(&self, ttl: Duration) -> bool {
+        (Utc::now() - self.fetched_at)
+            .to_std()
+            .map_or(true, |age| age < ttl)
+    }
+}
+
+pub fn meta_path(filepath: &Path) -> PathBuf {
+    let mut name = filepath.as_os_str().to_owned();
+    name.push(".meta.json");
+    PathBuf::from(name)
+}
+
+pub fn load_entry(filepath: &Path) -> Option<CacheEntry> {
+    let content = fs::read_to_string(meta_path(filepath)).ok()?;
+    serde_json::from_str(&content).ok()
+}
+
+pub fn save_entry(filepath: &Path, entry: &CacheEntry) -> Result<()> {
+    fs::write(meta_path(filepath), serde_json::to_string_pretty(entry)?)?;
+    Ok(())
+}
+
+/// Whether the file is cached and was fetched less than `ttl` ago. Files downloaded before
+/// freshness tracking have no metadata and count as stale.
+pub fn is_fresh(filepath: &Path, ttl: Duration) -> bool {
+    filepath.exists() && load_entry(filepath).is_some_and(|entry| entry.is_fresh(ttl))
+}
+
+/// Mark a cached file as stale so the next fetch goes to the server. The validators are kept,
+/// so an unchanged file is still revalidated without downloading it again.
+pub fn invalidate(filepath: &Path) -> Result<()> {
+    if let Some(mut entry) = load_entry(filepath) {
+        entry.fetched_at = DateTime::UNIX_EPOCH;
+        save_entry(filepath, &entry)?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cache_freshness() {
+        let dir = tempfile::tempdir().unwrap();
+        let filepath = dir.path().join("CIK0000320193_0.json");
+        let ttl = Duration::from_secs(3600);
+
+        fs::write(&filepath, "{}").unwrap();
+        assert_eq!(
+            meta_path(&filepath),
+            dir.path().join("CIK0000320193_0.json.meta.json")
+        );
+        assert!(!is_fresh(&filepath, ttl));
+
+        let entry = CacheEntry {
+            url: "https://data.sec.gov/submissions/CIK0000320193.json".to_string(),
+            fetched_at: Utc::now(),
+            etag: Some("\"abc\"".to_string()),
+            last_modified: None,
+        };
+        save_entry(&filepath, &entry).unwrap();
+        assert!(is_fresh(&filepath, ttl));
+        assert!(!is_fresh(&filepath, Duration::ZERO));
+
+        invalidate(&filepath).unwrap();
+        assert!(!is_fresh(&filepath, ttl));
+        assert_eq!(load_entry(&filepath).unwrap().etag, entry.etag);
+    }
+}
@@ -1,9 +1,108 @@
+use super::cache::{self, CacheEntry};
+use super::rate_limit::RateLimiter;
 use anyhow::Result;
 use mime::Mime;
-use reqwest::{header::HeaderValue, Client};
+use reqwest::{
+    header::{HeaderMap, HeaderValue},
+    Client, Response, StatusCode,
+};
 use std::path::Path;
+use std::time::{Duration, SystemTime};
 use url::Url;
-use super::rate_limit::RateLimiter;
+
+/// Retries of a throttled (429) or failed (5xx) request before giving up
+const MAX_RETRIES: u32 = 5;
+const INITIAL_BACKOFF: Duration = Duration::from_secs(1);
+const MAX_BACKOFF: Duration = Duration::from_secs(60);
+
+fn is_retryable(status: StatusCode) -> bool {
+    status == StatusCode::TOO_MANY_REQUESTS || status.is_server_error()
+}
+
+/// Delay requested by a `Retry-After` header, given either in seconds or as an HTTP date
+fn retry_after(headers: &HeaderMap) -> Option<Duration> {
+    let value = headers
+        .get(reqwest::header::RETRY_AFTER)?
+        .to_str()
+        .ok()?
+        .trim();
+    if let Ok(seconds) = value.parse::<u64>() {
+        return Some(Duration::from_secs(seconds));
+    }
+    let date = chrono::DateTime::parse_from_rfc2822(value).ok()?;
+    (date.with_timezone(&chrono::Utc) - chrono::Utc::now())
+        .to_std()
+        .ok()
+}
+
+/// Exponential backoff with up to 50% random jitter, capped at `MAX_BACKOFF`
+fn backoff_delay(attempt: u32) -> Duration {
+    let base = INITIAL_BACKOFF
+        .saturating_mul(2u32.saturating_pow(attempt))
+        .min(MAX_BACKOFF);
+    // The clock's sub-second part is random enough to spread out concurrent retries
+    let nanos = SystemTime::now()
+        .duration_since(SystemTime::UNIX_EPOCH)
+        .map_or(0, |d| d.subsec_nanos());
+    let jitter = base.mul_f64(0.5 * (nanos as f64 / 1_000_000_000.0));
+    (base + jitter).min(MAX_BACKOFF)
+}
+
+/// Send a GET request through the rate limiter, retrying 429 and 5xx responses.
+/// A `Retry-After` delay pauses the whole limiter, so concurrent requests back off too.
+async fn send_with_retry(
+    client: &Client,
+    url: &Url,
+    user_agent: &str,
+    content_type: &Mime,
+    extra_headers: HeaderMap,
+    rate_limiter: &RateLimiter,
+) -> Result<Response> {
+    let content_type_value = HeaderValue::from_str(content_type.as_ref())?;
+    let mut attempt = 0;
+
+    loop {
+        // Acquire rate limit permit before making request
+        rate_limiter.acquire().await;
+
+        let response = client
+            .get(url.as_str())
+            .header(reqwest::header::USER_AGENT, user_agent)
+            .header(reqwest::header::ACCEPT_ENCODING, "gzip, deflate")
+            .header(reqwest::header::ACCEPT, &content_type_value)
+            .header(reqwest::header::CONTENT_TYPE, &content_type_value)
+            .headers(extra_headers.clone())
+            .send()
+            .await?;
+
+        let status = response.status();
+        if !is_retryable(status) || attempt >= MAX_RETRIES {
+            if attempt > 0 {
+                log::info!("{} returned {} after {} retries", url, status, attempt);
+            }
+            return Ok(response);
+        }
+
+        attempt += 1;
+        let delay = match retry_after(response.headers()) {
+            Some(delay) => {
+                let delay = delay.min(MAX_BACKOFF);
+                rate_limiter.pause(delay);
+                delay
+            }
+            None => backoff_delay(attempt - 1),
+        };
+        log::warn!(
+            "{} returned {}, retry {}/{} in {:.1}s",
+            url,
+            status,
+            attempt,
+            MAX_RETRIES,
+            delay.as_secs_f64()
+        );
+        tokio::time::sleep(delay).await;
+    }
+}
 
 pub async fn fetch_and_save(
     client: &Client,
@@ -14,20 +113,99 @@ pub async fn fetch_and_save(
     rate_limiter: &RateLimiter,
 ) -> Result<()> {
     log::debug!("Fetching URL: {}", url);
-    
-    // Acquire rate limit permit before making request
-    let _permit = rate_limiter.acquire().await;
 
-    let content_type_value = HeaderValue::from_str(content_type.as_ref())?;
-    let request = client
-        .get(url.as_str())
-        .header(reqwest::header::USER_AGENT, user_agent)
-        .header(reqwest::header::ACCEPT_ENCODING, "gzip, deflate")
-        .header(reqwest::header::ACCEPT, &content_type_value)
-        .header(reqwest::header::CONTENT_TYPE, &content_type_value);
+    let response = send_with_retry(
+        client,
+        url,
+        user_agent,
+        &content_type,
+        HeaderMap::new(),
+        rate_limiter,
+    )
+    .await?;
+    save_response(response, filepath).await
+}
+
+/// Fetch `url` into `filepath` unless the cached copy is younger than `ttl`.
+/// Stale copies are revalidated with `If-None-Match` / `If-Modified-Since`, so an unchanged
+/// resource costs a 304 instead of a download. Returns whether new content was saved.
+pub async fn fetch_if_stale(
+    client: &Client,
+    url: &Url,
+    filepath: &Path,
+    user_agent: &str,
+    content_type: Mime,
+    rate_limiter: &RateLimiter,
+    ttl: Duration,
+) -> Result<bool> {
+    if cache::is_fresh(filepath, ttl) {
+        log::debug!("Using fresh cached copy of {} at {:?}", url, filepath);
+        return Ok(false);
+    }
+
+    let cached = filepath
+        .exists()
+        .then(|| cache::load_entry(filepath))
+        .flatten();
+    let mut headers = HeaderMap::new();
+    if let Some(entry) = &cached {
+        if let Some(etag) = entry
+            .etag
+            .as_deref()
+            .and_then(|v| HeaderValue::from_str(v).ok())
+        {
+            headers.insert(reqwest::header::IF_NONE_MATCH, etag);
+        }
+        if let Some(modified) = entry
+            .last_modified
+            .as_deref()
+            .and_then(|v| HeaderValue::from_str(v).ok())
+        {
+            headers.insert(reqwest::header::IF_MODIFIED_SINCE, modified);
+        }
+    }
 
-    let response = request.send().await?;
+    log::debug!("Fetching URL: {}", url);
+    let response = send_with_retry(
+        client,
+        url,
+        user_agent,
+        &content_type,
+        headers,
+        rate_limiter,
+    )
+    .await?;
+
+    let header = |name| {
+        response
+            .headers()
+            .get(name)
+            .and_then(|v: &HeaderValue| v.to_str().ok())
+            .map(String::from)
+    };
+    let mut entry = CacheEntry {
+        url: url.to_string(),
+        fetched_at: chrono::Utc::now(),
+        etag: header(reqwest::header::ETAG),
+        last_modified: header(reqwest::header::LAST_MODIFIED),
+    };
+
+    if response.status() == StatusCode::NOT_MODIFIED {
+        if let Some(cached) = cached {
+            log::debug!("{} not modified since last fetch", url);
+            entry.etag = entry.etag.or(cached.etag);
+            entry.last_modified = entry.last_modified.or(cached.last_modified);
+            cache::save_entry(filepath, &entry)?;
+            return Ok(false);
+        }
+    }
+
+    save_response(response, filepath).await?;
+    cache::save_entry(filepath, &entry)?;
+    Ok(true)
+}
 
+async fn save_response(response: Response, filepath: &Path) -> Result<()> {
     log::debug!("Response status: {}", response.status());
     log::debug!("Response headers: {:?}", response.headers());
 
@@ -70,3 +248,33 @@ pub async fn fetch_and_save(
 
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_retry_delays() {
+        let mut headers = HeaderMap::new();
+        assert_eq!(retry_after(&headers), None);
+        headers.insert(reqwest::header::RETRY_AFTER, HeaderValue::from_static("7"));
+        assert_eq!(retry_after(&headers), Some(Duration::from_secs(7)));
+        headers.insert(
+            reqwest::header::RETRY_AFTER,
+            HeaderValue::from_static("Wed, 21 Oct 2015 07:28:00 GMT"),
+        );
+        // A date in the past means no wait is needed
+        assert_eq!(retry_after(&headers), None);
+
+        for attempt in 0..10 {
+            let delay = backoff_delay(attempt);
+            assert!(
+                delay
+                    >= INITIAL_BACKOFF
+                        .saturating_mul(2u32.pow(attempt))
+                        .min(MAX_BACKOFF)
+            );
+            assert!(delay <= MAX_BACKOFF);
+        }
+    }
+}
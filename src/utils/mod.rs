@@ -1,3 +1,4 @@
+pub mod cache;
 pub mod dirs;
 pub mod http;
 pub mod progress;
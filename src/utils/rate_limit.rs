@@ -1,19 +1,73 @@
-use std::sync::Arc;
-use tokio::sync::Semaphore;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::time::Instant;
 
-#[derive(Clone)]
+/// Token bucket limiting the number of requests per second sent to a host.
+/// The bucket holds at most one second worth of requests, so bursts stay within the rate.
+#[derive(Clone, Debug)]
 pub struct RateLimiter {
-    semaphore: Arc<Semaphore>,
+    requests_per_second: f64,
+    bucket: Arc<Mutex<Bucket>>,
+}
+
+#[derive(Debug)]
+struct Bucket {
+    tokens: f64,
+    last_refill: Instant,
+    /// Set when the server asked us to back off; no permits are handed out before it
+    paused_until: Option<Instant>,
 }
 
 impl RateLimiter {
-    pub fn new(max_concurrent: usize) -> Self {
+    pub fn new(requests_per_second: u32) -> Self {
+        let requests_per_second = requests_per_second.max(1) as f64;
         RateLimiter {
-            semaphore: Arc::new(Semaphore::new(max_concurrent)),
+            requests_per_second,
+            bucket: Arc::new(Mutex::new(Bucket {
+                tokens: requests_per_second,
+                last_refill: Instant::now(),
+                paused_until: None,
+            })),
+        }
+    }
+
+    /// Wait until a request may be sent
+    pub async fn acquire(&self) {
+        loop {
+            let wait = {
+                let mut bucket = self.bucket.lock().expect("Rate limiter lock poisoned");
+                let now = Instant::now();
+
+                match bucket.paused_until {
+                    Some(until) if until > now => until - now,
+                    _ => {
+                        bucket.paused_until = None;
+                        let elapsed = now.duration_since(bucket.last_refill).as_secs_f64();
+                        bucket.tokens = (bucket.tokens + elapsed * self.requests_per_second)
+                            .min(self.requests_per_second);
+                        bucket.last_refill = now;
+
+                        if bucket.tokens >= 1.0 {
+                            bucket.tokens -= 1.0;
+                            return;
+                        }
+                        Duration::from_secs_f64((1.0 - bucket.tokens) / self.requests_per_second)
+                    }
+                }
+            };
+            tokio::time::sleep(wait).await;
         }
     }
 
-    pub async fn acquire(&self) -> tokio::sync::SemaphorePermit {
-        self.semaphore.acquire().await.expect("Semaphore closed")
+    /// Hold back every request through this limiter for `delay`, e.g. after a 429 response
+    pub fn pause(&self, delay: Duration) {
+        let mut bucket = self.bucket.lock().expect("Rate limiter lock poisoned");
+        let until = Instant::now() + delay;
+        if bucket.paused_until.is_none_or(|current| current < until) {
+            bucket.paused_until = Some(until);
+            // Start refilling only once the pause is over
+            bucket.last_refill = until;
+        }
+        bucket.tokens = 0.0;
     }
 }
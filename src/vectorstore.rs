@@ -44,6 +44,46 @@ pub async fn store_document(
     store_documents(documents, store).await
 }
 
+/// Store a document that was split into sections, so no chunk spans two sections.
+///
+/// Each section comes with the metadata that is specific to it (e.g. `item`,
+/// `section_title`), which is merged over the document metadata. Chunk indices
+/// run across the whole document.
+pub async fn store_sectioned_document(
+    sections: Vec<(String, HashMap<String, Value>)>,
+    metadata: HashMap<String, Value>,
+    store: &dyn VectorStore<Options = PgOptions>,
+) -> Result<()> {
+    let chunks: Vec<(String, HashMap<String, Value>)> = sections
+        .into_iter()
+        .flat_map(|(content, section_metadata)| {
+            let mut chunk_metadata = metadata.clone();
+            chunk_metadata.extend(section_metadata);
+            chunk_document(content)
+                .into_iter()
+                .map(move |chunk| (chunk, chunk_metadata.clone()))
+        })
+        .collect();
+
+    let total_chunks = chunks.len();
+    let documents = chunks
+        .into_iter()
+        .enumerate()
+        .map(|(i, (chunk, mut chunk_metadata))| {
+            chunk_metadata.insert("chunk_index".to_string(), serde_json::json!(i));
+            chunk_metadata.insert("total_chunks".to_string(), serde_json::json!(total_chunks));
+
+            Document {
+                page_content: chunk,
+                metadata: chunk_metadata,
+                score: 0.0,
+            }
+        })
+        .collect();
+
+    store_documents(documents, store).await
+}
+
 fn chunk_document(content: String) -> Vec<String> {
     content
         .chars()